@@ -24,6 +24,13 @@ pkgs.callPackage (
       pkgs.git
       pkgs.mercurial
       pkgs.jujutsu
+      pkgs.subversion
+      pkgs.fossil
+      pkgs.pijul
+      pkgs.breezy
+      pkgs.darcs
+      pkgs.cvs
+      pkgs.sapling
       pkgs.cargo-llvm-cov # for test coverage
       pkgs.grcov # for test coverage
       pkgs.cargo-tarpaulin # for test coverage
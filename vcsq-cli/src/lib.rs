@@ -4,12 +4,13 @@
 //! Example: `vcsq is-clean .`
 //!
 //! See `--help` for complete doc, and README at <https://gitlab.com/jzacsh/vcsq> for more.
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::{json, Value};
 use std::io;
 use std::num::NonZero;
 use thiserror::Error;
 use vcsq_lib::plexer;
-use vcsq_lib::repo::{Driver, DriverError, QueryDir};
+use vcsq_lib::repo::{Driver, DriverError, FileStatus, QueryDir};
 
 /// Top-Level instructions into which we parse the resluts of CLI args.
 #[derive(Parser, Debug)]
@@ -18,17 +19,129 @@ use vcsq_lib::repo::{Driver, DriverError, QueryDir};
     version,
     about = "vcs queries in rust",
     long_about = "vcsq is a rust CLI providing Version Control System (VCS) inspection, without you
-needing to know each VCS's proprietary incantations."
+needing to know each VCS's proprietary incantations.",
+    after_long_help = EXIT_CODES_HELP
 )]
 pub struct MainArgs {
     /// Directory for which you'd like to ask VCS questions.
     #[arg(short, long)]
     pub dir: Option<QueryDir>,
 
+    /// How to print answers to stdout (errors are always plain text on stderr).
+    #[arg(long, value_enum, global = true, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Treat dir as a repo of this brand (eg: "Mercurial"; case-insensitive), rather than detecting
+    /// which brand it is.
+    #[arg(long, global = true, env = "VCSQ_BRAND")]
+    pub brand: Option<String>,
+
     #[command(subcommand)]
     pub query: Option<QueryCmd>,
 }
 
+/// Output encodings of the CLI's answers.
+///
+/// JSON schema per subcommand (field order is stable; new fields may be appended):
+/// - `brand`: `{"brand": BRAND}`, where BRAND is the name of a `VcsBrand` (eg: `"Git"`)
+/// - `brand --all`: list of `{"brand": BRAND}`
+/// - `root`: `{"root": PATH}`
+/// - `is-clean`: `{"clean": BOOL}` (exit code is unchanged)
+/// - `current-name`: `{"name": NAME}`
+/// - `current-id`, `parent-id`, `child-id`: `{"id": ID}` (`current-id`'s ID is null for a repo with
+///   no history yet, when passed `--empty-history=empty`)
+/// - `parent-name`: `{"id": ID, "name": NAME, "distance": INT}`
+/// - `child-ids`: list of `{"id": ID}`
+/// - `tracked-files`, `dirty-files`, `current-files`: list of `{"path": PATH}`
+/// - `dirty-files --status`: list of `{"kind": KIND, "path": PATH, "original_path": PATH|null,
+///   "staged": BOOL|null}` where `KIND` is one of "modified", "added", "deleted", "renamed",
+///   "copied", "untracked", "conflicted"
+/// - `check-health` (or no subcommand): list of `{"brand": BRAND, "ok": BOOL, "output":
+///   STRING|null, "error": STRING|null}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable plain text, one answer per line.
+    Text,
+
+    /// A single JSON document; list answers are printed as one JSON array.
+    Json,
+
+    /// Like json, but list answers are printed as one JSON document per line (JSON Lines).
+    Jsonl,
+}
+
+/// What `current-id` answers for a repo that has no commits yet (see
+/// [`DriverError::EmptyHistory`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EmptyHistoryOutput {
+    /// Print [`EMPTY_HISTORY_SENTINEL`] as though it were the current ID.
+    Sentinel,
+
+    /// Print nothing (or a null ID for json formats), and exit zero.
+    Empty,
+
+    /// Print nothing, and exit with the empty-history exit code.
+    Error,
+}
+
+/// Placeholder ID `current-id` prints for a repo with no commits yet, regardless of its brand.
+///
+/// NOTE: this is the 50 zeros `current-id` has always printed for an empty git repo, so scripts
+/// comparing against it keep working (even though it's longer than any brand's real IDs).
+pub const EMPTY_HISTORY_SENTINEL: &str = "00000000000000000000000000000000000000000000000000";
+
+/// Generic failure, and also the exit code of queries whose answer is a negative (eg: `is-clean`
+/// when the repo is dirty).
+pub const EXIT_FAILURE: u8 = 1;
+
+/// Invalid usage of the CLI (same as clap's own usage errors).
+pub const EXIT_USAGE: u8 = 2;
+
+/// See [`DriverError::NotARepository`].
+pub const EXIT_NOT_A_REPOSITORY: u8 = 3;
+
+/// See [`DriverError::VcsBinaryMissing`].
+pub const EXIT_VCS_BINARY_MISSING: u8 = 4;
+
+/// See [`DriverError::RepoDirty`].
+pub const EXIT_REPO_DIRTY: u8 = 5;
+
+/// See [`DriverError::RepoNotDirty`].
+pub const EXIT_REPO_NOT_DIRTY: u8 = 6;
+
+/// See [`DriverError::EmptyHistory`].
+pub const EXIT_EMPTY_HISTORY: u8 = 7;
+
+/// See [`DriverError::NoParent`].
+pub const EXIT_NO_PARENT: u8 = 8;
+
+/// See [`DriverError::NoChild`].
+pub const EXIT_NO_CHILD: u8 = 9;
+
+/// See [`DriverError::MultipleChildren`].
+pub const EXIT_MULTIPLE_CHILDREN: u8 = 10;
+
+/// See [`DriverError::UnexpectedOutput`].
+pub const EXIT_UNEXPECTED_OUTPUT: u8 = 11;
+
+/// See [`DriverError::Unsupported`].
+pub const EXIT_UNSUPPORTED: u8 = 12;
+
+const EXIT_CODES_HELP: &str = "Exit codes:
+  0   success
+  1   failure (or a negative answer, eg: is-clean on a dirty repo)
+  2   usage error
+  3   dir is not a repo of any known VCS (or of the --brand given)
+  4   VCS binary missing from $PATH
+  5   repo is dirty (see --dirty-ok flags)
+  6   repo is not dirty (see --clean-ok flags)
+  7   repo has no history yet
+  8   current revision has no parent
+  9   current revision has no children
+  10  current revision has multiple children
+  11  VCS printed output vcsq couldn't interpret
+  12  query has no equivalent in the repo's brand of VCS";
+
 #[derive(Error, Debug)]
 enum CliError {
     #[error("usage error: {0}")]
@@ -41,6 +154,36 @@ enum CliError {
     Unknown(String),
 }
 
+impl CliError {
+    /// Distinct exit code for each kind of failure (see `EXIT_CODES_HELP`).
+    fn exit_code(&self) -> u8 {
+        match self {
+            CliError::Usage(_) => EXIT_USAGE,
+            CliError::Plexing(driver_error) => match driver_error {
+                DriverError::NotARepository { .. } | DriverError::NotOfBrand { .. } => {
+                    EXIT_NOT_A_REPOSITORY
+                }
+                DriverError::UnknownBrand { .. } => EXIT_USAGE,
+                DriverError::VcsBinaryMissing { .. } => EXIT_VCS_BINARY_MISSING,
+                DriverError::RepoDirty => EXIT_REPO_DIRTY,
+                DriverError::RepoNotDirty => EXIT_REPO_NOT_DIRTY,
+                DriverError::EmptyHistory => EXIT_EMPTY_HISTORY,
+                DriverError::NoParent => EXIT_NO_PARENT,
+                DriverError::NoChild => EXIT_NO_CHILD,
+                DriverError::MultipleChildren => EXIT_MULTIPLE_CHILDREN,
+                DriverError::UnexpectedOutput { .. } => EXIT_UNEXPECTED_OUTPUT,
+                DriverError::Unsupported(_) => EXIT_UNSUPPORTED,
+                DriverError::Directory(_)
+                | DriverError::Command { .. }
+                | DriverError::Stderr { .. }
+                | DriverError::Library { .. }
+                | DriverError::RootName(_) => EXIT_FAILURE,
+            },
+            CliError::Unknown(_) => EXIT_FAILURE,
+        }
+    }
+}
+
 impl MainArgs {
     /// Alternative to clap's parse, just so we can handle defaults
     ///
@@ -57,7 +200,7 @@ impl MainArgs {
                     "require either subcmd with a query or a direct --dir".into(),
                 ))?
                 .clone();
-            Ok(QueryCmd::Brand { dir })
+            Ok(QueryCmd::Brand { dir, all: false })
         }
     }
 }
@@ -71,7 +214,14 @@ impl MainArgs {
 pub enum QueryCmd {
     /// Prints the brand of the VCS repo, or exits non-zero if it's not a known VCS repo.
     #[command(arg_required_else_help = true)]
-    Brand { dir: QueryDir },
+    Brand {
+        dir: QueryDir,
+
+        /// Also print any other brands colocated at the repo's root (eg: Git, beneath a Jujutsu
+        /// repo colocated with git), one per line after the brand in use.
+        #[arg(long, default_value_t = false)]
+        all: bool,
+    },
 
     /// Prints the root dir of the repo
     #[command(arg_required_else_help = true)]
@@ -92,12 +242,15 @@ pub enum QueryCmd {
         /// Whether to be silent about any answers being flawed, in the event `IsClean` is false.
         #[arg(long, default_value_t = false)]
         dirty_ok: bool,
+
+        /// What to answer if the repo has no commits yet.
+        #[arg(long, value_enum, default_value_t = EmptyHistoryOutput::Sentinel)]
+        empty_history: EmptyHistoryOutput,
     },
 
     /// Print the VCS repo's current human-readable revision (eg: branch or tag in git, bookmark in
-    /// jj)
+    /// jj). Non-zero exit with no stderr output indicates there isn't one.
     #[command(arg_required_else_help = true)]
-    #[cfg(debug_assertions)]
     CurrentName {
         dir: QueryDir,
 
@@ -107,26 +260,30 @@ pub enum QueryCmd {
     },
 
     /// Print the VCS repo's parent revision ID to the current point in history (eg: rev in
-    /// Mercurial, ref in git, etc).
+    /// Mercurial, ref in git, etc). For merges this is the first parent, and at the root of history
+    /// this exits non-zero.
     #[command(arg_required_else_help = true)]
-    #[cfg(debug_assertions)]
     ParentId { dir: QueryDir },
 
     /// Print the VCS repo's parent revision's human-readable revision name for the first parent it
     /// finds with one, or until it has stepped --max steps. Non-zero exit with no stderr output
     /// indicates one wasn't found.
+    ///
+    /// Only first parents are walked (eg: a merge's second parent is never considered).
     #[command(arg_required_else_help = true)]
-    #[cfg(debug_assertions)]
     ParentName {
         dir: QueryDir,
 
         /// Max number of parents back to walk when seeking a parent with a hand-written ref name.
         max: NonZero<u64>,
+
+        /// Also print how many steps back the name was found, as a second tab-separated column.
+        #[arg(long, default_value_t = false)]
+        distance: bool,
     },
 
     /// Print the VCS repo's descendent revision IDs, if any exit.
     #[command(arg_required_else_help = true)]
-    #[cfg(debug_assertions)]
     ChildIds {
         dir: QueryDir,
 
@@ -135,9 +292,9 @@ pub enum QueryCmd {
         max: u64,
     },
 
-    /// Print the VCS repo's descendent revision ID, or error if more than one exists.
+    /// Print the VCS repo's descendent revision ID, or error if more than one exists (or if none
+    /// do).
     #[command(arg_required_else_help = true)]
-    #[cfg(debug_assertions)]
     ChildId { dir: QueryDir },
 
     /// Lists filepaths tracked by this repo, ignoring the state of the repo (ie: any "staged"
@@ -153,26 +310,56 @@ pub enum QueryCmd {
         dir: QueryDir,
         #[arg(long, default_value_t = false)]
         clean_ok: bool,
+
+        /// Whether to prefix each file with a brand-neutral status code, in the form `XS path`
+        /// where `X` is one of: `M` (modified), `A` (added), `D` (deleted), `R` (renamed), `C`
+        /// (copied), `?` (untracked), `U` (conflicted); and `S` is `+` if the change is staged
+        /// (only meaningful for VCS with an index, like git) or a space otherwise. Renames and
+        /// copies are printed as `XS original -> path`. A file may be listed twice (eg: once
+        /// staged and once unstaged).
+        #[arg(long, default_value_t = false)]
+        status: bool,
         // TODO: (feature) add flag like "--exists" to only show files that are currently present
         // (eg: so this can be piped right to an editor's args).
     },
 
-    /// Prints what files were touched by the `CurrentId`
+    /// Prints what files were touched by the `CurrentId` (ie: relative to its first parent, unless
+    /// --base is given).
     #[command(arg_required_else_help = true)]
-    #[cfg(debug_assertions)]
     CurrentFiles {
         dir: QueryDir,
 
         /// Whether to be silent about any answers being flawed, in the event `IsClean` is false.
         #[arg(long, default_value_t = false)]
         dirty_ok: bool,
-        // TODO: (feature) allow an optional Id or Name  (ref or bookmark) of which to compare
-        // (instead of just the default which is "parent commit").
 
+        /// ID or name (eg: ref or bookmark) of a point in history to compare against, instead of
+        /// the parent of `CurrentId`.
+        #[arg(long)]
+        base: Option<String>,
         // TODO: (feature) implement subcommand here, eg: enum {diffstat, diff, files} (unified
         // with IsClean)
     },
 
+    /// Prints a one-line summary of the repo (eg: for a shell's `$PS1`), rendered from a template
+    /// of these placeholders:
+    /// - `%b`: brand of the VCS (as printed by `Brand`)
+    /// - `%i`: shortened `CurrentId`, or nothing if the repo has no history yet
+    /// - `%n`: current human-readable name (as printed by `CurrentName`), or nothing
+    /// - `%m`: `+` if the repo is dirty, or nothing
+    /// - `%a`: `^N` and `vN` for commits ahead of and behind upstream (each omitted if zero)
+    /// - `%o`: name of any operation in progress (eg: "rebase" or "merge"), or nothing
+    /// - `%%`: a literal `%`
+    ///
+    /// Only the placeholders used are ever queried.
+    #[command(arg_required_else_help = true)]
+    Prompt {
+        dir: QueryDir,
+
+        #[arg(short = 'f', long, default_value = "%b:%n%m")]
+        template: String,
+    },
+
     /// Prints any system/$PATH info that might be useful for debugging issues this binary might
     /// have on your system.
     CheckHealth,
@@ -187,32 +374,228 @@ impl QueryCmd {
     // we can rely on its presence?
     fn dir_path(&self) -> Option<&QueryDir> {
         match self {
-            QueryCmd::Brand { dir }
+            QueryCmd::Brand { dir, all: _ }
             | QueryCmd::Root { dir }
             | QueryCmd::IsClean { dir }
-            | QueryCmd::DirtyFiles { dir, clean_ok: _ }
+            | QueryCmd::DirtyFiles {
+                dir,
+                clean_ok: _,
+                status: _,
+            }
             | QueryCmd::TrackedFiles { dir }
-            | QueryCmd::CurrentId { dir, dirty_ok: _ } => Some(dir),
-            QueryCmd::CheckHealth => None,
-            #[cfg(debug_assertions)]
-            QueryCmd::CurrentName { dir, dirty_ok: _ }
+            | QueryCmd::CurrentId {
+                dir,
+                dirty_ok: _,
+                empty_history: _,
+            }
             | QueryCmd::ParentId { dir }
-            | QueryCmd::ParentName { dir, max: _ }
+            | QueryCmd::ParentName {
+                dir,
+                max: _,
+                distance: _,
+            }
             | QueryCmd::ChildIds { dir, max: _ }
             | QueryCmd::ChildId { dir }
-            | QueryCmd::CurrentFiles { dir, dirty_ok: _ } => Some(dir),
+            | QueryCmd::Prompt { dir, template: _ }
+            | QueryCmd::CurrentName { dir, dirty_ok: _ }
+            | QueryCmd::CurrentFiles {
+                dir,
+                dirty_ok: _,
+                base: _,
+            } => Some(dir),
+            QueryCmd::CheckHealth => None,
         }
     }
 }
 
+/// Renders `status` in the brand-neutral format documented on `QueryCmd::DirtyFiles`.
+fn format_status(status: &FileStatus) -> String {
+    let stage = if status.staged == Some(true) {
+        '+'
+    } else {
+        ' '
+    };
+    match &status.original_path {
+        Some(original) => format!(
+            "{}{stage} {} -> {}",
+            status.kind.code(),
+            original.display(),
+            status.path.display()
+        ),
+        None => format!("{}{stage} {}", status.kind.code(), status.path.display()),
+    }
+}
+
+/// Writes a single answer to `stdout`, as either `text` or `json` depending on `format`.
+fn emit(stdout: &mut dyn io::Write, format: OutputFormat, text: &str, json: &Value) {
+    let line = match format {
+        OutputFormat::Text => text.to_string(),
+        OutputFormat::Json | OutputFormat::Jsonl => json.to_string(),
+    };
+    writeln!(stdout, "{line}").unwrap_or_else(|_| panic!("failed stdout write of: {line}"));
+}
+
+/// Writes a list of answers to `stdout`, where each entry is a pair of its text and json forms.
+fn emit_list(stdout: &mut dyn io::Write, format: OutputFormat, entries: Vec<(String, Value)>) {
+    match format {
+        OutputFormat::Text | OutputFormat::Jsonl => {
+            for (text, json) in entries {
+                emit(stdout, format, &text, &json);
+            }
+        }
+        OutputFormat::Json => {
+            let list = Value::Array(entries.into_iter().map(|(_, json)| json).collect());
+            emit(stdout, format, "", &list);
+        }
+    }
+}
+
+fn path_entries(files: &[QueryDir]) -> Vec<(String, Value)> {
+    files
+        .iter()
+        .map(|file| (file.display().to_string(), json!({ "path": file })))
+        .collect()
+}
+
+/// Number of characters of a `CurrentId` to print for the `%i` prompt placeholder.
+const PROMPT_SHORT_ID_LEN: usize = 12;
+
+/// Parsed pieces of a `QueryCmd::Prompt` template.
+#[derive(Debug, PartialEq)]
+enum PromptSegment {
+    Literal(String),
+    Brand,
+    ShortId,
+    Name,
+    Dirty,
+    AheadBehind,
+    Operation,
+}
+
+impl PromptSegment {
+    fn parse_template(template: &str) -> Result<Vec<PromptSegment>, CliError> {
+        let mut segments = vec![];
+        let mut literal = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                literal.push(c);
+                continue;
+            }
+            let placeholder = match chars.next() {
+                Some('%') => {
+                    literal.push('%');
+                    continue;
+                }
+                Some('b') => PromptSegment::Brand,
+                Some('i') => PromptSegment::ShortId,
+                Some('n') => PromptSegment::Name,
+                Some('m') => PromptSegment::Dirty,
+                Some('a') => PromptSegment::AheadBehind,
+                Some('o') => PromptSegment::Operation,
+                Some(unknown) => {
+                    return Err(CliError::Usage(format!(
+                        "unknown prompt placeholder: %{unknown}"
+                    )))
+                }
+                None => {
+                    return Err(CliError::Usage(
+                        "prompt template ends with a lone '%'".to_string(),
+                    ))
+                }
+            };
+            if !literal.is_empty() {
+                segments.push(PromptSegment::Literal(std::mem::take(&mut literal)));
+            }
+            segments.push(placeholder);
+        }
+        if !literal.is_empty() {
+            segments.push(PromptSegment::Literal(literal));
+        }
+        Ok(segments)
+    }
+}
+
+/// Renders `template` (see `QueryCmd::Prompt`), querying `plexer` at most once per placeholder
+/// (or just once for all of `%i`, `%n` and `%m`).
+fn render_prompt(plexer: &plexer::Repo, template: &str) -> Result<String, CliError> {
+    let segments = PromptSegment::parse_template(template)?;
+    let wants = |segment: &PromptSegment| segments.contains(segment);
+
+    // One snapshot answers all of these at once.
+    let snapshot = if [
+        PromptSegment::ShortId,
+        PromptSegment::Name,
+        PromptSegment::Dirty,
+    ]
+    .iter()
+    .any(wants)
+    {
+        Some(plexer.snapshot()?)
+    } else {
+        None
+    };
+    let short_id: String = snapshot
+        .as_ref()
+        .and_then(|snapshot| snapshot.id.as_id())
+        .map(|id| id.chars().take(PROMPT_SHORT_ID_LEN).collect())
+        .unwrap_or_default();
+    let name = snapshot.as_ref().and_then(|snapshot| snapshot.name.clone());
+    let dirty = snapshot
+        .as_ref()
+        .is_some_and(|snapshot| !snapshot.is_clean());
+    let ahead_behind = if wants(&PromptSegment::AheadBehind) {
+        plexer.ahead_behind()?
+    } else {
+        None
+    };
+    let operation = if wants(&PromptSegment::Operation) {
+        plexer.operation()?
+    } else {
+        None
+    };
+
+    let mut prompt = String::new();
+    for segment in &segments {
+        match segment {
+            PromptSegment::Literal(literal) => prompt.push_str(literal),
+            PromptSegment::Brand => prompt.push_str(plexer.brand.as_ref()),
+            PromptSegment::ShortId => prompt.push_str(&short_id),
+            PromptSegment::Name => prompt.push_str(name.as_deref().unwrap_or_default()),
+            PromptSegment::Dirty => {
+                if dirty {
+                    prompt.push('+');
+                }
+            }
+            PromptSegment::AheadBehind => {
+                let Some(counts) = ahead_behind else {
+                    continue;
+                };
+                if counts.ahead > 0 {
+                    prompt.push_str(&format!("^{}", counts.ahead));
+                }
+                if counts.behind > 0 {
+                    prompt.push_str(&format!("v{}", counts.behind));
+                }
+            }
+            PromptSegment::Operation => {
+                prompt.push_str(operation.map(|o| o.name()).unwrap_or_default());
+            }
+        }
+    }
+    Ok(prompt)
+}
+
 struct PlexerQuery<'a> {
     plexer: plexer::Repo,
     cli: QueryCmd,
+    format: OutputFormat,
     stdout: &'a mut dyn io::Write,
 }
 
 impl<'a> PlexerQuery<'a> {
     fn new(
+        registry: &plexer::Registry,
         args: &'a MainArgs,
         stdout: &'a mut dyn io::Write,
     ) -> Result<Option<PlexerQuery<'a>>, CliError> {
@@ -225,76 +608,182 @@ impl<'a> PlexerQuery<'a> {
                 "dir must be a readable directory".to_string(),
             ));
         }
-        let plexer = plexer::Repo::new_driver(&dir)?;
+        let plexer = match &args.brand {
+            Some(name) => {
+                let brand = registry
+                    .brands()
+                    .into_iter()
+                    .find(|brand| brand.as_ref().eq_ignore_ascii_case(name))
+                    .unwrap_or_else(|| plexer::VcsBrand::Other(name.clone()));
+                registry.open_as(&dir, &brand)?
+            }
+            None => registry.new_driver(&dir)?,
+        };
         Ok(Some(PlexerQuery {
             plexer,
             cli: query,
+            format: args.format,
             stdout,
         }))
     }
 
     pub fn handle_query(&mut self) -> Result<u8, CliError> {
         match self.cli {
-            QueryCmd::Brand { dir: _ } => {
-                writeln!(self.stdout, "{:?}", self.plexer.brand)
-                    .unwrap_or_else(|_| panic!("failed stdout write of: {:?}", self.plexer.brand));
+            QueryCmd::Brand { dir: _, all: false } => {
+                let brand = &self.plexer.brand;
+                emit(
+                    self.stdout,
+                    self.format,
+                    brand.as_ref(),
+                    &json!({ "brand": brand }),
+                );
+            }
+            QueryCmd::Brand { dir: _, all: true } => {
+                let brands = std::iter::once(&self.plexer.brand).chain(&self.plexer.also);
+                emit_list(
+                    self.stdout,
+                    self.format,
+                    brands
+                        .map(|brand| (brand.as_ref().to_string(), json!({ "brand": brand })))
+                        .collect(),
+                );
             }
             QueryCmd::Root { dir: _ } => {
                 let root_path = self.plexer.root()?;
                 let dir_path = root_path.as_path().to_str().ok_or_else(|| {
                     CliError::Unknown(format!("vcs generated invalid unicode: {root_path:?}"))
                 })?;
-                writeln!(self.stdout, "{dir_path}")
-                    .unwrap_or_else(|_| panic!("failed stdout write of: {dir_path}"));
+                emit(
+                    self.stdout,
+                    self.format,
+                    dir_path,
+                    &json!({ "root": dir_path }),
+                );
             }
             QueryCmd::IsClean { dir: _ } => {
                 let is_clean = self.plexer.is_clean().map_err(CliError::Plexing)?;
+                if self.format != OutputFormat::Text {
+                    emit(self.stdout, self.format, "", &json!({ "clean": is_clean }));
+                }
                 return Ok(u8::from(!is_clean));
             }
             QueryCmd::CheckHealth => panic!("bug: PlexerQuery() should not be constructed for the generalized CheckHealth query"),
             QueryCmd::CurrentId {
                 dir: _,
                 dirty_ok,
+                empty_history,
             } => {
-                let current_id = self.plexer.current_ref_id(dirty_ok)?;
-                writeln!(self.stdout, "{current_id}").unwrap_or_else(|_| {
-                    panic!("failed stdout write of: {current_id}")
-                });
+                let current_id = match self.plexer.current_ref_id(dirty_ok) {
+                    Ok(current_id) => current_id,
+                    Err(DriverError::EmptyHistory) => match empty_history {
+                        EmptyHistoryOutput::Sentinel => EMPTY_HISTORY_SENTINEL.to_string(),
+                        EmptyHistoryOutput::Empty => {
+                            if self.format != OutputFormat::Text {
+                                emit(self.stdout, self.format, "", &json!({ "id": null }));
+                            }
+                            return Ok(0);
+                        }
+                        EmptyHistoryOutput::Error => return Err(DriverError::EmptyHistory.into()),
+                    },
+                    Err(err) => return Err(err.into()),
+                };
+                emit(self.stdout, self.format, &current_id, &json!({ "id": current_id }));
             },
-            #[cfg(debug_assertions)]
-            QueryCmd::CurrentName {
+            QueryCmd::ParentId { dir: _ } => {
+                let parent_id = self.plexer.parent_ref_id()?;
+                emit(self.stdout, self.format, &parent_id, &json!({ "id": parent_id }));
+            },
+            QueryCmd::ParentName {
                 dir: _,
-                dirty_ok: _,
+                max,
+                distance,
+            } => {
+                let Some(ancestor) = self.plexer.first_ancestor_ref_name(Some(max))? else {
+                    return Ok(EXIT_FAILURE);
+                };
+                let line = if distance {
+                    format!("{}\t{}", ancestor.name, ancestor.distance)
+                } else {
+                    ancestor.name.clone()
+                };
+                emit(self.stdout, self.format, &line, &json!(ancestor));
+            },
+            QueryCmd::ChildIds { dir: _, max } => {
+                let child_ids = self.plexer.child_ref_ids(NonZero::new(max))?;
+                let entries = child_ids
+                    .into_iter()
+                    .map(|child_id| {
+                        let json = json!({ "id": child_id });
+                        (child_id, json)
+                    })
+                    .collect();
+                emit_list(self.stdout, self.format, entries);
             }
-            | QueryCmd::ParentId { dir: _ }
-            | QueryCmd::ParentName { dir: _, max: _ }
-            | QueryCmd::ChildIds { dir: _, max: _ }
-            | QueryCmd::ChildId { dir: _ }
-            | QueryCmd::CurrentFiles {
+            QueryCmd::ChildId { dir: _ } => {
+                let child_id = self.plexer.child_ref_id()?;
+                emit(self.stdout, self.format, &child_id, &json!({ "id": child_id }));
+            }
+            QueryCmd::CurrentFiles {
                 dir: _,
-                dirty_ok: _,
-            } => todo!(),
-            QueryCmd::DirtyFiles { dir: _, clean_ok } => {
+                dirty_ok,
+                ref base,
+            } => {
+                if !dirty_ok && !self.plexer.is_clean()? {
+                    return Err(DriverError::RepoDirty.into());
+                }
+                let files = self.plexer.changed_files(base.as_deref(), None)?;
+                emit_list(self.stdout, self.format, path_entries(&files));
+            }
+            QueryCmd::Prompt {
+                dir: _,
+                ref template,
+            } => {
+                let prompt = render_prompt(&self.plexer, template)?;
+                emit(
+                    self.stdout,
+                    self.format,
+                    &prompt,
+                    &json!({ "prompt": prompt }),
+                );
+            }
+            QueryCmd::CurrentName { dir: _, dirty_ok } => {
+                let Some(name) = self.plexer.current_ref_name(dirty_ok)? else {
+                    return Ok(EXIT_FAILURE);
+                };
+                emit(self.stdout, self.format, &name, &json!({ "name": name }));
+            }
+            QueryCmd::DirtyFiles {
+                dir: _,
+                clean_ok,
+                status: true,
+            } => {
+                let statuses = self.plexer.status().map_err(CliError::Plexing)?;
+                if statuses.is_empty() && !clean_ok {
+                    return Err(DriverError::RepoNotDirty.into());
+                }
+                let entries = statuses
+                    .iter()
+                    .map(|status| (format_status(status), json!(status)))
+                    .collect();
+                emit_list(self.stdout, self.format, entries);
+            }
+            QueryCmd::DirtyFiles {
+                dir: _,
+                clean_ok,
+                status: false,
+            } => {
                 let files = self
                     .plexer
                     .dirty_files(clean_ok)
                     .map_err(CliError::Plexing)?;
-                for file in files {
-                    writeln!(self.stdout, "{}", file.display()).unwrap_or_else(|_| {
-                        panic!("failed stdout write of: {}", file.display())
-                    });
-                }
+                emit_list(self.stdout, self.format, path_entries(&files));
             }
             QueryCmd::TrackedFiles { dir: _ } => {
                 let files = self
                     .plexer
                     .tracked_files()
                     .map_err(CliError::Plexing)?;
-                for file in files {
-                    writeln!(self.stdout, "{}", file.display()).unwrap_or_else(|_| {
-                        panic!("failed stdout write of: {}", file.display())
-                    });
-                }
+                emit_list(self.stdout, self.format, path_entries(&files));
             }
         }
         Ok(0)
@@ -315,11 +804,26 @@ pub fn main_vcsquery(
     stdout: &mut dyn io::Write,
     stderr: &mut dyn io::Write,
 ) -> u8 {
-    let plexerq = match PlexerQuery::new(args, stdout) {
+    let registry = plexer::Registry::with_defaults().with_external_adapters();
+    main_vcsquery_with(&registry, args, stdout, stderr)
+}
+
+/// Like [`main_vcsquery`], but answering with `registry`'s adapters, eg: so a downstream binary
+/// can offer this same CLI for brands of VCS it has added its own adapters for.
+///
+/// # Panics
+/// Should only panic if stderr or stdout writes fail.
+pub fn main_vcsquery_with(
+    registry: &plexer::Registry,
+    args: &MainArgs,
+    stdout: &mut dyn io::Write,
+    stderr: &mut dyn io::Write,
+) -> u8 {
+    let plexerq = match PlexerQuery::new(registry, args, stdout) {
         Ok(pq) => pq,
         Err(e) => {
             writeln!(stderr, "{e}").unwrap_or_else(|_| panic!("failed stderr write of: {e}"));
-            return 1;
+            return e.exit_code();
         }
     };
     if let Some(mut pq) = plexerq {
@@ -327,24 +831,42 @@ pub fn main_vcsquery(
             Ok(ret) => ret,
             Err(e) => {
                 writeln!(stderr, "{e}").unwrap_or_else(|_| panic!("failed stderr write of: {e}"));
-                1
+                e.exit_code()
             }
         };
     }
 
-    let mut has_fail = false;
-    for report in plexer::check_health() {
+    let reports = registry.check_health();
+    let has_fail = reports.iter().any(|report| report.health.is_err());
+    if args.format != OutputFormat::Text {
+        let entries = reports
+            .iter()
+            .map(|report| (String::new(), json!(report)))
+            .collect();
+        emit_list(stdout, args.format, entries);
+        return u8::from(has_fail);
+    }
+    for report in reports {
         let message = match &report.health {
             Ok(h) => h.stdout.clone(),
             Err(e) => e.to_string(),
         };
         if report.health.is_err() {
-            writeln!(stderr, "FAIL: check for {:?}:\n{}", report.brand, message)
-                .unwrap_or_else(|e| panic!("failed stderr write: {e}"));
-            has_fail = true;
+            writeln!(
+                stderr,
+                "FAIL: check for {}:\n{}",
+                report.brand.as_ref(),
+                message
+            )
+            .unwrap_or_else(|e| panic!("failed stderr write: {e}"));
         } else {
-            writeln!(stdout, "PASS: check for {:?}:\n{}", report.brand, message)
-                .unwrap_or_else(|e| panic!("failed stderr write: {e}"));
+            writeln!(
+                stdout,
+                "PASS: check for {}:\n{}",
+                report.brand.as_ref(),
+                message
+            )
+            .unwrap_or_else(|e| panic!("failed stderr write: {e}"));
         }
     }
     u8::from(has_fail)
@@ -1,8 +1,9 @@
 use crate::libtest::consts::{ERROR_NOT_VALID_DIR, ERROR_NO_KNOWN_VCS};
-use crate::libtest::setup::{TestDirs, TestScope};
+use crate::libtest::setup::{vcs_test_setup, TestDirs, TestScope};
 use assert_cmd::Command;
 
 use predicates::prelude::*;
+use vcsq_cli::{EXIT_NOT_A_REPOSITORY, EXIT_USAGE};
 
 static TEST_SCOPE: TestScope = TestScope::new("cmd_brand.rs");
 
@@ -42,6 +43,193 @@ fn jj() {
         .stderr(predicate::str::is_empty());
 }
 
+#[test]
+fn svn() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).svn_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("brand").arg(test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::eq("Subversion\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn fossil() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).fossil_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("brand").arg(test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::eq("Fossil\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn pijul() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).pijul_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("brand").arg(test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::eq("Pijul\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn bzr() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).bzr_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("brand").arg(test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::eq("Bazaar\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn darcs() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).darcs_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("brand").arg(test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::eq("Darcs\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn cvs() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).cvs_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("brand").arg(test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::eq("Cvs\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn sl() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).sl_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("brand").arg(test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::eq("Sapling\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn jj_colocated() {
+    let test_dirs = TestDirs::create_once(&TEST_SCOPE);
+    let test_dir = test_dirs.root_dir.join("test-jj-colocated-repo");
+    vcs_test_setup::run_cli_from_tempdir(
+        "jj",
+        &["git", "init", "--colocate", "test-jj-colocated-repo"],
+        &test_dirs.root_dir,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("brand").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::eq("Jujutsu\n"))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("brand").arg("--all").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::eq("Jujutsu\nGit\n"))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("brand")
+        .arg("--brand")
+        .arg("git")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::eq("Git\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn git_read_by_other_brands() {
+    // Breezy and Sapling can both read git repos (so `brz root` and `sl root` succeed in one), but
+    // neither colocates atop git like jj does, so a plain git repo is only ever git's.
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).git_repo;
+    vcs_test_setup::commit_touched_git(test_dir, "git-read-by-others.md").unwrap();
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("brand").arg("--all").arg(test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::eq("Git\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn git_nested_in_hg() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).hg_repo.join("vendored");
+    std::fs::create_dir(&test_dir).expect("test arrange: mkdir failed");
+    vcs_test_setup::run_cli_from_tempdir("git", &["init"], &test_dir).unwrap();
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("brand").arg("--all").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::eq("Git\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn forced_brand() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).git_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd
+        .arg("brand")
+        .arg(test_dir)
+        .env("VCSQ_BRAND", "Git")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::eq("Git\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn forced_brand_unknown() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).git_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd
+        .arg("brand")
+        .arg("--brand")
+        .arg("rcs")
+        .arg(test_dir)
+        .assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::starts_with(
+            "vcs error: no adapter for brand rcs (registered brands: Git, Mercurial,",
+        ))
+        .code(i32::from(EXIT_USAGE));
+}
+
 #[test]
 fn novcs() {
     let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_vcs;
@@ -51,7 +239,8 @@ fn novcs() {
     assert
         .failure()
         .stdout(predicate::str::is_empty())
-        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"));
+        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"))
+        .code(i32::from(EXIT_NOT_A_REPOSITORY));
 }
 
 #[test]
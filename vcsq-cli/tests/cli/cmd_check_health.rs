@@ -23,5 +23,13 @@ fn full_pass() {
             "Mercurial Distributed SCM (version",
         ))
         .stdout(predicate::str::contains("PASS: check for Jujutsu:"))
-        .stdout(predicate::str::contains("jj "));
+        .stdout(predicate::str::contains("jj "))
+        .stdout(predicate::str::contains("PASS: check for Subversion:"))
+        .stdout(predicate::str::contains("PASS: check for Fossil:"))
+        .stdout(predicate::str::contains("This is fossil version"))
+        .stdout(predicate::str::contains("PASS: check for Pijul:"))
+        .stdout(predicate::str::contains("PASS: check for Bazaar:"))
+        .stdout(predicate::str::contains("PASS: check for Darcs:"))
+        .stdout(predicate::str::contains("PASS: check for Cvs:"))
+        .stdout(predicate::str::contains("PASS: check for Sapling:"));
 }
@@ -0,0 +1,325 @@
+use crate::libtest::consts::{ERROR_NOT_VALID_DIR, ERROR_NO_KNOWN_VCS};
+use crate::libtest::setup::{vcs_test_setup, TestDirs, TestScope};
+use assert_cmd::Command;
+use predicates::prelude::*;
+use vcsq_cli::{EXIT_MULTIPLE_CHILDREN, EXIT_NOT_A_REPOSITORY, EXIT_NO_CHILD};
+use vcsq_lib::repo::{ERROR_REPO_MULTIPLE_CHILDREN, ERROR_REPO_NO_CHILD};
+
+static TEST_SCOPE: TestScope = TestScope::new("cmd_child_id.rs");
+
+#[test]
+fn git() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).git_repo;
+
+    //
+    // Assert: no children, because there's no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-ids").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-id").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_NO_CHILD))
+        .code(i32::from(EXIT_NO_CHILD));
+
+    //
+    // Arrange+Assert: checking out the first of two commits shows the second as its child
+    //
+    vcs_test_setup::commit_touched_git(&test_dir, "git-first.md").unwrap();
+    let first_id = vcs_test_setup::read_current_id(&test_dir);
+    vcs_test_setup::commit_touched_git(&test_dir, "git-second.md").unwrap();
+    let second_id = vcs_test_setup::read_current_id(&test_dir);
+    vcs_test_setup::run_cli_from_tempdir(
+        "git",
+        &["checkout", "--quiet", "--detach", "HEAD~1"],
+        &test_dir,
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-ids").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(second_id.clone()))
+        .stderr(predicate::str::is_empty());
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(second_id.clone()))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: branching off the first commit gives it multiple children
+    //
+    vcs_test_setup::run_cli_from_tempdir(
+        "git",
+        &["checkout", "--quiet", "-b", "sibling"],
+        &test_dir,
+    )
+    .unwrap();
+    vcs_test_setup::commit_touched_git(&test_dir, "git-sibling.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir(
+        "git",
+        &["checkout", "--quiet", "--detach", first_id.trim()],
+        &test_dir,
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-ids").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains(second_id))
+        .stdout(predicate::str::is_match("^([0-9a-f]+\n){2}$").unwrap())
+        .stderr(predicate::str::is_empty());
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("child-ids")
+        .arg("--max")
+        .arg("1")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_match("^[0-9a-f]+\n$").unwrap())
+        .stderr(predicate::str::is_empty());
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-id").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_MULTIPLE_CHILDREN))
+        .code(i32::from(EXIT_MULTIPLE_CHILDREN));
+}
+
+#[test]
+fn hg() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).hg_repo;
+
+    //
+    // Assert: no children, because there's no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-ids").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-id").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_NO_CHILD))
+        .code(i32::from(EXIT_NO_CHILD));
+
+    //
+    // Arrange+Assert: updating to the first of two commits shows the second as its child
+    //
+    vcs_test_setup::commit_touched_hg(&test_dir, "mercurial-first.md").unwrap();
+    let first_id = vcs_test_setup::read_current_id(&test_dir);
+    vcs_test_setup::commit_touched_hg(&test_dir, "mercurial-second.md").unwrap();
+    let second_id = vcs_test_setup::read_current_id(&test_dir);
+    vcs_test_setup::run_cli_from_tempdir("hg", &["update", "--rev", "p1(.)"], &test_dir).unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-ids").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(second_id.clone()))
+        .stderr(predicate::str::is_empty());
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(second_id.clone()))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: a new head off the first commit gives it multiple children
+    //
+    vcs_test_setup::commit_touched_hg(&test_dir, "mercurial-sibling.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir("hg", &["update", "--rev", first_id.trim()], &test_dir)
+        .unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-ids").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains(second_id))
+        .stdout(predicate::str::is_match("^([0-9a-f]+\n){2}$").unwrap())
+        .stderr(predicate::str::is_empty());
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("child-ids")
+        .arg("--max")
+        .arg("1")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_match("^[0-9a-f]+\n$").unwrap())
+        .stderr(predicate::str::is_empty());
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-id").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_MULTIPLE_CHILDREN))
+        .code(i32::from(EXIT_MULTIPLE_CHILDREN));
+}
+
+#[test]
+fn jj() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).jj_repo;
+
+    //
+    // Assert: no children, because there's no history (the working-copy commit doesn't count)
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-ids").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-id").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_NO_CHILD))
+        .code(i32::from(EXIT_NO_CHILD));
+
+    //
+    // Arrange+Assert: editing atop the first of two commits shows the second as its child
+    //
+    vcs_test_setup::commit_touched_jj(&test_dir, "jj-vcs-first.md").unwrap();
+    let first_id = vcs_test_setup::read_current_id(&test_dir);
+    vcs_test_setup::commit_touched_jj(&test_dir, "jj-vcs-second.md").unwrap();
+    let second_id = vcs_test_setup::read_current_id(&test_dir);
+    vcs_test_setup::run_cli_from_tempdir("jj", &["new", first_id.trim()], &test_dir).unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-ids").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(second_id.clone()))
+        .stderr(predicate::str::is_empty());
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(second_id.clone()))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: a sibling commit off the first commit gives it multiple children
+    //
+    vcs_test_setup::commit_touched_jj(&test_dir, "jj-vcs-sibling.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir("jj", &["new", first_id.trim()], &test_dir).unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-ids").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains(second_id))
+        .stdout(predicate::str::is_match("^([0-9a-f]+\n){2}$").unwrap())
+        .stderr(predicate::str::is_empty());
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("child-ids")
+        .arg("--max")
+        .arg("1")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_match("^[0-9a-f]+\n$").unwrap())
+        .stderr(predicate::str::is_empty());
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-id").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_MULTIPLE_CHILDREN))
+        .code(i32::from(EXIT_MULTIPLE_CHILDREN));
+}
+
+#[test]
+fn svn() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).svn_repo;
+
+    //
+    // Arrange: three revisions, with the working copy back at the first
+    //
+    vcs_test_setup::commit_touched_svn(&test_dir, "svn-first.md").unwrap();
+    vcs_test_setup::commit_touched_svn(&test_dir, "svn-second.md").unwrap();
+    vcs_test_setup::commit_touched_svn(&test_dir, "svn-third.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir("svn", &["update", "--revision", "1"], &test_dir).unwrap();
+
+    //
+    // Assert: later revisions are listed up to --max, but only the next is the child
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-ids").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("2\n3\n"))
+        .stderr(predicate::str::is_empty());
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("child-ids")
+        .arg("--max")
+        .arg("1")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("2\n"))
+        .stderr(predicate::str::is_empty());
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("child-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("2\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn novcs() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_vcs;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("child-ids").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"))
+        .code(i32::from(EXIT_NOT_A_REPOSITORY));
+}
+
+#[test]
+fn non_dir() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_dir;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("child-ids").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NOT_VALID_DIR.to_string() + "\n"));
+}
+
+#[test]
+fn non_extant() {
+    let test_dirs = &TestDirs::create_once(&TEST_SCOPE);
+    let non_extant_path = test_dirs.non_extant();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("child-ids").arg(non_extant_path).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NOT_VALID_DIR.to_string() + "\n"));
+}
@@ -0,0 +1,298 @@
+use crate::libtest::consts::{ERROR_NOT_VALID_DIR, ERROR_NO_KNOWN_VCS};
+use crate::libtest::setup::{make_test_temp, vcs_test_setup, TestDirs, TestScope};
+use assert_cmd::Command;
+use predicates::prelude::*;
+use vcsq_cli::{EXIT_NOT_A_REPOSITORY, EXIT_REPO_DIRTY, EXIT_UNSUPPORTED};
+use vcsq_lib::repo::ERROR_REPO_NOT_CLEAN;
+
+static TEST_SCOPE: TestScope = TestScope::new("cmd_current_files.rs");
+
+#[test]
+fn git() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).git_repo;
+
+    //
+    // Assert: nothing touched, because there's no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-files").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: the root commit's files are all listed
+    //
+    vcs_test_setup::commit_touched_git(&test_dir, "git-first.md").unwrap();
+    let first_id = vcs_test_setup::read_current_id(&test_dir);
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-files").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("git-first.md\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: only files touched relative to the parent are listed
+    //
+    vcs_test_setup::commit_touched_git(&test_dir, "git-second.md").unwrap();
+    vcs_test_setup::commit_touched_git(&test_dir, "git-third.md").unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-files").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("git-third.md\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Assert: files touched since an explicit base are listed
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("current-files")
+        .arg("--base")
+        .arg(first_id.trim())
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("git-second.md\ngit-third.md\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: dirty repo refuses to answer, unless --dirty-ok
+    //
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("git-unclean.md");
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-files").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_NOT_CLEAN))
+        .code(i32::from(EXIT_REPO_DIRTY));
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("current-files")
+        .arg("--dirty-ok")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("git-third.md\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn hg() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).hg_repo;
+
+    //
+    // Assert: nothing touched, because there's no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-files").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: the root commit's files are all listed
+    //
+    vcs_test_setup::commit_touched_hg(&test_dir, "mercurial-first.md").unwrap();
+    let first_id = vcs_test_setup::read_current_id(&test_dir);
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-files").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("mercurial-first.md\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: only files touched relative to the parent are listed
+    //
+    vcs_test_setup::commit_touched_hg(&test_dir, "mercurial-second.md").unwrap();
+    vcs_test_setup::commit_touched_hg(&test_dir, "mercurial-third.md").unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-files").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("mercurial-third.md\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Assert: files touched since an explicit base are listed
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("current-files")
+        .arg("--base")
+        .arg(first_id.trim())
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(
+            "mercurial-second.md\nmercurial-third.md\n",
+        ))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: dirty repo refuses to answer, unless --dirty-ok
+    //
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("mercurial-unclean.md");
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-files").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_NOT_CLEAN))
+        .code(i32::from(EXIT_REPO_DIRTY));
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("current-files")
+        .arg("--dirty-ok")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("mercurial-third.md\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn jj() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).jj_repo;
+
+    //
+    // Assert: nothing touched, because there's no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-files").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: the root commit's files are all listed
+    //
+    vcs_test_setup::commit_touched_jj(&test_dir, "jj-vcs-first.md").unwrap();
+    let first_id = vcs_test_setup::read_current_id(&test_dir);
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-files").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("jj-vcs-first.md\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: only files touched relative to the parent are listed
+    //
+    vcs_test_setup::commit_touched_jj(&test_dir, "jj-vcs-second.md").unwrap();
+    vcs_test_setup::commit_touched_jj(&test_dir, "jj-vcs-third.md").unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-files").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("jj-vcs-third.md\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Assert: files touched since an explicit base are listed
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("current-files")
+        .arg("--base")
+        .arg(first_id.trim())
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("jj-vcs-second.md\njj-vcs-third.md\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: dirty repo refuses to answer, unless --dirty-ok
+    //
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("jj-vcs-unclean.md");
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-files").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_NOT_CLEAN))
+        .code(i32::from(EXIT_REPO_DIRTY));
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("current-files")
+        .arg("--dirty-ok")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("jj-vcs-third.md\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn pijul() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).pijul_repo;
+
+    //
+    // Assert: pijul states aren't trees, so can't be compared
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-files").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("unsupported"))
+        .code(i32::from(EXIT_UNSUPPORTED));
+}
+
+#[test]
+fn novcs() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_vcs;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("current-files").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"))
+        .code(i32::from(EXIT_NOT_A_REPOSITORY));
+}
+
+#[test]
+fn non_dir() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_dir;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("current-files").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NOT_VALID_DIR.to_string() + "\n"));
+}
+
+#[test]
+fn non_extant() {
+    let test_dirs = &TestDirs::create_once(&TEST_SCOPE);
+    let non_extant_path = test_dirs.non_extant();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("current-files").arg(non_extant_path).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NOT_VALID_DIR.to_string() + "\n"));
+}
@@ -2,6 +2,10 @@ use crate::libtest::consts::{ERROR_NOT_VALID_DIR, ERROR_NO_KNOWN_VCS};
 use crate::libtest::setup::{git_cmd_args, make_test_temp, vcs_test_setup, TestDirs, TestScope};
 use assert_cmd::Command;
 use predicates::prelude::*;
+use vcsq_cli::{
+    EMPTY_HISTORY_SENTINEL, EXIT_EMPTY_HISTORY, EXIT_NOT_A_REPOSITORY, EXIT_UNSUPPORTED,
+};
+use vcsq_lib::repo::ERROR_REPO_EMPTY_HISTORY;
 
 static TEST_SCOPE: TestScope = TestScope::new("cmd_current_id.rs");
 
@@ -17,10 +21,49 @@ fn git() {
     assert
         .success()
         .stdout(predicate::str::diff(
-            "00000000000000000000000000000000000000000000000000\n",
+            EMPTY_HISTORY_SENTINEL.to_string() + "\n",
         ))
         .stderr(predicate::str::is_empty());
 
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("current-id")
+        .arg("--empty-history")
+        .arg("empty")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("--format")
+        .arg("json")
+        .arg("current-id")
+        .arg("--empty-history")
+        .arg("empty")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("{\"id\":null}\n"))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("current-id")
+        .arg("--empty-history")
+        .arg("error")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_EMPTY_HISTORY))
+        .code(i32::from(EXIT_EMPTY_HISTORY));
+
     //
     // Arrange: add a commit to the repo's history
     //
@@ -67,7 +110,7 @@ fn hg() {
     assert
         .success()
         .stdout(predicate::str::diff(
-            "0000000000000000000000000000000000000000\n",
+            EMPTY_HISTORY_SENTINEL.to_string() + "\n",
         ))
         .stderr(predicate::str::is_empty());
 
@@ -115,7 +158,7 @@ fn jj() {
     assert
         .success()
         .stdout(predicate::str::diff(
-            "0000000000000000000000000000000000000000\n",
+            EMPTY_HISTORY_SENTINEL.to_string() + "\n",
         ))
         .stderr(predicate::str::is_empty());
 
@@ -150,6 +193,216 @@ fn jj() {
         .stderr(predicate::str::is_empty());
 }
 
+#[test]
+fn svn() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).svn_repo;
+
+    //
+    // Assert: repo has no real current id, because it has no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(
+            EMPTY_HISTORY_SENTINEL.to_string() + "\n",
+        ))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: add two commits to the repo's history
+    //
+    vcs_test_setup::commit_touched_svn(&test_dir, "svn-first.md").unwrap();
+    vcs_test_setup::commit_touched_svn(&test_dir, "svn-second.md").unwrap();
+
+    //
+    // Assert: revision numbers are deterministic
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("2\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn fossil() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).fossil_repo;
+
+    //
+    // Assert: repo already has a current id, because `fossil init` makes an initial check-in
+    //
+    let initial_id = vcs_test_setup::read_current_id(&test_dir);
+    assert_ne!(
+        initial_id,
+        EMPTY_HISTORY_SENTINEL.to_string() + "\n",
+        "fossil repos start with an (empty) check-in"
+    );
+
+    //
+    // Arrange: add a commit to the repo's history
+    //
+    vcs_test_setup::commit_touched_fossil(&test_dir, "fossil-first.md").unwrap();
+
+    //
+    // Assert: current id moved to the new check-in
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty().not())
+        .stdout(predicate::str::diff(initial_id).not())
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn pijul() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).pijul_repo;
+
+    //
+    // Assert: channel has no state yet, because no changes were recorded to it
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(
+            EMPTY_HISTORY_SENTINEL.to_string() + "\n",
+        ))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("current-id")
+        .arg("--empty-history")
+        .arg("error")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_EMPTY_HISTORY))
+        .code(i32::from(EXIT_EMPTY_HISTORY));
+}
+
+#[test]
+fn bzr() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).bzr_repo;
+
+    //
+    // Assert: repo has no real current id, because it has no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(
+            EMPTY_HISTORY_SENTINEL.to_string() + "\n",
+        ))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: add a commit to the repo's history
+    //
+    vcs_test_setup::commit_touched_bzr(&test_dir, "bzr-first.md").unwrap();
+
+    //
+    // Assert: repo now has a current id (a revision id, rather than a revno)
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty().not())
+        .stdout(predicate::str::diff("1\n").not())
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn darcs() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).darcs_repo;
+
+    //
+    // Assert: repo has no real current id, because it has no patches
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(
+            EMPTY_HISTORY_SENTINEL.to_string() + "\n",
+        ))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: record a patch
+    //
+    vcs_test_setup::commit_touched_darcs(&test_dir, "darcs-first.md").unwrap();
+
+    //
+    // Assert: repo now has a current id (a hash of its set of patches)
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty().not())
+        .stdout(predicate::str::diff(EMPTY_HISTORY_SENTINEL.to_string() + "\n").not())
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn cvs() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).cvs_repo;
+    vcs_test_setup::commit_touched_cvs(&test_dir, "cvs-first.md").unwrap();
+
+    //
+    // Assert: cvs only has revisions of individual files, so the repo has no id
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-id").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("unsupported"))
+        .code(i32::from(EXIT_UNSUPPORTED));
+}
+
+#[test]
+fn sl() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).sl_repo;
+
+    //
+    // Assert: repo has no real current id, because it has no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(
+            EMPTY_HISTORY_SENTINEL.to_string() + "\n",
+        ))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: add a commit to the repo's history
+    //
+    vcs_test_setup::commit_touched_sl(&test_dir, "sl-first.md").unwrap();
+
+    //
+    // Assert: repo now has a current id
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty().not())
+        .stdout(predicate::str::diff(EMPTY_HISTORY_SENTINEL.to_string() + "\n").not())
+        .stderr(predicate::str::is_empty());
+}
+
 #[test]
 fn novcs() {
     let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_vcs;
@@ -159,7 +412,8 @@ fn novcs() {
     assert
         .failure()
         .stdout(predicate::str::is_empty())
-        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"));
+        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"))
+        .code(i32::from(EXIT_NOT_A_REPOSITORY));
 }
 
 #[test]
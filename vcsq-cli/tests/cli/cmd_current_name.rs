@@ -0,0 +1,444 @@
+use crate::libtest::consts::{ERROR_NOT_VALID_DIR, ERROR_NO_KNOWN_VCS};
+use crate::libtest::setup::{make_test_temp, vcs_test_setup, TestDirs, TestScope};
+use assert_cmd::Command;
+use predicates::prelude::*;
+use vcsq_cli::{EXIT_FAILURE, EXIT_NOT_A_REPOSITORY, EXIT_REPO_DIRTY};
+use vcsq_lib::repo::ERROR_REPO_NOT_CLEAN;
+
+static TEST_SCOPE: TestScope = TestScope::new("cmd_current_name.rs");
+
+#[test]
+fn git() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).git_repo;
+
+    //
+    // Arrange: some history on a named branch
+    //
+    vcs_test_setup::commit_touched_git(&test_dir, "git-first.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir("git", &["checkout", "-b", "feature"], &test_dir).unwrap();
+
+    //
+    // Assert: current branch is the name
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("feature\n"))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("--format")
+        .arg("json")
+        .arg("current-name")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("{\"name\":\"feature\"}\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: dirty repos only answer when asked to
+    //
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("git-docs.md");
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_NOT_CLEAN))
+        .code(i32::from(EXIT_REPO_DIRTY));
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("current-name")
+        .arg("--dirty-ok")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("feature\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: tags take precedence over the branch
+    //
+    vcs_test_setup::run_cli_from_tempdir("git", &["tag", "v1.0.0"], &test_dir).unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("current-name")
+        .arg("--dirty-ok")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("v1.0.0\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn hg() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).hg_repo;
+
+    //
+    // Assert: no name, because there's no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty())
+        .code(i32::from(EXIT_FAILURE));
+
+    //
+    // Arrange: some tagged history
+    //
+    vcs_test_setup::commit_touched_hg(&test_dir, "hg-first.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir(
+        "hg",
+        &["tag", "--message", "test arrange: tagging", "v1.0.0"],
+        &test_dir,
+    )
+    .unwrap();
+
+    //
+    // Assert: tagging committed atop the tag, so that commit is only named by its branch
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("default\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: bookmarks take precedence over the branch
+    //
+    vcs_test_setup::run_cli_from_tempdir("hg", &["bookmark", "feature"], &test_dir).unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("feature\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: tags take precedence over bookmarks
+    //
+    vcs_test_setup::run_cli_from_tempdir("hg", &["update", "--rev", "v1.0.0"], &test_dir).unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("v1.0.0\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn jj() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).jj_repo;
+
+    //
+    // Assert: no name, because there's no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty())
+        .code(i32::from(EXIT_FAILURE));
+
+    //
+    // Arrange+Assert: bookmark on the current revision
+    //
+    vcs_test_setup::commit_touched_jj(&test_dir, "jj-first.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir(
+        "jj",
+        &["bookmark", "create", "--revision", "@-", "feature"],
+        &test_dir,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("feature\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: bookmark on the nearest bookmarked ancestor
+    //
+    vcs_test_setup::commit_touched_jj(&test_dir, "jj-second.md").unwrap();
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("feature\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn svn() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).svn_repo;
+
+    //
+    // Assert: no name, because the repo doesn't follow the trunk/branches/tags layout
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty())
+        .code(i32::from(EXIT_FAILURE));
+
+    //
+    // Arrange: lay the repo out conventionally, and switch to a branch
+    //
+    for dir in ["trunk", "branches"] {
+        vcs_test_setup::run_cli_from_tempdir("svn", &["mkdir", dir], &test_dir).unwrap();
+    }
+    vcs_test_setup::run_cli_from_tempdir(
+        "svn",
+        &["commit", "--message", "test arrange: standard layout"],
+        &test_dir,
+    )
+    .unwrap();
+    vcs_test_setup::run_cli_from_tempdir(
+        "svn",
+        &[
+            "copy",
+            "^/trunk",
+            "^/branches/feature",
+            "--message",
+            "test arrange: branching",
+        ],
+        &test_dir,
+    )
+    .unwrap();
+    vcs_test_setup::run_cli_from_tempdir("svn", &["switch", "^/branches/feature"], &test_dir)
+        .unwrap();
+
+    //
+    // Assert: branch named by its path
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("feature\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn fossil() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).fossil_repo;
+
+    //
+    // Assert: branch is the name, as fossil repos start on trunk
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("trunk\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: tags take precedence over the branch
+    //
+    vcs_test_setup::commit_touched_fossil(&test_dir, "fossil-first.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir("fossil", &["tag", "add", "v1.0.0", "current"], &test_dir)
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("v1.0.0\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn pijul() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).pijul_repo;
+
+    //
+    // Assert: current channel is the name
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("main\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn bzr() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).bzr_repo;
+
+    //
+    // Assert: branch nickname (defaulting to the branch's dirname) is the name
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(
+            test_dir.file_name().unwrap().to_string_lossy().to_string() + "\n",
+        ))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: tags take precedence over the nickname
+    //
+    vcs_test_setup::commit_touched_bzr(&test_dir, "bzr-first.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir("brz", &["tag", "v1.0.0"], &test_dir).unwrap();
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("v1.0.0\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn darcs() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).darcs_repo;
+
+    //
+    // Assert: no tags yet, so no name
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .failure()
+        .code(i32::from(EXIT_FAILURE))
+        .stdout(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: the newest tag is the name
+    //
+    vcs_test_setup::commit_touched_darcs(&test_dir, "darcs-first.md").unwrap();
+    vcs_test_setup::tag_darcs(&test_dir, "v1.0.0").unwrap();
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("v1.0.0\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn cvs() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).cvs_repo;
+
+    //
+    // Assert: no sticky tag yet, so no name
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .failure()
+        .code(i32::from(EXIT_FAILURE))
+        .stdout(predicate::str::is_empty());
+
+    //
+    // Arrange: branch, and update onto the branch (making it sticky)
+    //
+    vcs_test_setup::commit_touched_cvs(&test_dir, "cvs-first.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir("cvs", &["tag", "-b", "feature"], &test_dir).unwrap();
+    vcs_test_setup::run_cli_from_tempdir("cvs", &["update", "-r", "feature"], &test_dir).unwrap();
+
+    //
+    // Assert: the sticky branch is the name
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("feature\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn sl() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).sl_repo;
+
+    //
+    // Assert: no name, because there's no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty())
+        .code(i32::from(EXIT_FAILURE));
+
+    //
+    // Arrange: some bookmarked history
+    //
+    vcs_test_setup::commit_touched_sl(&test_dir, "sl-first.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir("sl", &["bookmark", "main"], &test_dir).unwrap();
+
+    //
+    // Assert
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("current-name").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("main\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn novcs() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_vcs;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("current-name").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"))
+        .code(i32::from(EXIT_NOT_A_REPOSITORY));
+}
+
+#[test]
+fn non_dir() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_dir;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("current-name").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NOT_VALID_DIR.to_string() + "\n"));
+}
+
+#[test]
+fn non_extant() {
+    let test_dirs = &TestDirs::create_once(&TEST_SCOPE);
+    let non_extant_path = test_dirs.non_extant();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("current-name").arg(non_extant_path).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NOT_VALID_DIR.to_string() + "\n"));
+}
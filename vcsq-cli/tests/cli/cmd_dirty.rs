@@ -1,8 +1,9 @@
 use crate::libtest::consts::{ERROR_NOT_VALID_DIR, ERROR_NO_KNOWN_VCS};
-use crate::libtest::setup::{make_test_temp, TestDirs, TestScope};
+use crate::libtest::setup::{make_test_temp, vcs_test_setup, TestDirs, TestScope};
 use assert_cmd::Command;
-use vcsq_lib::repo::ERROR_REPO_NOT_DIRTY;
 use predicates::prelude::*;
+use vcsq_cli::{EXIT_NOT_A_REPOSITORY, EXIT_REPO_NOT_DIRTY};
+use vcsq_lib::repo::ERROR_REPO_NOT_DIRTY;
 
 static TEST_SCOPE: TestScope = TestScope::new("cmd_dirty.rs");
 
@@ -33,7 +34,8 @@ fn git() {
     assert
         .failure()
         .stdout(predicate::str::is_empty())
-        .stderr(predicate::str::contains(ERROR_REPO_NOT_DIRTY));
+        .stderr(predicate::str::contains(ERROR_REPO_NOT_DIRTY))
+        .code(i32::from(EXIT_REPO_NOT_DIRTY));
 
     //
     // Arrange: make the repo dirty
@@ -52,6 +54,40 @@ fn git() {
         .success()
         .stdout(predicate::str::diff("git-docs.md\n"))
         .stderr(predicate::str::is_empty());
+
+    //
+    // Assert: status codes are reported too
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("dirty-files")
+        .arg("--status")
+        .arg(test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("?  git-docs.md\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: stage the new file
+    //
+    vcs_test_setup::run_cli_from_tempdir("git", &["add", "git-docs.md"], test_dir)
+        .expect("test arrange: git add failed");
+
+    //
+    // Assert: staged files are marked as such
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("dirty-files")
+        .arg("--status")
+        .arg(test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("A+ git-docs.md\n"))
+        .stderr(predicate::str::is_empty());
 }
 
 #[test]
@@ -81,7 +117,8 @@ fn hg() {
     assert
         .failure()
         .stdout(predicate::str::is_empty())
-        .stderr(predicate::str::contains(ERROR_REPO_NOT_DIRTY));
+        .stderr(predicate::str::contains(ERROR_REPO_NOT_DIRTY))
+        .code(i32::from(EXIT_REPO_NOT_DIRTY));
 
     //
     // Arrange: make the repo dirty
@@ -100,6 +137,20 @@ fn hg() {
         .success()
         .stdout(predicate::str::diff("mercurial.md\n"))
         .stderr(predicate::str::is_empty());
+
+    //
+    // Assert: status codes are reported too
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("dirty-files")
+        .arg("--status")
+        .arg(test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("?  mercurial.md\n"))
+        .stderr(predicate::str::is_empty());
 }
 
 #[test]
@@ -129,7 +180,8 @@ fn jj() {
     assert
         .failure()
         .stdout(predicate::str::is_empty())
-        .stderr(predicate::str::contains(ERROR_REPO_NOT_DIRTY));
+        .stderr(predicate::str::contains(ERROR_REPO_NOT_DIRTY))
+        .code(i32::from(EXIT_REPO_NOT_DIRTY));
 
     //
     // Arrange: make the repo dirty
@@ -148,6 +200,52 @@ fn jj() {
         .success()
         .stdout(predicate::str::diff("jj-vcs-docs.md\n"))
         .stderr(predicate::str::is_empty());
+
+    //
+    // Assert: status codes are reported too
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("dirty-files")
+        .arg("--status")
+        .arg(test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("A  jj-vcs-docs.md\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn darcs_spaced_paths() {
+    let test_dirs = TestDirs::create_once(&TEST_SCOPE);
+    let test_dir = &test_dirs.darcs_repo;
+
+    //
+    // Arrange: edit a recorded file, and add an untracked one, both with spaces in their names
+    //
+    vcs_test_setup::commit_touched_darcs(test_dir, "darcs spaced.md")
+        .expect("test arrange: darcs record failed");
+    std::fs::write(test_dir.join("darcs spaced.md"), "edited\n")
+        .expect("test arrange: write failed");
+    make_test_temp::touch(&test_dir.join("darcs new file.md")).expect("test arrange: touch failed");
+
+    //
+    // Assert: whole paths are reported
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("dirty-files")
+        .arg("--status")
+        .arg(test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(
+            predicate::str::contains("M  darcs spaced.md\n")
+                .and(predicate::str::contains("?  darcs new file.md\n")),
+        )
+        .stderr(predicate::str::is_empty());
 }
 
 #[test]
@@ -159,7 +257,8 @@ fn novcs() {
     assert
         .failure()
         .stdout(predicate::str::is_empty())
-        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"));
+        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"))
+        .code(i32::from(EXIT_NOT_A_REPOSITORY));
 }
 
 #[test]
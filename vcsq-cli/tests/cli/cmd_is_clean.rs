@@ -2,6 +2,7 @@ use crate::libtest::consts::{ERROR_NOT_VALID_DIR, ERROR_NO_KNOWN_VCS};
 use crate::libtest::setup::{make_test_temp, TestDirs, TestScope};
 use assert_cmd::Command;
 use predicates::prelude::*;
+use vcsq_cli::EXIT_NOT_A_REPOSITORY;
 
 static TEST_SCOPE: TestScope = TestScope::new("cmd_is_clean.rs");
 
@@ -107,6 +108,244 @@ fn jj() {
         .stderr(predicate::str::is_empty());
 }
 
+#[test]
+fn svn() {
+    let test_dirs = TestDirs::create_once(&TEST_SCOPE);
+    let test_dir = &test_dirs.svn_repo;
+
+    //
+    // Arrange+Assert: clean repo lists nothing dirty
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("is-clean").arg(test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: make the repo dirty
+    //
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("svn-unclean.md");
+
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+
+    //
+    // Assert: dirty repo now has report of what's dirty
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("is-clean").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn fossil() {
+    let test_dirs = TestDirs::create_once(&TEST_SCOPE);
+    let test_dir = &test_dirs.fossil_repo;
+
+    //
+    // Arrange+Assert: clean repo lists nothing dirty
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("is-clean").arg(test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: make the repo dirty
+    //
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("fossil-unclean.md");
+
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+
+    //
+    // Assert: dirty repo now has report of what's dirty
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("is-clean").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn pijul() {
+    let test_dirs = TestDirs::create_once(&TEST_SCOPE);
+    let test_dir = &test_dirs.pijul_repo;
+
+    //
+    // Arrange+Assert: clean repo lists nothing dirty
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("is-clean").arg(test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: make the repo dirty
+    //
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("pijul-unclean.md");
+
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+
+    //
+    // Assert: dirty repo now has report of what's dirty
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("is-clean").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn bzr() {
+    let test_dirs = TestDirs::create_once(&TEST_SCOPE);
+    let test_dir = &test_dirs.bzr_repo;
+
+    //
+    // Arrange+Assert: clean repo lists nothing dirty
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("is-clean").arg(test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: make the repo dirty
+    //
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("bzr-unclean.md");
+
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+
+    //
+    // Assert: dirty repo now has report of what's dirty
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("is-clean").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn darcs() {
+    let test_dirs = TestDirs::create_once(&TEST_SCOPE);
+    let test_dir = &test_dirs.darcs_repo;
+
+    //
+    // Arrange+Assert: clean repo lists nothing dirty
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("is-clean").arg(test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: make the repo dirty
+    //
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("darcs-unclean.md");
+
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+
+    //
+    // Assert: dirty repo now has report of what's dirty
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("is-clean").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn cvs() {
+    let test_dirs = TestDirs::create_once(&TEST_SCOPE);
+    let test_dir = &test_dirs.cvs_repo;
+
+    //
+    // Arrange+Assert: clean repo lists nothing dirty
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("is-clean").arg(test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: make the repo dirty
+    //
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("cvs-unclean.md");
+
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+
+    //
+    // Assert: dirty repo now has report of what's dirty
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("is-clean").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn sl() {
+    let test_dirs = TestDirs::create_once(&TEST_SCOPE);
+    let test_dir = &test_dirs.sl_repo;
+
+    //
+    // Arrange+Assert: clean repo lists nothing dirty
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("is-clean").arg(test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: make the repo dirty
+    //
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("sl-unclean.md");
+
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+
+    //
+    // Assert: dirty repo now has report of what's dirty
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("is-clean").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+}
+
 #[test]
 fn novcs() {
     let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_vcs;
@@ -116,7 +355,8 @@ fn novcs() {
     assert
         .failure()
         .stdout(predicate::str::is_empty())
-        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"));
+        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"))
+        .code(i32::from(EXIT_NOT_A_REPOSITORY));
 }
 
 #[test]
@@ -0,0 +1,166 @@
+use crate::libtest::consts::{ERROR_NOT_VALID_DIR, ERROR_NO_KNOWN_VCS};
+use crate::libtest::setup::{vcs_test_setup, TestDirs, TestScope};
+use assert_cmd::Command;
+use predicates::prelude::*;
+use vcsq_cli::{EXIT_EMPTY_HISTORY, EXIT_NOT_A_REPOSITORY, EXIT_NO_PARENT};
+use vcsq_lib::repo::{ERROR_REPO_EMPTY_HISTORY, ERROR_REPO_NO_PARENT};
+
+static TEST_SCOPE: TestScope = TestScope::new("cmd_parent_id.rs");
+
+#[test]
+fn git() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).git_repo;
+
+    //
+    // Assert: repo has no parent, because it has no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-id").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_EMPTY_HISTORY))
+        .code(i32::from(EXIT_EMPTY_HISTORY));
+
+    //
+    // Arrange+Assert: root commit still has no parent
+    //
+    vcs_test_setup::commit_touched_git(&test_dir, "git-first.md").unwrap();
+    let first_id = vcs_test_setup::read_current_id(&test_dir);
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-id").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_NO_PARENT))
+        .code(i32::from(EXIT_NO_PARENT));
+
+    //
+    // Arrange+Assert: second commit's parent is the first commit
+    //
+    vcs_test_setup::commit_touched_git(&test_dir, "git-second.md").unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(first_id))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn hg() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).hg_repo;
+
+    //
+    // Assert: repo has no parent, because it has no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-id").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_NO_PARENT))
+        .code(i32::from(EXIT_NO_PARENT));
+
+    //
+    // Arrange+Assert: root commit still has no parent
+    //
+    vcs_test_setup::commit_touched_hg(&test_dir, "mercurial-first.md").unwrap();
+    let first_id = vcs_test_setup::read_current_id(&test_dir);
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-id").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_NO_PARENT))
+        .code(i32::from(EXIT_NO_PARENT));
+
+    //
+    // Arrange+Assert: second commit's parent is the first commit
+    //
+    vcs_test_setup::commit_touched_hg(&test_dir, "mercurial-second.md").unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(first_id))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn jj() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).jj_repo;
+
+    //
+    // Assert: repo has no parent, because it has no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-id").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_NO_PARENT))
+        .code(i32::from(EXIT_NO_PARENT));
+
+    //
+    // Arrange+Assert: root commit still has no parent
+    //
+    vcs_test_setup::commit_touched_jj(&test_dir, "jj-vcs-first.md").unwrap();
+    let first_id = vcs_test_setup::read_current_id(&test_dir);
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-id").arg(&test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(ERROR_REPO_NO_PARENT))
+        .code(i32::from(EXIT_NO_PARENT));
+
+    //
+    // Arrange+Assert: second commit's parent is the first commit
+    //
+    vcs_test_setup::commit_touched_jj(&test_dir, "jj-vcs-second.md").unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-id").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(first_id))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn novcs() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_vcs;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("parent-id").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"))
+        .code(i32::from(EXIT_NOT_A_REPOSITORY));
+}
+
+#[test]
+fn non_dir() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_dir;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("parent-id").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NOT_VALID_DIR.to_string() + "\n"));
+}
+
+#[test]
+fn non_extant() {
+    let test_dirs = &TestDirs::create_once(&TEST_SCOPE);
+    let non_extant_path = test_dirs.non_extant();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("parent-id").arg(non_extant_path).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NOT_VALID_DIR.to_string() + "\n"));
+}
@@ -0,0 +1,232 @@
+use crate::libtest::consts::{ERROR_NOT_VALID_DIR, ERROR_NO_KNOWN_VCS};
+use crate::libtest::setup::{vcs_test_setup, TestDirs, TestScope};
+use assert_cmd::Command;
+use predicates::prelude::*;
+use vcsq_cli::EXIT_NOT_A_REPOSITORY;
+
+static TEST_SCOPE: TestScope = TestScope::new("cmd_parent_name.rs");
+
+#[test]
+fn git() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).git_repo;
+
+    //
+    // Assert: no names to find, because there's no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-name").arg(&test_dir).arg("5").assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: tag a commit, then build on top of it
+    //
+    vcs_test_setup::commit_touched_git(&test_dir, "git-first.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir("git", &["tag", "v1.0.0"], &test_dir).unwrap();
+    vcs_test_setup::commit_touched_git(&test_dir, "git-second.md").unwrap();
+
+    //
+    // Assert: tag is found on the parent
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-name").arg(&test_dir).arg("5").assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("v1.0.0\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: tag is found further back, with its distance
+    //
+    vcs_test_setup::commit_touched_git(&test_dir, "git-third.md").unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("parent-name")
+        .arg("--distance")
+        .arg(&test_dir)
+        .arg("5")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("v1.0.0\t2\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Assert: tag isn't found if it's beyond max
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-name").arg(&test_dir).arg("1").assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn hg() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).hg_repo;
+
+    //
+    // Assert: no names to find, because there's no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-name").arg(&test_dir).arg("5").assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: tag a commit (which itself commits to .hgtags, building on top of it)
+    //
+    vcs_test_setup::commit_touched_hg(&test_dir, "mercurial-first.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir(
+        "hg",
+        &["tag", "--message", "test arrange phase: tagging", "v1.0.0"],
+        &test_dir,
+    )
+    .unwrap();
+
+    //
+    // Assert: tag is found on the parent
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-name").arg(&test_dir).arg("5").assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("v1.0.0\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: tag is found further back, with its distance
+    //
+    vcs_test_setup::commit_touched_hg(&test_dir, "mercurial-third.md").unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("parent-name")
+        .arg("--distance")
+        .arg(&test_dir)
+        .arg("5")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("v1.0.0\t2\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Assert: tag isn't found if it's beyond max
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-name").arg(&test_dir).arg("1").assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn jj() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).jj_repo;
+
+    //
+    // Assert: no names to find, because there's no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-name").arg(&test_dir).arg("5").assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: bookmark a commit, then build on top of it
+    //
+    vcs_test_setup::commit_touched_jj(&test_dir, "jj-vcs-first.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir(
+        "jj",
+        &["bookmark", "create", "--revision", "@-", "v1.0.0"],
+        &test_dir,
+    )
+    .unwrap();
+    vcs_test_setup::commit_touched_jj(&test_dir, "jj-vcs-second.md").unwrap();
+
+    //
+    // Assert: bookmark is found on the parent
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-name").arg(&test_dir).arg("5").assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("v1.0.0\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: bookmark is found further back, with its distance
+    //
+    vcs_test_setup::commit_touched_jj(&test_dir, "jj-vcs-third.md").unwrap();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("parent-name")
+        .arg("--distance")
+        .arg(&test_dir)
+        .arg("5")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("v1.0.0\t2\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Assert: bookmark isn't found if it's beyond max
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("parent-name").arg(&test_dir).arg("1").assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn novcs() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_vcs;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("parent-name").arg(test_dir).arg("5").assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"))
+        .code(i32::from(EXIT_NOT_A_REPOSITORY));
+}
+
+#[test]
+fn non_dir() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_dir;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("parent-name").arg(test_dir).arg("5").assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NOT_VALID_DIR.to_string() + "\n"));
+}
+
+#[test]
+fn non_extant() {
+    let test_dirs = &TestDirs::create_once(&TEST_SCOPE);
+    let non_extant_path = test_dirs.non_extant();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd
+        .arg("parent-name")
+        .arg(non_extant_path)
+        .arg("5")
+        .assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NOT_VALID_DIR.to_string() + "\n"));
+}
@@ -0,0 +1,238 @@
+use crate::libtest::consts::{ERROR_NOT_VALID_DIR, ERROR_NO_KNOWN_VCS};
+use crate::libtest::setup::{make_test_temp, vcs_test_setup, TestDirs, TestScope};
+use assert_cmd::Command;
+use predicates::prelude::*;
+use vcsq_cli::EXIT_NOT_A_REPOSITORY;
+
+static TEST_SCOPE: TestScope = TestScope::new("cmd_prompt.rs");
+
+#[test]
+fn git() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).git_repo;
+
+    //
+    // Assert: no ID, because there's no history
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("prompt")
+        .arg("-f")
+        .arg("[%i]%m")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("[]\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: some history on a named branch
+    //
+    vcs_test_setup::commit_touched_git(&test_dir, "git-first.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir("git", &["checkout", "-b", "feature"], &test_dir).unwrap();
+    let current_id = vcs_test_setup::read_current_id(&test_dir);
+
+    //
+    // Assert: default template
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("prompt").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("Git:feature\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Assert: every placeholder (no upstream, nor operation in progress)
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("prompt")
+        .arg("-f")
+        .arg("%b %i %n%m [%a%o] 100%%")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(format!(
+            "Git {} feature [] 100%\n",
+            &current_id[..12]
+        )))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: dirty marker
+    //
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("git-docs.md");
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("prompt").arg(&test_dir).assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("Git:feature+\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn hg() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).hg_repo;
+
+    //
+    // Arrange: some tagged history
+    //
+    vcs_test_setup::commit_touched_hg(&test_dir, "hg-first.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir(
+        "hg",
+        &["tag", "--message", "test arrange: tagging", "v1.0.0"],
+        &test_dir,
+    )
+    .unwrap();
+    vcs_test_setup::run_cli_from_tempdir("hg", &["update", "--rev", "v1.0.0"], &test_dir).unwrap();
+
+    //
+    // Assert
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("prompt")
+        .arg("-f")
+        .arg("%b:%n%m [%a%o]")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("Mercurial:v1.0.0 []\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: dirty marker
+    //
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("mercurial.md");
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("prompt")
+        .arg("-f")
+        .arg("%b:%m")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("Mercurial:+\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn jj() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).jj_repo;
+
+    //
+    // Arrange: some bookmarked history
+    //
+    vcs_test_setup::commit_touched_jj(&test_dir, "jj-first.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir(
+        "jj",
+        &["bookmark", "create", "--revision", "@-", "feature"],
+        &test_dir,
+    )
+    .unwrap();
+    let current_id = vcs_test_setup::read_current_id(&test_dir);
+
+    //
+    // Assert
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("prompt")
+        .arg("-f")
+        .arg("%b %i %n%m [%a%o]")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(format!(
+            "Jujutsu {} feature []\n",
+            &current_id[..12]
+        )))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange+Assert: dirty marker
+    //
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("jj-vcs-docs.md");
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("prompt")
+        .arg("-f")
+        .arg("%b:%m")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("Jujutsu:+\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn bad_template() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).git_repo;
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("prompt")
+        .arg("-f")
+        .arg("%b %z")
+        .arg(&test_dir)
+        .assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(
+            "usage error: unknown prompt placeholder: %z\n",
+        ));
+}
+
+#[test]
+fn novcs() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_vcs;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("prompt").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"))
+        .code(i32::from(EXIT_NOT_A_REPOSITORY));
+}
+
+#[test]
+fn non_dir() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_dir;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("prompt").arg(test_dir).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NOT_VALID_DIR.to_string() + "\n"));
+}
+
+#[test]
+fn non_extant() {
+    let test_dirs = &TestDirs::create_once(&TEST_SCOPE);
+    let non_extant_path = test_dirs.non_extant();
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd.arg("prompt").arg(non_extant_path).assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NOT_VALID_DIR.to_string() + "\n"));
+}
@@ -2,6 +2,7 @@ use crate::libtest::consts::{ERROR_NOT_VALID_DIR, ERROR_NO_KNOWN_VCS};
 use crate::libtest::setup::{TestDirs, TestScope};
 use assert_cmd::Command;
 use predicates::prelude::*;
+use vcsq_cli::EXIT_NOT_A_REPOSITORY;
 
 static TEST_SCOPE: TestScope = TestScope::new("cmd_root.rs");
 
@@ -46,6 +47,197 @@ fn jj() {
         .stderr(predicate::str::is_empty())
         .stdout(predicate::str::diff(expected_root + "\n"));
 }
+
+#[test]
+fn svn() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).svn_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let expected_root = test_dir.display().to_string();
+
+    let assert = cmd.arg("root").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(expected_root + "\n"));
+}
+
+#[test]
+fn fossil() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).fossil_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let expected_root = test_dir.display().to_string();
+
+    let assert = cmd.arg("root").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(expected_root + "\n"));
+}
+
+#[test]
+fn fossil_relative_subdir() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).fossil_repo;
+    let sub_dir = test_dir.join("fossil-relative-sub");
+    std::fs::create_dir(&sub_dir).expect("test arrange: mkdir failed");
+    let expected_root = test_dir.display().to_string();
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.current_dir(&sub_dir).arg("root").arg(".").assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(expected_root.clone() + "\n"));
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .current_dir(test_dir)
+        .arg("root")
+        .arg("fossil-relative-sub")
+        .assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(expected_root + "\n"));
+}
+
+#[test]
+fn pijul() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).pijul_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let expected_root = test_dir.display().to_string();
+
+    let assert = cmd.arg("root").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(expected_root + "\n"));
+}
+
+#[test]
+fn pijul_relative_subdir() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).pijul_repo;
+    let sub_dir = test_dir.join("pijul-relative-sub");
+    std::fs::create_dir(&sub_dir).expect("test arrange: mkdir failed");
+    let expected_root = test_dir.display().to_string();
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.current_dir(&sub_dir).arg("root").arg(".").assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(expected_root.clone() + "\n"));
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .current_dir(test_dir)
+        .arg("root")
+        .arg("pijul-relative-sub")
+        .assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(expected_root + "\n"));
+}
+
+#[test]
+fn bzr() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).bzr_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let expected_root = test_dir.display().to_string();
+
+    let assert = cmd.arg("root").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(expected_root + "\n"));
+}
+
+#[test]
+fn darcs() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).darcs_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let expected_root = test_dir.display().to_string();
+
+    let assert = cmd.arg("root").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(expected_root + "\n"));
+}
+
+#[test]
+fn darcs_relative_subdir() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).darcs_repo;
+    let sub_dir = test_dir.join("darcs-relative-sub");
+    std::fs::create_dir(&sub_dir).expect("test arrange: mkdir failed");
+    let expected_root = test_dir.display().to_string();
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.current_dir(&sub_dir).arg("root").arg(".").assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(expected_root.clone() + "\n"));
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .current_dir(test_dir)
+        .arg("root")
+        .arg("darcs-relative-sub")
+        .assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(expected_root + "\n"));
+}
+
+#[test]
+fn cvs() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).cvs_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let expected_root = test_dir.display().to_string();
+
+    let assert = cmd.arg("root").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(expected_root + "\n"));
+}
+
+#[test]
+fn cvs_relative() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).cvs_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let expected_root = test_dir.display().to_string();
+
+    let assert = cmd.current_dir(test_dir).arg("root").arg(".").assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(expected_root + "\n"));
+}
+
+#[test]
+fn sl() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).sl_repo;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let expected_root = test_dir.display().to_string();
+
+    let assert = cmd.arg("root").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(expected_root + "\n"));
+}
+
 #[test]
 fn novcs() {
     let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_vcs;
@@ -55,7 +247,8 @@ fn novcs() {
     assert
         .failure()
         .stdout(predicate::str::is_empty())
-        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"));
+        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"))
+        .code(i32::from(EXIT_NOT_A_REPOSITORY));
 }
 
 #[test]
@@ -2,6 +2,7 @@ use crate::libtest::consts::{ERROR_NOT_VALID_DIR, ERROR_NO_KNOWN_VCS};
 use crate::libtest::setup::{git_cmd_args, make_test_temp, vcs_test_setup, TestDirs, TestScope};
 use assert_cmd::Command;
 use predicates::prelude::*;
+use vcsq_cli::EXIT_NOT_A_REPOSITORY;
 
 static TEST_SCOPE: TestScope = TestScope::new("cmd_tracked_files.rs");
 
@@ -27,7 +28,7 @@ fn git() {
         touched_file.push(basename);
         make_test_temp::touch(touched_file.as_ref()).unwrap();
     }
-    vcs_test_setup::run_cli_from_tempdir("git", &["add", "."], &test_dir).unwrap();
+    vcs_test_setup::run_cli_from_tempdir("git", &["add", "."], test_dir).unwrap();
     vcs_test_setup::run_cli_from_tempdir(
         "git",
         git_cmd_args(&[
@@ -37,7 +38,7 @@ fn git() {
             "test arrange phase: ensuring git history",
         ])
         .as_slice(),
-        &test_dir,
+        test_dir,
     )
     .unwrap();
 
@@ -74,7 +75,7 @@ fn hg() {
         touched_file.push(basename);
         make_test_temp::touch(touched_file.as_ref()).unwrap();
     }
-    vcs_test_setup::run_cli_from_tempdir("hg", &["add", "."], &test_dir).unwrap();
+    vcs_test_setup::run_cli_from_tempdir("hg", &["add", "."], test_dir).unwrap();
     vcs_test_setup::run_cli_from_tempdir(
         "hg",
         &[
@@ -82,7 +83,7 @@ fn hg() {
             "--message",
             "test arrange phase: ensuring hg history",
         ],
-        &test_dir,
+        test_dir,
     )
     .unwrap();
 
@@ -126,7 +127,7 @@ fn jj() {
             "--message",
             "test arrange phase: ensuring jj history",
         ],
-        &test_dir,
+        test_dir,
     )
     .unwrap();
 
@@ -141,6 +142,240 @@ fn jj() {
         .stdout(predicate::str::diff(tracked_files.join("\n") + "\n"));
 }
 
+#[test]
+fn svn() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).svn_repo;
+
+    //
+    // Assert: nothing to track yet, so no output
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("tracked-files").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::is_empty());
+
+    //
+    // Arrange: ensure we have history to talk about the tracking of
+    //
+    let tracked_files = vec!["svn-license.txt", "svn-readme.txt"];
+    for basename in &tracked_files {
+        vcs_test_setup::commit_touched_svn(test_dir, basename).unwrap();
+    }
+
+    //
+    // Assert: actual history to report, all committed files listed
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("tracked-files").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(tracked_files.join("\n") + "\n"));
+}
+
+#[test]
+fn fossil() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).fossil_repo;
+
+    //
+    // Assert: nothing to track yet, so no output
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("tracked-files").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::is_empty());
+
+    //
+    // Arrange: ensure we have history to talk about the tracking of
+    //
+    let tracked_files = vec!["fossil-license.txt", "fossil-readme.txt"];
+    for basename in &tracked_files {
+        vcs_test_setup::commit_touched_fossil(test_dir, basename).unwrap();
+    }
+
+    //
+    // Assert: actual history to report, all committed files listed
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("tracked-files").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(tracked_files.join("\n") + "\n"));
+}
+
+#[test]
+fn pijul() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).pijul_repo;
+
+    //
+    // Assert: nothing to track yet, so no output
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("tracked-files").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::is_empty());
+
+    //
+    // Arrange: track some files (pijul tracks files from the moment they're added)
+    //
+    let tracked_files = vec!["pijul-license.txt", "pijul-readme.txt"];
+    for basename in &tracked_files {
+        let mut touched_file = test_dir.clone();
+        touched_file.push(basename);
+        make_test_temp::touch(touched_file.as_ref()).unwrap();
+        vcs_test_setup::run_cli_from_tempdir("pijul", &["add", basename], test_dir).unwrap();
+    }
+
+    //
+    // Assert: all tracked files listed
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("tracked-files").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(tracked_files.join("\n") + "\n"));
+}
+
+#[test]
+fn bzr() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).bzr_repo;
+
+    //
+    // Assert: nothing to track yet, so no output
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("tracked-files").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::is_empty());
+
+    //
+    // Arrange: ensure we have history to talk about the tracking of
+    //
+    let tracked_files = vec!["bzr-license.txt", "bzr-readme.txt"];
+    for basename in &tracked_files {
+        vcs_test_setup::commit_touched_bzr(test_dir, basename).unwrap();
+    }
+
+    //
+    // Assert: actual history to report, all committed files listed
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("tracked-files").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(tracked_files.join("\n") + "\n"));
+}
+
+#[test]
+fn darcs() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).darcs_repo;
+
+    //
+    // Assert: nothing to track yet, so no output
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("tracked-files").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::is_empty());
+
+    //
+    // Arrange: ensure we have history to talk about the tracking of
+    //
+    let tracked_files = vec!["darcs-license.txt", "darcs-readme.txt"];
+    for basename in &tracked_files {
+        vcs_test_setup::commit_touched_darcs(test_dir, basename).unwrap();
+    }
+
+    //
+    // Assert: actual history to report, all recorded files listed
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("tracked-files").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(tracked_files.join("\n") + "\n"));
+}
+
+#[test]
+fn cvs() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).cvs_repo;
+
+    //
+    // Assert: nothing to track yet, so no output
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("tracked-files").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::is_empty());
+
+    //
+    // Arrange: ensure we have history to talk about the tracking of
+    //
+    let tracked_files = vec!["cvs-license.txt", "cvs-readme.txt"];
+    for basename in &tracked_files {
+        vcs_test_setup::commit_touched_cvs(test_dir, basename).unwrap();
+    }
+
+    //
+    // Assert: actual history to report, all committed files listed
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("tracked-files").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(tracked_files.join("\n") + "\n"));
+}
+
+#[test]
+fn sl() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).sl_repo;
+
+    //
+    // Assert: nothing to track yet, so no output
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("tracked-files").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::is_empty());
+
+    //
+    // Arrange: ensure we have history to talk about the tracking of
+    //
+    let tracked_files = vec!["sl-license.txt", "sl-readme.txt"];
+    for basename in &tracked_files {
+        vcs_test_setup::commit_touched_sl(test_dir, basename).unwrap();
+    }
+
+    //
+    // Assert: actual history to report, all committed files listed
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd.arg("tracked-files").arg(test_dir).assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::diff(tracked_files.join("\n") + "\n"));
+}
+
 #[test]
 fn novcs() {
     let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_vcs;
@@ -150,7 +385,8 @@ fn novcs() {
     assert
         .failure()
         .stdout(predicate::str::is_empty())
-        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"));
+        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"))
+        .code(i32::from(EXIT_NOT_A_REPOSITORY));
 }
 
 #[test]
@@ -0,0 +1,187 @@
+//! Drives the reference external adapter, `tests/fixtures/vcsq-adapter-fakevcs`, via `$PATH`.
+use crate::libtest::setup::{make_test_temp, TestDirs, TestScope};
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+use vcsq_cli::{EXIT_NOT_A_REPOSITORY, EXIT_UNSUPPORTED};
+
+static TEST_SCOPE: TestScope = TestScope::new("external.rs");
+
+/// Creates a fakevcs repo named `basename`, tracking `tracked.md` (with `untracked.md` beside it)
+/// and at revision `abc123` named `main`.
+fn fake_repo(basename: &str) -> PathBuf {
+    let mut repo = TestDirs::create_once(&TEST_SCOPE).root_dir;
+    repo.push(basename);
+    let meta = repo.join(".fakevcs");
+    fs::create_dir_all(&meta).expect("test arrange: mkdir failed");
+    fs::write(meta.join("tracked"), "tracked.md\n").expect("test arrange: write failed");
+    fs::write(meta.join("id"), "abc123").expect("test arrange: write failed");
+    fs::write(meta.join("name"), "main").expect("test arrange: write failed");
+    make_test_temp::touch(&repo.join("tracked.md")).expect("test arrange: touch failed");
+    make_test_temp::touch(&repo.join("untracked.md")).expect("test arrange: touch failed");
+    repo
+}
+
+/// The vcsq binary, with the reference adapter on its `$PATH`.
+fn vcsq() -> Command {
+    vcsq_with_adapters("tests/fixtures")
+}
+
+/// The vcsq binary, with the adapters in `fixtures` (relative to this crate) on its `$PATH`.
+fn vcsq_with_adapters(fixtures: &str) -> Command {
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(fixtures);
+    let mut path = vec![fixtures];
+    path.extend(std::env::split_paths(
+        &std::env::var_os("PATH").unwrap_or_default(),
+    ));
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    cmd.env("PATH", std::env::join_paths(path).unwrap());
+    cmd
+}
+
+#[test]
+fn brand() {
+    let test_dir = fake_repo("fakevcs-brand");
+    vcsq()
+        .arg("brand")
+        .arg(&test_dir)
+        .assert()
+        .success()
+        .stdout(predicate::eq("fakevcs\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn forced_brand() {
+    let test_dir = fake_repo("fakevcs-forced");
+    vcsq()
+        .arg("brand")
+        .arg("--brand")
+        .arg("FakeVCS")
+        .arg(&test_dir)
+        .assert()
+        .success()
+        .stdout(predicate::eq("fakevcs\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn forced_brand_rejected() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).not_vcs;
+    vcsq()
+        .arg("brand")
+        .arg(&test_dir)
+        .env("VCSQ_BRAND", "fakevcs")
+        .assert()
+        .code(i32::from(EXIT_NOT_A_REPOSITORY))
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::eq(
+            "vcs error: dir is not a repo of the brand it was opened as: fakevcs\n",
+        ));
+}
+
+#[test]
+fn broken_adapter_beside_builtin() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).git_repo;
+    vcsq_with_adapters("tests/fixtures/broken")
+        .arg("brand")
+        .arg(&test_dir)
+        .assert()
+        .success()
+        .stdout(predicate::eq("Git\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn broken_adapter_outside_any_repo() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).not_vcs;
+    vcsq_with_adapters("tests/fixtures/broken")
+        .arg("brand")
+        .arg(&test_dir)
+        .assert()
+        .code(i32::from(EXIT_NOT_A_REPOSITORY))
+        .stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn root() {
+    let test_dir = fake_repo("fakevcs-root");
+    let sub_dir = test_dir.join("docs");
+    fs::create_dir(&sub_dir).expect("test arrange: mkdir failed");
+    vcsq()
+        .arg("root")
+        .arg(&sub_dir)
+        .assert()
+        .success()
+        .stdout(predicate::eq(format!("{}\n", test_dir.display())))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn current_id_and_name() {
+    let test_dir = fake_repo("fakevcs-current");
+    vcsq()
+        .arg("current-id")
+        .arg("--dirty-ok")
+        .arg(&test_dir)
+        .assert()
+        .success()
+        .stdout(predicate::eq("abc123\n"))
+        .stderr(predicate::str::is_empty());
+    vcsq()
+        .arg("current-name")
+        .arg("--dirty-ok")
+        .arg(&test_dir)
+        .assert()
+        .success()
+        .stdout(predicate::eq("main\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn files() {
+    let test_dir = fake_repo("fakevcs-files");
+    vcsq()
+        .arg("tracked-files")
+        .arg(&test_dir)
+        .assert()
+        .success()
+        .stdout(predicate::eq("tracked.md\n"))
+        .stderr(predicate::str::is_empty());
+    vcsq()
+        .arg("dirty-files")
+        .arg("--status")
+        .arg(&test_dir)
+        .assert()
+        .success()
+        .stdout(predicate::eq("?  untracked.md\n"))
+        .stderr(predicate::str::is_empty());
+    vcsq()
+        .arg("is-clean")
+        .arg(&test_dir)
+        .assert()
+        .failure()
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn unsupported() {
+    let test_dir = fake_repo("fakevcs-unsupported");
+    vcsq()
+        .arg("parent-id")
+        .arg(&test_dir)
+        .assert()
+        .code(i32::from(EXIT_UNSUPPORTED))
+        .stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn check_health() {
+    vcsq()
+        .arg("check-health")
+        .assert()
+        .stdout(predicate::str::contains(
+            "PASS: check for fakevcs:\nfakevcs 0.1.0",
+        ));
+}
@@ -0,0 +1,250 @@
+use crate::libtest::consts::ERROR_NO_KNOWN_VCS;
+use crate::libtest::setup::{make_test_temp, vcs_test_setup, TestDirs, TestScope};
+use assert_cmd::Command;
+use predicates::prelude::*;
+use vcsq_cli::EXIT_NOT_A_REPOSITORY;
+
+static TEST_SCOPE: TestScope = TestScope::new("format.rs");
+
+#[test]
+fn git() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).git_repo;
+
+    //
+    // Assert: single answers are single json documents
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("--format")
+        .arg("json")
+        .arg("brand")
+        .arg(test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("{\"brand\":\"Git\"}\n"))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("is-clean")
+        .arg("--format")
+        .arg("json")
+        .arg(test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("{\"clean\":true}\n"))
+        .stderr(predicate::str::is_empty());
+
+    //
+    // Arrange: some history and some dirty state
+    //
+    vcs_test_setup::commit_touched_git(test_dir, "git-license.txt")
+        .expect("test arrange: commit failed");
+    vcs_test_setup::commit_touched_git(test_dir, "git-readme.txt")
+        .expect("test arrange: commit failed");
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("git-docs.md");
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+
+    //
+    // Assert: list answers are one json array, or one json document per line
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("--format")
+        .arg("json")
+        .arg("tracked-files")
+        .arg(test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(
+            "[{\"path\":\"git-license.txt\"},{\"path\":\"git-readme.txt\"}]\n",
+        ))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("--format")
+        .arg("jsonl")
+        .arg("tracked-files")
+        .arg(test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(
+            "{\"path\":\"git-license.txt\"}\n{\"path\":\"git-readme.txt\"}\n",
+        ))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("--format")
+        .arg("jsonl")
+        .arg("dirty-files")
+        .arg("--status")
+        .arg(test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff(
+            "{\"kind\":\"untracked\",\"path\":\"git-docs.md\",\"original_path\":null,\"staged\":false}\n",
+        ))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("--format")
+        .arg("json")
+        .arg("is-clean")
+        .arg(test_dir)
+        .assert();
+    assert
+        .failure()
+        .stdout(predicate::str::diff("{\"clean\":false}\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn hg() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).hg_repo;
+
+    //
+    // Arrange: some dirty state
+    //
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("mercurial.md");
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+
+    //
+    // Assert
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("--format")
+        .arg("json")
+        .arg("brand")
+        .arg(test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("{\"brand\":\"Mercurial\"}\n"))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("--format")
+        .arg("json")
+        .arg("dirty-files")
+        .arg(test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("[{\"path\":\"mercurial.md\"}]\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn jj() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).jj_repo;
+
+    //
+    // Arrange: some dirty state
+    //
+    let mut untracked_file = test_dir.clone();
+    untracked_file.push("jj-vcs-docs.md");
+    make_test_temp::touch(&untracked_file).expect("test arrange: touch failed");
+
+    //
+    // Assert
+    //
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("--format")
+        .arg("json")
+        .arg("brand")
+        .arg(test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("{\"brand\":\"Jujutsu\"}\n"))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+    let assert = cmd
+        .arg("--format")
+        .arg("json")
+        .arg("dirty-files")
+        .arg(test_dir)
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::diff("[{\"path\":\"jj-vcs-docs.md\"}]\n"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn check_health() {
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    let assert = cmd
+        .arg("--format")
+        .arg("jsonl")
+        .arg("check-health")
+        .assert();
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout(predicate::str::contains(
+            "{\"brand\":\"Git\",\"ok\":true,\"output\":\"git version",
+        ))
+        .stdout(predicate::str::contains(
+            "{\"brand\":\"Mercurial\",\"ok\":true,\"output\":\"Mercurial Distributed SCM (version",
+        ))
+        .stdout(predicate::str::contains(
+            "{\"brand\":\"Jujutsu\",\"ok\":true,\"output\":\"jj ",
+        ))
+        .stdout(predicate::str::contains(
+            "{\"brand\":\"Subversion\",\"ok\":true,\"output\":\"1.",
+        ))
+        .stdout(predicate::str::contains(
+            "{\"brand\":\"Fossil\",\"ok\":true,\"output\":\"This is fossil version",
+        ))
+        .stdout(predicate::str::contains(
+            "{\"brand\":\"Pijul\",\"ok\":true,\"output\":\"pijul ",
+        ))
+        .stdout(predicate::str::contains(
+            "{\"brand\":\"Bazaar\",\"ok\":true,\"output\":\"Breezy (brz)",
+        ))
+        .stdout(predicate::str::contains(
+            "{\"brand\":\"Darcs\",\"ok\":true,\"output\":\"",
+        ))
+        .stdout(predicate::str::contains(
+            "{\"brand\":\"Cvs\",\"ok\":true,\"output\":\"",
+        ))
+        .stdout(predicate::str::contains(
+            "{\"brand\":\"Sapling\",\"ok\":true,\"output\":\"Sapling ",
+        ))
+        .stdout(predicate::str::contains("\"error\":null}\n").count(10));
+}
+
+#[test]
+fn novcs() {
+    let test_dir = &TestDirs::create_once(&TEST_SCOPE).not_vcs;
+    let mut cmd = Command::cargo_bin("vcsq").unwrap();
+
+    // Errors are still plain text on stderr
+    let assert = cmd
+        .arg("--format")
+        .arg("json")
+        .arg("brand")
+        .arg(test_dir)
+        .assert();
+    assert
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::diff(ERROR_NO_KNOWN_VCS.to_string() + "\n"))
+        .code(i32::from(EXIT_NOT_A_REPOSITORY));
+}
@@ -1,8 +1,19 @@
 mod cmd_brand;
 mod cmd_check_health;
+mod cmd_child_id;
+mod cmd_current_files;
 mod cmd_current_id;
+mod cmd_current_name;
 mod cmd_dirty;
 mod cmd_is_clean;
+mod cmd_parent_id;
+mod cmd_parent_name;
+mod cmd_prompt;
 mod cmd_root;
 mod cmd_tracked_files;
+mod external;
+mod format;
+#[cfg(any(feature = "native-git", feature = "native-jj", feature = "native-hg"))]
+mod native_parity;
+mod registry;
 mod usage;
@@ -0,0 +1,146 @@
+//! Asks each in-process ("native") driver the same questions as the CLI-based driver it stands in
+//! for, expecting identical answers.
+use crate::libtest::setup::{make_test_temp, vcs_test_setup, TestDirs, TestScope};
+use std::path::Path;
+use vcsq_lib::repo::Validator;
+
+static TEST_SCOPE: TestScope = TestScope::new("native_parity.rs");
+
+/// Asserts both drivers of `dir` answer the same (for the questions native drivers answer
+/// themselves, rather than deferring to their CLI-based driver).
+fn assert_parity(cli: &dyn Validator, native: &dyn Validator, dir: &Path) {
+    let cli = cli
+        .new_driver(dir.to_path_buf())
+        .unwrap()
+        .expect("cli driver should recognize repo");
+    let native = native
+        .new_driver(dir.to_path_buf())
+        .unwrap()
+        .expect("native driver should recognize repo");
+
+    assert_eq!(native.brand(), cli.brand());
+    assert_eq!(native.root().unwrap(), cli.root().unwrap());
+    assert_eq!(native.status().unwrap(), cli.status().unwrap());
+    assert_eq!(
+        native.tracked_files().unwrap(),
+        cli.tracked_files().unwrap()
+    );
+    assert_eq!(
+        format!("{:?}", native.current_ref_id(true /*dirty_ok*/)),
+        format!("{:?}", cli.current_ref_id(true /*dirty_ok*/)),
+    );
+    assert_eq!(
+        native.current_ref_name(true /*dirty_ok*/).unwrap(),
+        cli.current_ref_name(true /*dirty_ok*/).unwrap(),
+    );
+}
+
+#[cfg(feature = "native-git")]
+#[test]
+fn git() {
+    use vcsq_lib::adapter::{git, git_native};
+
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).git_repo;
+
+    //
+    // Assert: no history yet
+    //
+    assert_parity(&git::Loader {}, &git_native::Loader {}, &test_dir);
+
+    //
+    // Arrange+Assert: tagged history, then a commit past the tag
+    //
+    vcs_test_setup::commit_touched_git(&test_dir, "git-first.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir("git", &["tag", "v1.0.0"], &test_dir).unwrap();
+    assert_parity(&git::Loader {}, &git_native::Loader {}, &test_dir);
+    vcs_test_setup::run_cli_from_tempdir("git", &["checkout", "--quiet", "v1.0.0"], &test_dir)
+        .unwrap();
+    assert_parity(&git::Loader {}, &git_native::Loader {}, &test_dir);
+    vcs_test_setup::run_cli_from_tempdir("git", &["checkout", "--quiet", "-"], &test_dir).unwrap();
+    vcs_test_setup::commit_touched_git(&test_dir, "git-second.md").unwrap();
+    assert_parity(&git::Loader {}, &git_native::Loader {}, &test_dir);
+
+    //
+    // Arrange+Assert: uncommitted edits, asked about from a subdirectory
+    //
+    let mut sub_dir = test_dir.clone();
+    sub_dir.push("docs");
+    std::fs::create_dir(&sub_dir).expect("test arrange: mkdir failed");
+    let mut new_file = sub_dir.clone();
+    new_file.push("git-docs.md");
+    make_test_temp::touch(&new_file).expect("test arrange: touch failed");
+    assert_parity(&git::Loader {}, &git_native::Loader {}, &sub_dir);
+}
+
+#[cfg(feature = "native-jj")]
+#[test]
+fn jj() {
+    use vcsq_lib::adapter::{jj, jj_native};
+
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).jj_repo;
+
+    //
+    // Assert: no history yet
+    //
+    assert_parity(&jj::Loader {}, &jj_native::Loader {}, &test_dir);
+
+    //
+    // Arrange+Assert: bookmarked history
+    //
+    vcs_test_setup::commit_touched_jj(&test_dir, "jj-first.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir(
+        "jj",
+        &["bookmark", "create", "--revision", "@-", "main"],
+        &test_dir,
+    )
+    .unwrap();
+    assert_parity(&jj::Loader {}, &jj_native::Loader {}, &test_dir);
+
+    //
+    // Arrange+Assert: edits jj hasn't snapshotted yet, asked about from a subdirectory
+    //
+    let mut sub_dir = test_dir.clone();
+    sub_dir.push("docs");
+    std::fs::create_dir(&sub_dir).expect("test arrange: mkdir failed");
+    let mut new_file = sub_dir.clone();
+    new_file.push("jj-docs.md");
+    make_test_temp::touch(&new_file).expect("test arrange: touch failed");
+    assert_parity(&jj::Loader {}, &jj_native::Loader {}, &sub_dir);
+}
+
+#[cfg(feature = "native-hg")]
+#[test]
+fn hg() {
+    use vcsq_lib::adapter::{hg, hg_native};
+
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).hg_repo;
+
+    //
+    // Assert: no history yet
+    //
+    assert_parity(&hg::Loader {}, &hg_native::Loader {}, &test_dir);
+
+    //
+    // Arrange+Assert: tagged history, then a commit past the tag
+    //
+    vcs_test_setup::commit_touched_hg(&test_dir, "hg-first.md").unwrap();
+    vcs_test_setup::run_cli_from_tempdir("hg", &["tag", "v1.0.0"], &test_dir).unwrap();
+    assert_parity(&hg::Loader {}, &hg_native::Loader {}, &test_dir);
+    vcs_test_setup::run_cli_from_tempdir("hg", &["bookmark", "feature"], &test_dir).unwrap();
+    assert_parity(&hg::Loader {}, &hg_native::Loader {}, &test_dir);
+    vcs_test_setup::run_cli_from_tempdir("hg", &["update", "--rev", "v1.0.0"], &test_dir).unwrap();
+    assert_parity(&hg::Loader {}, &hg_native::Loader {}, &test_dir);
+    vcs_test_setup::commit_touched_hg(&test_dir, "hg-second.md").unwrap();
+    assert_parity(&hg::Loader {}, &hg_native::Loader {}, &test_dir);
+
+    //
+    // Arrange+Assert: uncommitted edits, asked about from a subdirectory
+    //
+    let mut sub_dir = test_dir.clone();
+    sub_dir.push("docs");
+    std::fs::create_dir(&sub_dir).expect("test arrange: mkdir failed");
+    let mut new_file = sub_dir.clone();
+    new_file.push("hg-docs.md");
+    make_test_temp::touch(&new_file).expect("test arrange: touch failed");
+    assert_parity(&hg::Loader {}, &hg_native::Loader {}, &sub_dir);
+}
@@ -0,0 +1,190 @@
+//! Plugs a downstream adapter (for a brand vcsq_lib doesn't know) into the CLI, via
+//! `vcsq_cli::main_vcsquery_with`.
+use crate::libtest::setup::{make_test_temp, TestDirs, TestScope};
+use clap::Parser;
+use std::num::NonZero;
+use std::process::ExitStatus;
+use vcsq_cli::{main_vcsquery_with, MainArgs};
+use vcsq_lib::plexer::{Registry, VcsBrand};
+use vcsq_lib::repo::{
+    AheadBehind, AncestorRef, Driver, DriverError, FileStatus, HistoryRefId, HistoryRefName,
+    QueryDir, RepoOperation, Validator, VcsAvailable,
+};
+
+static TEST_SCOPE: TestScope = TestScope::new("registry.rs");
+
+/// File marking a directory as a repo of the fake "Marker" VCS.
+const MARKER_FILE: &str = ".marker-vcs";
+
+#[derive(Debug)]
+struct MarkerLoader;
+
+#[derive(Debug)]
+struct MarkerRepo {
+    root: QueryDir,
+}
+
+impl Validator for MarkerLoader {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Other("Marker".to_string())
+    }
+
+    fn new_driver(&self, dir: QueryDir) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        if !dir.join(MARKER_FILE).exists() {
+            return Ok(None);
+        }
+        Ok(Some(Box::from(MarkerRepo { root: dir })))
+    }
+
+    fn check_health(&self) -> Result<VcsAvailable, DriverError> {
+        Ok(VcsAvailable {
+            status: ExitStatus::default(),
+            stdout: "marker 1.0".to_string(),
+            stderr: String::new(),
+        })
+    }
+}
+
+impl Driver for MarkerRepo {
+    fn brand(&self) -> VcsBrand {
+        MarkerLoader.brand()
+    }
+
+    fn root(&self) -> Result<QueryDir, DriverError> {
+        Ok(self.root.clone())
+    }
+
+    fn status(&self) -> Result<Vec<FileStatus>, DriverError> {
+        Ok(vec![])
+    }
+
+    fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError> {
+        Ok(vec![])
+    }
+
+    fn changed_files(
+        &self,
+        _base: Option<&str>,
+        _head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError> {
+        Ok(vec![])
+    }
+
+    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        Err(DriverError::EmptyHistory)
+    }
+
+    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError> {
+        Ok(None)
+    }
+
+    fn first_ancestor_ref_name(
+        &self,
+        _limit: Option<NonZero<u64>>,
+    ) -> Result<Option<AncestorRef>, DriverError> {
+        Ok(None)
+    }
+
+    fn child_ref_ids(&self, _max: Option<NonZero<u64>>) -> Result<Vec<HistoryRefId>, DriverError> {
+        Ok(vec![])
+    }
+
+    fn ahead_behind(&self) -> Result<Option<AheadBehind>, DriverError> {
+        Ok(None)
+    }
+
+    fn operation(&self) -> Result<Option<RepoOperation>, DriverError> {
+        Ok(None)
+    }
+
+    fn current_ref_id(&self, _dirty_ok: bool) -> Result<HistoryRefId, DriverError> {
+        Err(DriverError::EmptyHistory)
+    }
+
+    fn current_ref_name(&self, _dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
+        Ok(None)
+    }
+}
+
+/// Adapter of the same brand as `MarkerLoader`, that never recognizes anything.
+#[derive(Debug)]
+struct BlindMarkerLoader;
+
+impl Validator for BlindMarkerLoader {
+    fn brand(&self) -> VcsBrand {
+        MarkerLoader.brand()
+    }
+
+    fn new_driver(&self, _dir: QueryDir) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        Ok(None)
+    }
+
+    fn check_health(&self) -> Result<VcsAvailable, DriverError> {
+        MarkerLoader.check_health()
+    }
+}
+
+/// Runs the CLI with `args` against the built-in adapters plus `MarkerLoader`, returning its exit
+/// code, stdout and stderr.
+fn run_with_marker(args: &[&str]) -> (u8, String, String) {
+    run_with(
+        Registry::with_defaults().register(Box::from(MarkerLoader)),
+        args,
+    )
+}
+
+/// Runs the CLI with `args` against `registry`'s adapters, returning its exit code, stdout and
+/// stderr.
+fn run_with(registry: Registry, args: &[&str]) -> (u8, String, String) {
+    let args = MainArgs::parse_from(std::iter::once("vcsq").chain(args.iter().copied()));
+    let (mut stdout, mut stderr) = (vec![], vec![]);
+    let exit_code = main_vcsquery_with(&registry, &args, &mut stdout, &mut stderr);
+    (
+        exit_code,
+        String::from_utf8(stdout).unwrap(),
+        String::from_utf8(stderr).unwrap(),
+    )
+}
+
+#[test]
+fn brand() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).not_vcs;
+    let mut marker = test_dir.clone();
+    marker.push(MARKER_FILE);
+    make_test_temp::touch(&marker).expect("test arrange: touch failed");
+
+    let (exit_code, stdout, stderr) = run_with_marker(&["brand", test_dir.to_str().unwrap()]);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout, "Marker\n");
+    assert_eq!(exit_code, 0);
+
+    let (exit_code, stdout, _) =
+        run_with_marker(&["brand", "--format", "json", test_dir.to_str().unwrap()]);
+    assert_eq!(stdout, "{\"brand\":\"Marker\"}\n");
+    assert_eq!(exit_code, 0);
+}
+
+#[test]
+fn fallback_leaves_registered_brand() {
+    let test_dir = TestDirs::create_once(&TEST_SCOPE).not_vcs;
+    let mut marker = test_dir.clone();
+    marker.push(MARKER_FILE);
+    make_test_temp::touch(&marker).expect("test arrange: touch failed");
+
+    let registry = Registry::with_defaults()
+        .register(Box::from(MarkerLoader))
+        .register_fallback(Box::from(BlindMarkerLoader));
+    let (exit_code, stdout, stderr) = run_with(registry, &["brand", test_dir.to_str().unwrap()]);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout, "Marker\n");
+    assert_eq!(exit_code, 0);
+}
+
+#[test]
+fn check_health() {
+    let (_, stdout, _) = run_with_marker(&["check-health"]);
+    assert!(
+        stdout.contains("PASS: check for Marker:\nmarker 1.0"),
+        "missing Marker's report in: {stdout}"
+    );
+}
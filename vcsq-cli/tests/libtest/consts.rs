@@ -1,5 +1,5 @@
 pub static ERROR_NO_KNOWN_VCS: &str =
-    "vcs error: if dir is a VCS, it\'s of an unknown brand (tried these 3: Git, Mercurial, Jujutsu)";
+    "vcs error: if dir is a VCS, it\'s of an unknown brand (tried these 10: Git, Mercurial, Jujutsu, Subversion, Fossil, Pijul, Bazaar, Darcs, Cvs, Sapling)";
 
 pub static ERROR_NOT_VALID_DIR: &str = "usage error: dir must be a readable directory";
 
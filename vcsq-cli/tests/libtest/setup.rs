@@ -5,6 +5,19 @@ use thiserror::Error;
 pub static TEST_VCS_BASENAME_GIT: &str = "test-git-repo";
 pub static TEST_VCS_BASENAME_HG: &str = "test-hg-repo";
 pub static TEST_VCS_BASENAME_JJ: &str = "test-jj-on-git-repo";
+pub static TEST_VCS_BASENAME_SVN: &str = "test-svn-repo";
+/// Repository (ie: the server side) `TEST_VCS_BASENAME_SVN` is a working copy of.
+pub static TEST_VCS_BASENAME_SVN_SERVER: &str = "test-svn-server";
+pub static TEST_VCS_BASENAME_FOSSIL: &str = "test-fossil-repo";
+/// Repository database `TEST_VCS_BASENAME_FOSSIL` is a checkout of (kept outside the checkout, so
+/// it's not reported as an untracked file).
+pub static TEST_VCS_BASENAME_FOSSIL_DB: &str = "test-fossil-repo.fossil";
+pub static TEST_VCS_BASENAME_PIJUL: &str = "test-pijul-repo";
+pub static TEST_VCS_BASENAME_BZR: &str = "test-bzr-repo";
+pub static TEST_VCS_BASENAME_DARCS: &str = "test-darcs-repo";
+pub static TEST_VCS_BASENAME_CVS: &str = "test-cvs-repo";
+pub static TEST_VCS_BASENAME_CVS_SERVER: &str = "test-cvs-server";
+pub static TEST_VCS_BASENAME_SL: &str = "test-sl-repo";
 pub static TEST_VCS_BASENAME_NONVCS: &str = "test-not-vcs";
 pub static TEST_VCS_BASENAME_NONDIR: &str = "test-not-dir";
 pub static TEST_SUBDIR_NAME_SUFFIX: &str = "testscope";
@@ -17,9 +30,21 @@ static ENVVAR_OVERRIDE_TESTDIR_ROOT: &str = "VCSQ_TESTDIR";
 /// /tmp/foo, depending on `ENVVAR_OVERRIDE_TESTDIR_ROOT` being present).
 static TESTDIR_TMPDIR_ROOT: &str = "vcsq-e2e-testdirs";
 
+/// Fossil user that owns (and so makes all the check-ins of) the test repo.
+static FAKE_FOSSIL_USER: &str = "e2etests-vcsq";
+
+/// Identity Breezy commits are made as (which it otherwise refuses to guess).
+static FAKE_BZR_WHOAMI: &str = "e2etests Vcsq <e2e-tests@vcsq.codebase>";
+
+/// Author Darcs patches (and tags) are recorded as, so it never prompts for one.
+static FAKE_DARCS_AUTHOR: &str = "e2etests Vcsq <e2e-tests@vcsq.codebase>";
+
+/// Sapling config flag naming who commits are made by (which it otherwise refuses to guess).
+static FAKE_SL_USERNAME_CONFIG: &str = "ui.username=e2etests Vcsq <e2e-tests@vcsq.codebase>";
+
 /// config flags that should be passed before the verb of a git command (eg: before `commit`
 /// subcommand) to avoid errors about global config (say in a ci/cd).
-static FAKE_GIT_CONFIG_FLAGS: &'static [&'static str; 4] = &[
+static FAKE_GIT_CONFIG_FLAGS: &[&str; 4] = &[
     "-c",
     "user.name='e2etests Vcsq'",
     "-c",
@@ -68,6 +93,13 @@ pub struct TestDirs {
     pub git_repo: PathBuf,
     pub hg_repo: PathBuf,
     pub jj_repo: PathBuf,
+    pub svn_repo: PathBuf,
+    pub fossil_repo: PathBuf,
+    pub pijul_repo: PathBuf,
+    pub bzr_repo: PathBuf,
+    pub darcs_repo: PathBuf,
+    pub cvs_repo: PathBuf,
+    pub sl_repo: PathBuf,
     pub not_vcs: PathBuf,
     pub not_dir: PathBuf,
 }
@@ -121,6 +153,27 @@ impl TestDirs {
         jj_repo.push(TEST_VCS_BASENAME_JJ);
         assert!(Path::exists(&jj_repo), "jj_repo missing: {:?}", &jj_repo);
 
+        let mut svn_repo = root_dir.clone();
+        svn_repo.push(TEST_VCS_BASENAME_SVN);
+
+        let mut fossil_repo = root_dir.clone();
+        fossil_repo.push(TEST_VCS_BASENAME_FOSSIL);
+
+        let mut pijul_repo = root_dir.clone();
+        pijul_repo.push(TEST_VCS_BASENAME_PIJUL);
+
+        let mut bzr_repo = root_dir.clone();
+        bzr_repo.push(TEST_VCS_BASENAME_BZR);
+
+        let mut darcs_repo = root_dir.clone();
+        darcs_repo.push(TEST_VCS_BASENAME_DARCS);
+
+        let mut cvs_repo = root_dir.clone();
+        cvs_repo.push(TEST_VCS_BASENAME_CVS);
+
+        let mut sl_repo = root_dir.clone();
+        sl_repo.push(TEST_VCS_BASENAME_SL);
+
         let mut not_vcs = root_dir.clone();
         not_vcs.push(TEST_VCS_BASENAME_NONVCS);
         assert!(Path::exists(&not_vcs), "not_vcs missing: {:?}", &not_vcs);
@@ -134,6 +187,13 @@ impl TestDirs {
             git_repo,
             hg_repo,
             jj_repo,
+            svn_repo,
+            fossil_repo,
+            pijul_repo,
+            bzr_repo,
+            darcs_repo,
+            cvs_repo,
+            sl_repo,
             not_vcs,
             not_dir,
         })
@@ -209,7 +269,7 @@ impl TestDirs {
 
         test_scope.setup_idempotence.call_once(|| {
             let tmpdir_root = mktemp(TESTDIR_TMPDIR_ROOT, test_scope)
-                .expect(&format!("setting up test dir: {}", test_scope.test_name));
+                .unwrap_or_else(|_| panic!("setting up test dir: {}", test_scope.test_name));
             eprintln!("SETUP: {:?}", tmpdir_root.clone());
             match TestDirs::create(&tmpdir_root) {
                 Ok(()) => {}
@@ -228,10 +288,16 @@ impl TestDirs {
 }
 
 pub mod vcs_test_setup {
-    use super::TestSetupError;
     use super::{
+        TestSetupError, FAKE_BZR_WHOAMI, FAKE_DARCS_AUTHOR, FAKE_FOSSIL_USER,
+        FAKE_SL_USERNAME_CONFIG,
+    };
+    use super::{
+        TEST_VCS_BASENAME_BZR, TEST_VCS_BASENAME_CVS, TEST_VCS_BASENAME_CVS_SERVER,
+        TEST_VCS_BASENAME_DARCS, TEST_VCS_BASENAME_FOSSIL, TEST_VCS_BASENAME_FOSSIL_DB,
         TEST_VCS_BASENAME_GIT, TEST_VCS_BASENAME_HG, TEST_VCS_BASENAME_JJ,
-        TEST_VCS_BASENAME_NONDIR, TEST_VCS_BASENAME_NONVCS,
+        TEST_VCS_BASENAME_NONDIR, TEST_VCS_BASENAME_NONVCS, TEST_VCS_BASENAME_PIJUL,
+        TEST_VCS_BASENAME_SL, TEST_VCS_BASENAME_SVN, TEST_VCS_BASENAME_SVN_SERVER,
     };
     use std::path::Path;
     use std::path::PathBuf;
@@ -275,6 +341,177 @@ pub mod vcs_test_setup {
         })
     }
 
+    /// Touches `basename` in `repo_dir` and commits it to git history.
+    pub fn commit_touched_git(repo_dir: &PathBuf, basename: &str) -> Result<(), TestSetupError> {
+        touch_in(repo_dir, basename)?;
+        run_cli_from_tempdir("git", &["add", "."], repo_dir)?;
+        run_cli_from_tempdir(
+            "git",
+            super::git_cmd_args(&[
+                "commit",
+                "--no-verify",
+                "--message",
+                "test arrange phase: ensuring git history",
+            ])
+            .as_slice(),
+            repo_dir,
+        )
+    }
+
+    /// Touches `basename` in `repo_dir` and commits it to hg history.
+    pub fn commit_touched_hg(repo_dir: &PathBuf, basename: &str) -> Result<(), TestSetupError> {
+        touch_in(repo_dir, basename)?;
+        run_cli_from_tempdir("hg", &["add", "."], repo_dir)?;
+        run_cli_from_tempdir(
+            "hg",
+            &[
+                "commit",
+                "--message",
+                "test arrange phase: ensuring hg history",
+            ],
+            repo_dir,
+        )
+    }
+
+    /// Touches `basename` in `repo_dir` and commits it to jj history (leaving a fresh, empty
+    /// working-copy commit on top).
+    pub fn commit_touched_jj(repo_dir: &PathBuf, basename: &str) -> Result<(), TestSetupError> {
+        touch_in(repo_dir, basename)?;
+        run_cli_from_tempdir(
+            "jj",
+            &[
+                "commit",
+                "--message",
+                "test arrange phase: ensuring jj history",
+            ],
+            repo_dir,
+        )
+    }
+
+    /// Touches `basename` in `repo_dir` and commits it to svn history (then updates the working
+    /// copy, so it's entirely at the new revision).
+    pub fn commit_touched_svn(repo_dir: &PathBuf, basename: &str) -> Result<(), TestSetupError> {
+        touch_in(repo_dir, basename)?;
+        run_cli_from_tempdir("svn", &["add", basename], repo_dir)?;
+        run_cli_from_tempdir(
+            "svn",
+            &[
+                "commit",
+                "--message",
+                "test arrange phase: ensuring svn history",
+            ],
+            repo_dir,
+        )?;
+        run_cli_from_tempdir("svn", &["update"], repo_dir)
+    }
+
+    /// Touches `basename` in `repo_dir` and commits it to fossil history.
+    pub fn commit_touched_fossil(repo_dir: &PathBuf, basename: &str) -> Result<(), TestSetupError> {
+        touch_in(repo_dir, basename)?;
+        run_cli_from_tempdir("fossil", &["add", basename], repo_dir)?;
+        run_cli_from_tempdir(
+            "fossil",
+            &[
+                "commit",
+                "--no-warnings",
+                "--message",
+                "test arrange phase: ensuring fossil history",
+            ],
+            repo_dir,
+        )
+    }
+
+    /// Touches `basename` in `repo_dir` and commits it to bzr history.
+    pub fn commit_touched_bzr(repo_dir: &PathBuf, basename: &str) -> Result<(), TestSetupError> {
+        touch_in(repo_dir, basename)?;
+        run_cli_from_tempdir("brz", &["add", basename], repo_dir)?;
+        run_cli_from_tempdir(
+            "brz",
+            &[
+                "commit",
+                "--message",
+                "test arrange phase: ensuring bzr history",
+            ],
+            repo_dir,
+        )
+    }
+
+    /// Touches `basename` in `repo_dir` and records it as a darcs patch.
+    pub fn commit_touched_darcs(repo_dir: &PathBuf, basename: &str) -> Result<(), TestSetupError> {
+        touch_in(repo_dir, basename)?;
+        run_cli_from_tempdir("darcs", &["add", basename], repo_dir)?;
+        run_cli_from_tempdir(
+            "darcs",
+            &[
+                "record",
+                "--all",
+                "--author",
+                FAKE_DARCS_AUTHOR,
+                "--name",
+                "test arrange phase: ensuring darcs history",
+            ],
+            repo_dir,
+        )
+    }
+
+    /// Records a darcs tag named `tag` (of every patch in `repo_dir`).
+    pub fn tag_darcs(repo_dir: &PathBuf, tag: &str) -> Result<(), TestSetupError> {
+        run_cli_from_tempdir(
+            "darcs",
+            &["tag", "--author", FAKE_DARCS_AUTHOR, tag],
+            repo_dir,
+        )
+    }
+
+    /// Touches `basename` in `repo_dir` and commits it to cvs history.
+    pub fn commit_touched_cvs(repo_dir: &PathBuf, basename: &str) -> Result<(), TestSetupError> {
+        touch_in(repo_dir, basename)?;
+        run_cli_from_tempdir("cvs", &["add", basename], repo_dir)?;
+        run_cli_from_tempdir(
+            "cvs",
+            &["commit", "-m", "test arrange phase: ensuring cvs history"],
+            repo_dir,
+        )
+    }
+
+    /// Touches `basename` in `repo_dir` and commits it to sl history.
+    pub fn commit_touched_sl(repo_dir: &PathBuf, basename: &str) -> Result<(), TestSetupError> {
+        touch_in(repo_dir, basename)?;
+        run_cli_from_tempdir("sl", &["add", "."], repo_dir)?;
+        run_cli_from_tempdir(
+            "sl",
+            &[
+                "--config",
+                FAKE_SL_USERNAME_CONFIG,
+                "commit",
+                "--message",
+                "test arrange phase: ensuring sl history",
+            ],
+            repo_dir,
+        )
+    }
+
+    /// Reads back vcsq's own `current-id` answer for `repo_dir`, trailing newline included (so
+    /// tests can later expect it as some other query's output).
+    pub fn read_current_id(repo_dir: &Path) -> String {
+        let output = assert_cmd::Command::cargo_bin("vcsq")
+            .unwrap()
+            .arg("current-id")
+            .arg(repo_dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "test arrange: current-id failed");
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    fn touch_in(repo_dir: &Path, basename: &str) -> Result<(), TestSetupError> {
+        use super::make_test_temp::touch;
+
+        let mut touched_file = repo_dir.to_path_buf();
+        touched_file.push(basename);
+        touch(&touched_file)
+    }
+
     fn setup_temp_repo_git(tmpdir_root: &PathBuf) -> Result<(), TestSetupError> {
         run_cli_from_tempdir("git", &["init", TEST_VCS_BASENAME_GIT], tmpdir_root)
     }
@@ -287,6 +524,94 @@ pub mod vcs_test_setup {
         run_cli_from_tempdir("jj", &["git", "init", TEST_VCS_BASENAME_JJ], tmpdir_root)
     }
 
+    fn setup_temp_repo_svn(tmpdir_root: &PathBuf) -> Result<(), TestSetupError> {
+        let mut server = tmpdir_root.clone();
+        server.push(TEST_VCS_BASENAME_SVN_SERVER);
+        run_cli_from_tempdir(
+            "svnadmin",
+            &["create", TEST_VCS_BASENAME_SVN_SERVER],
+            tmpdir_root,
+        )?;
+        let server_url = format!("file://{}", server.to_string_lossy());
+        run_cli_from_tempdir(
+            "svn",
+            &["checkout", &server_url, TEST_VCS_BASENAME_SVN],
+            tmpdir_root,
+        )
+    }
+
+    fn setup_temp_repo_fossil(tmpdir_root: &PathBuf) -> Result<(), TestSetupError> {
+        use std::fs::create_dir;
+
+        run_cli_from_tempdir(
+            "fossil",
+            &[
+                "init",
+                "--admin-user",
+                FAKE_FOSSIL_USER,
+                TEST_VCS_BASENAME_FOSSIL_DB,
+            ],
+            tmpdir_root,
+        )?;
+        let mut repo_db = tmpdir_root.clone();
+        repo_db.push(TEST_VCS_BASENAME_FOSSIL_DB);
+        let mut checkout = tmpdir_root.clone();
+        checkout.push(TEST_VCS_BASENAME_FOSSIL);
+        create_dir(&checkout).map_err(|source| TestSetupError::System {
+            context: format!(
+                "temp_repo_fossil({}): create_dir",
+                checkout.to_string_lossy()
+            ),
+            source,
+        })?;
+        run_cli_from_tempdir("fossil", &["open", &repo_db.to_string_lossy()], &checkout)
+    }
+
+    fn setup_temp_repo_pijul(tmpdir_root: &PathBuf) -> Result<(), TestSetupError> {
+        run_cli_from_tempdir("pijul", &["init", TEST_VCS_BASENAME_PIJUL], tmpdir_root)
+    }
+
+    fn setup_temp_repo_bzr(tmpdir_root: &PathBuf) -> Result<(), TestSetupError> {
+        run_cli_from_tempdir("brz", &["init", TEST_VCS_BASENAME_BZR], tmpdir_root)?;
+        let mut repo_dir = tmpdir_root.clone();
+        repo_dir.push(TEST_VCS_BASENAME_BZR);
+        run_cli_from_tempdir("brz", &["whoami", "--branch", FAKE_BZR_WHOAMI], &repo_dir)
+    }
+
+    fn setup_temp_repo_darcs(tmpdir_root: &PathBuf) -> Result<(), TestSetupError> {
+        run_cli_from_tempdir(
+            "darcs",
+            &["init", "--repodir", TEST_VCS_BASENAME_DARCS],
+            tmpdir_root,
+        )
+    }
+
+    fn setup_temp_repo_cvs(tmpdir_root: &PathBuf) -> Result<(), TestSetupError> {
+        use std::fs::create_dir;
+
+        let mut server = tmpdir_root.clone();
+        server.push(TEST_VCS_BASENAME_CVS_SERVER);
+        let cvsroot = format!(":local:{}", server.to_string_lossy());
+        run_cli_from_tempdir("cvs", &["-d", &cvsroot, "init"], tmpdir_root)?;
+
+        // a module is just a directory in the repository, so we needn't `cvs import` one
+        let mut module = server.clone();
+        module.push(TEST_VCS_BASENAME_CVS);
+        create_dir(&module).map_err(|source| TestSetupError::System {
+            context: format!("temp_repo_cvs({}): create_dir", module.to_string_lossy()),
+            source,
+        })?;
+        run_cli_from_tempdir(
+            "cvs",
+            &["-d", &cvsroot, "checkout", TEST_VCS_BASENAME_CVS],
+            tmpdir_root,
+        )
+    }
+
+    fn setup_temp_repo_sl(tmpdir_root: &PathBuf) -> Result<(), TestSetupError> {
+        run_cli_from_tempdir("sl", &["init", TEST_VCS_BASENAME_SL], tmpdir_root)
+    }
+
     fn setup_temp_nonvcs_dir(mut tmpdir_root: PathBuf) -> Result<(), TestSetupError> {
         use std::fs::create_dir;
         tmpdir_root.push(TEST_VCS_BASENAME_NONVCS);
@@ -307,11 +632,33 @@ pub mod vcs_test_setup {
         plain_file.push(TEST_VCS_BASENAME_NONDIR);
         touch(&plain_file)
     }
+    /// Sets up each of the less common VCS repos, on a best-effort basis: a brand whose binary
+    /// is absent (or whose setup otherwise fails) is only logged, leaving its repo dir missing so
+    /// that just that brand's tests fail rather than the whole harness.
+    fn setup_optional_repos(tmpdir_root: &PathBuf) {
+        type Setup = fn(&PathBuf) -> Result<(), TestSetupError>;
+        let setups: [(&str, Setup); 7] = [
+            ("svn", setup_temp_repo_svn),
+            ("fossil", setup_temp_repo_fossil),
+            ("pijul", setup_temp_repo_pijul),
+            ("bzr", setup_temp_repo_bzr),
+            ("darcs", setup_temp_repo_darcs),
+            ("cvs", setup_temp_repo_cvs),
+            ("sl", setup_temp_repo_sl),
+        ];
+        for (brand, setup) in setups {
+            if let Err(e) = setup(tmpdir_root) {
+                eprintln!("SETUP: skipping {brand} repo: {e}");
+            }
+        }
+    }
+
     /// Creates new temp directories on disk.
     pub fn setup_temp_repos(tmpdir_root: &Path) -> Result<(), TestSetupError> {
         setup_temp_repo_git(&tmpdir_root.to_path_buf())?;
         setup_temp_repo_hg(&tmpdir_root.to_path_buf())?;
         setup_temp_repo_jj(&tmpdir_root.to_path_buf())?;
+        setup_optional_repos(&tmpdir_root.to_path_buf());
         setup_temp_nonvcs_dir(tmpdir_root.to_path_buf())?;
         setup_temp_plainfile(tmpdir_root)?;
         Ok(())
@@ -365,7 +712,7 @@ pub mod make_test_temp {
             .then_some(())
             .ok_or_else(|| format!("expect temp root_dir is dir: {root_dir:?}"))?;
 
-        (root_dir != PathBuf::from("/"))
+        (root_dir != Path::new("/"))
             .then_some(())
             .ok_or_else(|| format!("expect temp root_dir is not root: {root_dir:?}"))?;
 
@@ -1,8 +1,50 @@
 /// Provides Git <https://git-scm.com> driver for vcsq APIs.
 pub mod git;
 
+/// Provides Git driver for vcsq APIs that reads repos in-process (via gitoxide) rather than
+/// running `git` for each question.
+#[cfg(feature = "native-git")]
+pub mod git_native;
+
 /// Provides Mercurial <https://mercurial-scm.org> driver for vcsq APIs.
 pub mod hg;
 
+/// Provides Mercurial driver for vcsq APIs that reads `.hg` files directly rather than running
+/// `hg` for each question.
+#[cfg(feature = "native-hg")]
+pub mod hg_native;
+
 /// Provides Jujutsu <https://jj-vcs.github.io> driver for vcsq APIs.
 pub mod jj;
+
+/// Provides Jujutsu driver for vcsq APIs that reads repos in-process (via jj-lib) rather than
+/// running `jj` for each question.
+#[cfg(feature = "native-jj")]
+pub mod jj_native;
+
+/// Provides Subversion <https://subversion.apache.org> driver for vcsq APIs.
+pub mod svn;
+
+/// Provides Fossil <https://fossil-scm.org> driver for vcsq APIs.
+pub mod fossil;
+
+/// Provides Pijul <https://pijul.org> driver for vcsq APIs.
+pub mod pijul;
+
+/// Provides Bazaar <https://www.breezy-vcs.org> driver for vcsq APIs (via Breezy, Bazaar's
+/// successor).
+pub mod bzr;
+
+/// Provides Darcs <https://darcs.net> driver for vcsq APIs.
+pub mod darcs;
+
+/// Provides CVS <https://www.nongnu.org/cvs> driver for vcsq APIs.
+pub mod cvs;
+
+/// Provides Sapling <https://sapling-scm.com> driver for vcsq APIs.
+pub mod sapling;
+
+/// Provides a driver for vcsq APIs that asks an external `vcsq-adapter-<brand>` executable, for
+/// brands this library doesn't ship an adapter for.
+#[cfg(feature = "external")]
+pub mod external;
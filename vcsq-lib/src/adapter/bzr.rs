@@ -0,0 +1,430 @@
+use crate::plexer::VcsBrand;
+use crate::repo::{
+    may_be_in_repo, AheadBehind, AncestorRef, Driver, DriverError, FileStatus, FileStatusKind,
+    HistoryRefId, HistoryRefName, QueryDir, RepoOperation, Validator, VcsAvailable,
+};
+use std::num::NonZero;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+static VCS_BIN_NAME: &str = "brz";
+
+/// Breezy's directory of metadata, relative to the tree's root.
+const BZR_META_DIR: &str = ".bzr";
+
+/// Revision ID Breezy gives the (empty) start of every branch's history.
+const BZR_NULL_REVID: &str = "null:";
+
+/// Prefix of a revision spec that names a revision by its ID (rather than its revno, which is
+/// only meaningful within one branch).
+const BZR_REVSPEC_REVID: &str = "revid:";
+
+/// Separator `brz status --short` prints between the old and new path of a renamed file.
+const BZR_STATUS_RENAME_SEP: &str = " => ";
+
+/// Width of the status columns `brz status --short` prints before each path (versioning,
+/// content, and execute-bit changes, then a space).
+const BZR_STATUS_COLUMNS: usize = 4;
+
+/// Versioning code `brz status --short` gives each pending merge (ie: revisions merged into the
+/// tree, but not yet committed).
+const BZR_STATUS_PENDING_MERGE: char = 'P';
+
+#[derive(Debug)]
+pub struct Repo {
+    /// Root of the working tree, as reported by `brz root`.
+    root: QueryDir,
+}
+
+#[derive(Debug)]
+pub struct Loader
+where
+    Self: Sized;
+
+impl Validator for Loader {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Bazaar
+    }
+
+    fn new_driver(&self, dir: QueryDir) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        if !may_be_in_repo(&dir, BZR_META_DIR) {
+            return Ok(None);
+        }
+        let mut cmd = start_vcs_shellout();
+        cmd.current_dir(dir).arg("root").stderr(Stdio::null());
+        let output = DriverError::unwrap_cmd_lossy("brz cli".to_string(), cmd.output())?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let root = PathBuf::from(DriverError::expect_cmd_line("brz cli", &output)?);
+        let repo: Box<dyn Driver> = Box::from(Repo { root });
+        Ok(Some(repo))
+    }
+
+    fn check_health(&self) -> Result<VcsAvailable, DriverError> {
+        let mut cmd = start_vcs_shellout();
+        cmd.arg("--version");
+        DriverError::expect_cmd_lossy("brz cli: exec".to_string(), cmd.output())
+    }
+}
+
+fn start_vcs_shellout() -> Command {
+    let mut cmd = Command::new(VCS_BIN_NAME);
+    cmd.env("BRZ_PROGRESS_BAR", "none");
+    cmd
+}
+
+/// A revision as `brz revision-info` reports it.
+#[derive(Debug)]
+struct RevisionInfo {
+    /// Number of the revision within its branch's mainline (eg: `3`), or a dotted revno for
+    /// merged revisions (eg: `2.1.1`).
+    revno: String,
+
+    /// Globally unique ID of the revision.
+    revid: HistoryRefId,
+}
+
+impl Repo {
+    fn start_shellout(&self) -> Command {
+        let mut cmd = start_vcs_shellout();
+        cmd.current_dir(self.root.clone());
+        cmd
+    }
+
+    /// Describes `revspec`, or the working tree's basis revision if `None`.
+    fn brz_revision_info(&self, revspec: Option<&str>) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("revision-info");
+        match revspec {
+            Some(revspec) => cmd.arg("--revision").arg(revspec),
+            None => cmd.arg("--tree"),
+        };
+        cmd
+    }
+
+    /// Describes the newest revision of the branch (which the working tree may be behind).
+    fn brz_branch_tip_info(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("revision-info");
+        cmd
+    }
+
+    fn brz_status(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("status").arg("--short");
+        cmd
+    }
+
+    fn brz_files_of(&self, revspec: &str) -> Command {
+        let mut cmd = self.brz_status();
+        cmd.arg("--change").arg(revspec);
+        cmd
+    }
+
+    fn brz_files_between(&self, base: &str, head: &str) -> Command {
+        let mut cmd = self.brz_status();
+        cmd.arg("--revision").arg(format!("{base}..{head}"));
+        cmd
+    }
+
+    fn brz_tracked_files(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("ls")
+            .arg("--versioned")
+            .arg("--recursive")
+            .arg("--kind=file");
+        cmd
+    }
+
+    fn brz_tags(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("tags");
+        cmd
+    }
+
+    fn brz_nick(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("nick");
+        cmd
+    }
+
+    fn revision_info(&self, mut cmd: Command) -> Result<RevisionInfo, DriverError> {
+        let output = DriverError::expect_cmd_lossy("brz cli: exec".to_string(), cmd.output())?;
+        let line = DriverError::expect_cmd_line("brz cli: exec", &output)?;
+        let Some((revno, revid)) = line.split_once(char::is_whitespace) else {
+            return Err(DriverError::UnexpectedOutput {
+                cmd: "brz cli: exec".to_string(),
+                stdout: line,
+            });
+        };
+        Ok(RevisionInfo {
+            revno: revno.to_string(),
+            revid: revid.trim().to_string(),
+        })
+    }
+
+    /// The working tree's basis revision, or [`DriverError::EmptyHistory`] if there's no
+    /// commits yet.
+    fn current(&self) -> Result<RevisionInfo, DriverError> {
+        let current = self.revision_info(self.brz_revision_info(None))?;
+        if current.revid == BZR_NULL_REVID {
+            return Err(DriverError::EmptyHistory);
+        }
+        Ok(current)
+    }
+
+    /// Left-hand (ie: mainline) parent of the working tree's basis revision.
+    fn parent(&self) -> Result<RevisionInfo, DriverError> {
+        let current = self.current()?;
+        let parent = self.revision_info(self.brz_revision_info(Some(&format!(
+            "before:{BZR_REVSPEC_REVID}{}",
+            current.revid
+        ))))?;
+        if parent.revid == BZR_NULL_REVID {
+            return Err(DriverError::NoParent);
+        }
+        Ok(parent)
+    }
+
+    /// Tags of the branch, as `(name, revno)` pairs.
+    fn tags(&self) -> Result<Vec<(HistoryRefName, String)>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.brz_tags().output(),
+            0, /*min_lines*/
+            "brz cli: exec",
+            None,
+        )?;
+        Ok(lines
+            .iter()
+            .filter_map(|line| {
+                let (name, revno) = line.rsplit_once(char::is_whitespace)?;
+                Some((name.trim_end().to_string(), revno.to_string()))
+            })
+            .collect())
+    }
+
+    /// First tag on the revision numbered `revno`.
+    fn tag_at(&self, revno: &str) -> Result<Option<HistoryRefName>, DriverError> {
+        Ok(self
+            .tags()?
+            .into_iter()
+            .find(|(_, tag_revno)| tag_revno == revno)
+            .map(|(name, _)| name))
+    }
+
+    fn files_in(&self, mut cmd: Command) -> Result<Vec<QueryDir>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            cmd.output(),
+            0, /*min_lines*/
+            "brz cli: exec",
+            None,
+        )?;
+        Ok(lines
+            .iter()
+            .filter_map(|line| parse_status_line(line))
+            .map(|status| status.path)
+            .collect())
+    }
+}
+
+impl Driver for Repo {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Bazaar
+    }
+
+    fn root(&self) -> Result<QueryDir, DriverError> {
+        Ok(self.root.clone())
+    }
+
+    /// Lists changes to the working tree (Bazaar has no index, so nothing is ever staged).
+    fn status(&self) -> Result<Vec<FileStatus>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.brz_status().output(),
+            0, /*min_lines*/
+            "brz cli: exec",
+            None,
+        )?;
+        Ok(lines
+            .iter()
+            .filter_map(|line| parse_status_line(line))
+            .collect())
+    }
+
+    fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.brz_tracked_files().output(),
+            0, /*min_lines*/
+            "brz cli: exec",
+            None,
+        )?;
+        Ok(lines.into_iter().map(PathBuf::from).collect())
+    }
+
+    fn changed_files(
+        &self,
+        base: Option<&str>,
+        head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError> {
+        let head = match head {
+            Some(head) => head.to_string(),
+            None => match self.current() {
+                Ok(current) => format!("{BZR_REVSPEC_REVID}{}", current.revid),
+                Err(DriverError::EmptyHistory) => return Ok(vec![]),
+                Err(err) => return Err(err),
+            },
+        };
+        match base {
+            Some(base) => self.files_in(self.brz_files_between(base, &head)),
+            None => self.files_in(self.brz_files_of(&head)),
+        }
+    }
+
+    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        Ok(self.parent()?.revid)
+    }
+
+    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError> {
+        let parent = self.parent()?;
+        self.tag_at(&parent.revno)
+    }
+
+    /// Returns the nearest Bazaar tag on the mainline history of the working tree's basis
+    /// revision.
+    fn first_ancestor_ref_name(
+        &self,
+        limit: Option<NonZero<u64>>,
+    ) -> Result<Option<AncestorRef>, DriverError> {
+        let current = match self.current() {
+            Ok(current) => current,
+            Err(DriverError::EmptyHistory) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        // Only mainline revisions have plain numbers, so merged revisions are never walked.
+        let Ok(current_revno) = current.revno.parse::<u64>() else {
+            return Ok(None);
+        };
+        let tags = self.tags()?;
+        for steps_back in 1..current_revno {
+            if limit.is_some_and(|limit| steps_back > limit.get()) {
+                break;
+            }
+            let revno = (current_revno - steps_back).to_string();
+            if let Some((name, _)) = tags.iter().find(|(_, tag_revno)| *tag_revno == revno) {
+                let ancestor = self.revision_info(self.brz_revision_info(Some(&revno)))?;
+                return Ok(Some(AncestorRef {
+                    id: ancestor.revid,
+                    name: name.clone(),
+                    distance: NonZero::new(steps_back).expect("bug: walk starts at 1"),
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the next mainline revision of the branch, if the working tree is behind it (eg:
+    /// after `brz update --revision`).
+    fn child_ref_ids(&self, _max: Option<NonZero<u64>>) -> Result<Vec<HistoryRefId>, DriverError> {
+        let current_revno = match self.current() {
+            Ok(current) => current.revno.parse::<u64>().ok(),
+            Err(DriverError::EmptyHistory) => Some(0),
+            Err(err) => return Err(err),
+        };
+        let tip = self.revision_info(self.brz_branch_tip_info())?;
+        let (Some(current_revno), Ok(tip_revno)) = (current_revno, tip.revno.parse::<u64>()) else {
+            return Ok(vec![]);
+        };
+        if tip_revno <= current_revno {
+            return Ok(vec![]);
+        }
+        let child =
+            self.revision_info(self.brz_revision_info(Some(&(current_revno + 1).to_string())))?;
+        Ok(vec![child.revid])
+    }
+
+    /// Always `None`: Bazaar only compares branches with their parent on request (see `brz
+    /// missing`), which takes network access.
+    fn ahead_behind(&self) -> Result<Option<AheadBehind>, DriverError> {
+        Ok(None)
+    }
+
+    /// Reports a merge if revisions are merged into the working tree, but not yet committed.
+    fn operation(&self) -> Result<Option<RepoOperation>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.brz_status().output(),
+            0, /*min_lines*/
+            "brz cli: exec",
+            None,
+        )?;
+        Ok(lines
+            .iter()
+            .any(|line| line.starts_with(BZR_STATUS_PENDING_MERGE))
+            .then_some(RepoOperation::Merge))
+    }
+
+    /// Returns the revision ID of the working tree's basis revision (rather than its revno,
+    /// which is only meaningful within this branch).
+    fn current_ref_id(&self, dirty_ok: bool) -> Result<HistoryRefId, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        Ok(self.current()?.revid)
+    }
+
+    /// Returns a tag on the working tree's basis revision if there is one, otherwise the branch's
+    /// nickname.
+    fn current_ref_name(&self, dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        match self.current() {
+            Ok(current) => {
+                let tag = self.tag_at(&current.revno)?;
+                if tag.is_some() {
+                    return Ok(tag);
+                }
+            }
+            Err(DriverError::EmptyHistory) => {}
+            Err(err) => return Err(err),
+        }
+        let output =
+            DriverError::expect_cmd_lossy("brz cli: exec".to_string(), self.brz_nick().output())?;
+        let nick = DriverError::expect_cmd_line("brz cli: exec", &output)?;
+        Ok((!nick.is_empty()).then_some(nick))
+    }
+}
+
+/// Parses one line of `brz status --short` output, whose first three columns describe the
+/// file's versioning (eg: `+` added), content (eg: `M` modified) and execute-bit changes, eg:
+/// ` M  path`.
+fn parse_status_line(line: &str) -> Option<FileStatus> {
+    let columns = line.get(..BZR_STATUS_COLUMNS)?.as_bytes();
+    let path = line.get(BZR_STATUS_COLUMNS..)?;
+    if path.is_empty() {
+        return None;
+    }
+    let kind = match (columns[0], columns[1]) {
+        (b'C', _) => FileStatusKind::Conflicted,
+        (b'R', _) => FileStatusKind::Renamed,
+        (b'?', _) => FileStatusKind::Untracked,
+        (b'+', _) | (_, b'N') => FileStatusKind::Added,
+        (b'-', _) | (_, b'D') => FileStatusKind::Deleted,
+        (_, b'M' | b'K') => FileStatusKind::Modified,
+        // only the execute bit changed
+        (b' ', b' ') if columns[2] == b'*' => FileStatusKind::Modified,
+        // Pending merges (`P`) list revisions, not files.
+        _ => return None,
+    };
+    let (original_path, path) = match path.split_once(BZR_STATUS_RENAME_SEP) {
+        Some((from, to)) if kind == FileStatusKind::Renamed => {
+            (Some(PathBuf::from(from)), PathBuf::from(to))
+        }
+        _ => (None, PathBuf::from(path)),
+    };
+    Some(FileStatus {
+        kind,
+        path,
+        original_path,
+        staged: None,
+    })
+}
@@ -0,0 +1,262 @@
+use crate::plexer::VcsBrand;
+use crate::repo::{
+    AheadBehind, AncestorRef, Driver, DriverError, FileStatus, FileStatusKind, HistoryRefId,
+    HistoryRefName, QueryDir, RepoOperation, Validator, VcsAvailable,
+};
+use std::fs;
+use std::num::NonZero;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+static VCS_BIN_NAME: &str = "cvs";
+
+/// CVS's directory of metadata, which _every_ directory of a working copy has.
+const CVS_META_DIR: &str = "CVS";
+
+/// File (in `CVS_META_DIR`) naming the repository a directory was checked out from.
+const CVS_META_ROOT: &str = "Root";
+
+/// File (in `CVS_META_DIR`) listing the files and subdirectories CVS tracks in a directory.
+const CVS_META_ENTRIES: &str = "Entries";
+
+/// File (in `CVS_META_DIR`) of changes CVS has yet to fold into `CVS_META_ENTRIES`.
+const CVS_META_ENTRIES_LOG: &str = "Entries.Log";
+
+/// File (in `CVS_META_DIR`) holding the sticky tag or date of a directory, if there is one.
+const CVS_META_TAG: &str = "Tag";
+
+/// Prefix an entry of `CVS_META_ENTRIES` has when it's a subdirectory, eg: `D/lib////`.
+const CVS_ENTRY_DIR_PREFIX: char = 'D';
+
+/// Prefix a revision in `CVS_META_ENTRIES` has when its file was removed (but not yet committed).
+const CVS_ENTRY_REMOVED_REV_PREFIX: char = '-';
+
+#[derive(Debug)]
+pub struct Repo {
+    /// Root of the working copy (ie: the topmost directory holding a `CVS_META_DIR`).
+    root: QueryDir,
+}
+
+#[derive(Debug)]
+pub struct Loader
+where
+    Self: Sized;
+
+impl Validator for Loader {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Cvs
+    }
+
+    /// Walks up from `dir` for as long as each directory is part of the same working copy, so
+    /// this never needs to shell out.
+    fn new_driver(&self, dir: QueryDir) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        // Relative paths (eg: `.`) lack the ancestors to search, so canonicalize first.
+        let dir = dir
+            .canonicalize()
+            .map_err(|e| DriverError::Directory(format!("{}: {}", dir.display(), e)))?;
+        let root = dir
+            .ancestors()
+            .take_while(|ancestor| ancestor.join(CVS_META_DIR).join(CVS_META_ROOT).is_file())
+            .last();
+        Ok(root.map(|root| {
+            let repo: Box<dyn Driver> = Box::from(Repo {
+                root: root.to_path_buf(),
+            });
+            repo
+        }))
+    }
+
+    fn check_health(&self) -> Result<VcsAvailable, DriverError> {
+        let mut cmd = Command::new(VCS_BIN_NAME);
+        cmd.arg("--version");
+        DriverError::expect_cmd_lossy("cvs cli: exec".to_string(), cmd.output())
+    }
+}
+
+impl Repo {
+    fn start_shellout(&self) -> Command {
+        let mut cmd = Command::new(VCS_BIN_NAME);
+        cmd.current_dir(self.root.clone());
+        // ignore ~/.cvsrc, whose defaults can change the output we parse
+        cmd.arg("-f");
+        cmd
+    }
+
+    /// Dry-runs an update, which CVS reports local changes (as well as remote ones) by.
+    fn cvs_dirty_files(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("-n").arg("-q").arg("update");
+        cmd
+    }
+
+    /// Reads a file of `dir`'s `CVS_META_DIR`, or `None` if CVS hasn't written one.
+    fn read_meta(dir: &Path, basename: &str) -> Result<Option<String>, DriverError> {
+        let path = dir.join(CVS_META_DIR).join(basename);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        fs::read_to_string(&path)
+            .map(Some)
+            .map_err(|e| DriverError::Directory(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Lists files tracked in `dir` (relative to `prefix`), recursing into tracked
+    /// subdirectories.
+    fn entries(&self, dir: &Path, prefix: &Path) -> Result<Vec<QueryDir>, DriverError> {
+        let mut entries: Vec<String> = Self::read_meta(dir, CVS_META_ENTRIES)?
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        // Entries.Log lines are an entry prefixed by whether it's being `A`dded or `R`emoved.
+        for line in Self::read_meta(dir, CVS_META_ENTRIES_LOG)?
+            .unwrap_or_default()
+            .lines()
+        {
+            match line.split_once(' ') {
+                Some(("A", entry)) => entries.push(entry.to_string()),
+                Some(("R", entry)) => entries.retain(|existing| existing != entry),
+                _ => {}
+            }
+        }
+
+        let mut files = vec![];
+        for entry in &entries {
+            let (kind, fields) = entry.split_once('/').unwrap_or((entry, ""));
+            let mut fields = fields.split('/');
+            let Some(name) = fields.next().filter(|name| !name.is_empty()) else {
+                continue;
+            };
+            if kind.starts_with(CVS_ENTRY_DIR_PREFIX) {
+                files.extend(self.entries(&dir.join(name), &prefix.join(name))?);
+            } else if !fields
+                .next()
+                .is_some_and(|rev| rev.starts_with(CVS_ENTRY_REMOVED_REV_PREFIX))
+            {
+                files.push(prefix.join(name));
+            }
+        }
+        Ok(files)
+    }
+}
+
+impl Driver for Repo {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Cvs
+    }
+
+    fn root(&self) -> Result<QueryDir, DriverError> {
+        Ok(self.root.clone())
+    }
+
+    /// Lists changes not yet committed (CVS has no index, so nothing is ever staged).
+    fn status(&self) -> Result<Vec<FileStatus>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.cvs_dirty_files().output(),
+            0, /*min_lines*/
+            "cvs cli: exec",
+            None,
+        )?;
+        Ok(lines
+            .iter()
+            .filter_map(|line| parse_update_line(&self.root, line))
+            .collect())
+    }
+
+    /// Lists files tracked in the working copy (which includes files added, but not yet
+    /// committed), as read from CVS's own bookkeeping rather than the repository.
+    fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError> {
+        let mut files = self.entries(&self.root, Path::new(""))?;
+        files.sort();
+        Ok(files)
+    }
+
+    /// Always [`DriverError::Unsupported`]: see `current_ref_id`.
+    fn changed_files(
+        &self,
+        _base: Option<&str>,
+        _head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError> {
+        self.current_ref_id(true /*dirty_ok*/).map(|_| vec![])
+    }
+
+    /// Always [`DriverError::Unsupported`]: see `current_ref_id`.
+    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        self.current_ref_id(true /*dirty_ok*/)
+    }
+
+    /// Always [`DriverError::Unsupported`]: see `current_ref_id`.
+    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError> {
+        self.current_ref_id(true /*dirty_ok*/).map(|_| None)
+    }
+
+    /// Always [`DriverError::Unsupported`]: see `current_ref_id`.
+    fn first_ancestor_ref_name(
+        &self,
+        _limit: Option<NonZero<u64>>,
+    ) -> Result<Option<AncestorRef>, DriverError> {
+        self.current_ref_id(true /*dirty_ok*/).map(|_| None)
+    }
+
+    /// Always [`DriverError::Unsupported`]: see `current_ref_id`.
+    fn child_ref_ids(&self, _max: Option<NonZero<u64>>) -> Result<Vec<HistoryRefId>, DriverError> {
+        self.current_ref_id(true /*dirty_ok*/).map(|_| vec![])
+    }
+
+    /// Always `None`: CVS keeps nothing locally to compare against the repository with.
+    fn ahead_behind(&self) -> Result<Option<AheadBehind>, DriverError> {
+        Ok(None)
+    }
+
+    /// Always `None`: CVS has no multi-step operations (conflicts are left in the working copy,
+    /// and reported by `status()`).
+    fn operation(&self) -> Result<Option<RepoOperation>, DriverError> {
+        Ok(None)
+    }
+
+    /// Always [`DriverError::Unsupported`]: CVS versions each file separately, so there's no
+    /// revision (nor history of them) that the whole working copy is at.
+    fn current_ref_id(&self, _dirty_ok: bool) -> Result<HistoryRefId, DriverError> {
+        Err(DriverError::Unsupported(
+            "cvs only has revisions of individual files".to_string(),
+        ))
+    }
+
+    /// Returns the sticky tag (or branch) the working copy was checked out at, if any.
+    fn current_ref_name(&self, dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        let Some(tag) = Self::read_meta(&self.root, CVS_META_TAG)? else {
+            return Ok(None);
+        };
+        // `T`ags name a branch and `N`s a non-branch tag, while `D`ates have no name.
+        Ok(tag.lines().next().and_then(|line| {
+            line.strip_prefix('T')
+                .or_else(|| line.strip_prefix('N'))
+                .map(str::to_string)
+        }))
+    }
+}
+
+/// Parses one line of `cvs -n -q update` output (run from `root`), eg: `M path`.
+fn parse_update_line(root: &Path, line: &str) -> Option<FileStatus> {
+    let (code, path) = line.split_once(' ')?;
+    let kind = match code {
+        "M" => FileStatusKind::Modified,
+        "A" => FileStatusKind::Added,
+        "R" => FileStatusKind::Deleted,
+        "C" => FileStatusKind::Conflicted,
+        "?" => FileStatusKind::Untracked,
+        // Updates waiting in the repository aren't local changes, unless the update would only
+        // be restoring a file that was deleted without `cvs remove`.
+        "U" | "P" if !root.join(path).exists() => FileStatusKind::Deleted,
+        _ => return None,
+    };
+    Some(FileStatus {
+        kind,
+        path: PathBuf::from(path),
+        original_path: None,
+        staged: None,
+    })
+}
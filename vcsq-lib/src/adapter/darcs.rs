@@ -0,0 +1,323 @@
+use crate::plexer::VcsBrand;
+use crate::repo::{
+    AheadBehind, AncestorRef, Driver, DriverError, FileStatus, FileStatusKind, HistoryRefId,
+    HistoryRefName, QueryDir, RepoOperation, Validator, VcsAvailable,
+};
+use std::num::NonZero;
+use std::path::PathBuf;
+use std::process::Command;
+
+static VCS_BIN_NAME: &str = "darcs";
+
+/// Darcs's directory of repo metadata, relative to the repo root.
+const DARCS_META_DIR: &str = "_darcs";
+
+/// What `darcs whatsnew` prints (while exiting non-zero) when there's nothing to report.
+const DARCS_WHATSNEW_NO_CHANGES: &str = "No changes!";
+
+/// Key of the `darcs show repo` field holding a hash of the set of patches in the repo.
+const DARCS_SHOW_REPO_WEAK_HASH: &str = "Weak Hash";
+
+/// Key of the `darcs show repo` field holding how many patches are in the repo.
+const DARCS_SHOW_REPO_NUM_PATCHES: &str = "Num Patches";
+
+/// Separator of the old and new path in `--summary` output of a move, eg: ` ./a -> ./b`.
+const DARCS_SUMMARY_MOVE_SEP: &str = " -> ";
+
+/// Suffix of a `--summary` code when the change conflicts, eg: `M!`.
+const DARCS_SUMMARY_CONFLICT: char = '!';
+
+/// Prefix Darcs gives every path in `--summary` output.
+const DARCS_PATH_PREFIX: &str = "./";
+
+#[derive(Debug)]
+pub struct Repo {
+    /// Root of the repo (ie: the directory holding `DARCS_META_DIR`).
+    root: QueryDir,
+}
+
+#[derive(Debug)]
+pub struct Loader
+where
+    Self: Sized;
+
+impl Validator for Loader {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Darcs
+    }
+
+    /// Looks for Darcs's metadata in `dir` or any of its ancestors, so this never needs to shell
+    /// out.
+    fn new_driver(&self, dir: QueryDir) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        // Relative paths (eg: `.`) lack the ancestors to search, so canonicalize first.
+        let dir = dir
+            .canonicalize()
+            .map_err(|e| DriverError::Directory(format!("{}: {}", dir.display(), e)))?;
+        let root = dir
+            .ancestors()
+            .find(|ancestor| ancestor.join(DARCS_META_DIR).is_dir());
+        Ok(root.map(|root| {
+            let repo: Box<dyn Driver> = Box::from(Repo {
+                root: root.to_path_buf(),
+            });
+            repo
+        }))
+    }
+
+    fn check_health(&self) -> Result<VcsAvailable, DriverError> {
+        let mut cmd = Command::new(VCS_BIN_NAME);
+        cmd.arg("--version");
+        DriverError::expect_cmd_lossy("darcs cli: exec".to_string(), cmd.output())
+    }
+}
+
+impl Repo {
+    fn start_shellout(&self) -> Command {
+        let mut cmd = Command::new(VCS_BIN_NAME);
+        cmd.current_dir(self.root.clone());
+        cmd
+    }
+
+    fn darcs_dirty_files(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("whatsnew").arg("--summary").arg("--look-for-adds");
+        cmd
+    }
+
+    fn darcs_tracked_files(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("show").arg("files").arg("--no-directories");
+        cmd
+    }
+
+    fn darcs_show_repo(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("show").arg("repo");
+        cmd
+    }
+
+    /// Lists tags, newest first.
+    fn darcs_tags(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("show").arg("tags");
+        cmd
+    }
+
+    fn darcs_latest_patch_files(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("log").arg("--last=1").arg("--summary");
+        cmd
+    }
+
+    /// Value of the `key` field of `darcs show repo`.
+    fn show_repo_field(&self, key: &str) -> Result<Option<String>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.darcs_show_repo().output(),
+            1, /*min_lines*/
+            "darcs cli: exec",
+            None,
+        )?;
+        Ok(lines.iter().find_map(|line| {
+            let (field, value) = line.split_once(':')?;
+            (field.trim() == key).then(|| value.trim().to_string())
+        }))
+    }
+}
+
+impl Driver for Repo {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Darcs
+    }
+
+    fn root(&self) -> Result<QueryDir, DriverError> {
+        Ok(self.root.clone())
+    }
+
+    /// Lists changes not yet recorded (Darcs has no index, so nothing is ever staged).
+    fn status(&self) -> Result<Vec<FileStatus>, DriverError> {
+        let output = DriverError::unwrap_cmd_lossy(
+            "darcs cli: exec".to_string(),
+            self.darcs_dirty_files().output(),
+        )?;
+        let lines = output.stdout_strings();
+        if !output.status.success() {
+            if lines.iter().any(|line| line == DARCS_WHATSNEW_NO_CHANGES) {
+                return Ok(vec![]);
+            }
+            return Err(DriverError::Stderr {
+                context: "darcs cli: exec".to_string(),
+                stderr: output.stderr,
+            });
+        }
+        Ok(lines
+            .iter()
+            .filter_map(|line| parse_summary_line(line))
+            .collect())
+    }
+
+    /// Lists files tracked in the working tree (which includes files added, but not yet
+    /// recorded).
+    fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.darcs_tracked_files().output(),
+            0, /*min_lines*/
+            "darcs cli: exec",
+            None,
+        )?;
+        Ok(lines
+            .iter()
+            .map(|path| PathBuf::from(path.strip_prefix(DARCS_PATH_PREFIX).unwrap_or(path)))
+            .collect())
+    }
+
+    /// Lists files touched by the newest patch when `base` and `head` are both `None`, and is
+    /// otherwise [`DriverError::Unsupported`]: Darcs states (see `current_ref_id`) are sets of
+    /// patches, so there's no history of trees to compare.
+    fn changed_files(
+        &self,
+        base: Option<&str>,
+        head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError> {
+        if base.is_some() || head.is_some() {
+            return Err(DriverError::Unsupported(
+                "darcs has no trees to compare states by".to_string(),
+            ));
+        }
+        let lines = DriverError::expect_cmd_lines(
+            self.darcs_latest_patch_files().output(),
+            0, /*min_lines*/
+            "darcs cli: exec",
+            None,
+        )?;
+        Ok(lines
+            .iter()
+            .filter_map(|line| parse_summary_line(line.trim_start()))
+            .map(|status| status.path)
+            .collect())
+    }
+
+    /// Always [`DriverError::Unsupported`]: patches can be applied in any order (so long as their
+    /// dependencies are), so a state has no single parent.
+    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        Err(DriverError::Unsupported(
+            "darcs states have no parents".to_string(),
+        ))
+    }
+
+    /// Always [`DriverError::Unsupported`], for the same reason as `parent_ref_id`.
+    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError> {
+        self.parent_ref_id().map(|_| None)
+    }
+
+    /// Always [`DriverError::Unsupported`], for the same reason as `parent_ref_id` (see
+    /// `current_ref_name` for the latest tag instead).
+    fn first_ancestor_ref_name(
+        &self,
+        _limit: Option<NonZero<u64>>,
+    ) -> Result<Option<AncestorRef>, DriverError> {
+        self.parent_ref_id().map(|_| None)
+    }
+
+    /// Always empty: the current state always includes every patch in the repo.
+    fn child_ref_ids(&self, _max: Option<NonZero<u64>>) -> Result<Vec<HistoryRefId>, DriverError> {
+        Ok(vec![])
+    }
+
+    /// Always `None`: Darcs only compares repos on request (see `darcs pull --dry-run`), which
+    /// takes network access.
+    fn ahead_behind(&self) -> Result<Option<AheadBehind>, DriverError> {
+        Ok(None)
+    }
+
+    /// Always `None`: Darcs has no multi-step operations (conflicts are recorded like any other
+    /// patch, and reported by `status()`).
+    fn operation(&self) -> Result<Option<RepoOperation>, DriverError> {
+        Ok(None)
+    }
+
+    /// Returns the "weak hash" of the repo: a hash of the _set_ of patches in it.
+    ///
+    /// Like Pijul, and unlike other brands' commit IDs, this doesn't depend on the order patches
+    /// were applied in, so two repos with the same patches share a state.
+    fn current_ref_id(&self, dirty_ok: bool) -> Result<HistoryRefId, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        if self
+            .show_repo_field(DARCS_SHOW_REPO_NUM_PATCHES)?
+            .is_some_and(|count| count == "0")
+        {
+            return Err(DriverError::EmptyHistory);
+        }
+        self.show_repo_field(DARCS_SHOW_REPO_WEAK_HASH)?
+            .ok_or_else(|| DriverError::UnexpectedOutput {
+                cmd: "darcs cli: exec".to_string(),
+                stdout: format!("no {DARCS_SHOW_REPO_WEAK_HASH} field"),
+            })
+    }
+
+    /// Returns the newest tag in the repo.
+    fn current_ref_name(&self, dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        let lines = DriverError::expect_cmd_lines(
+            self.darcs_tags().output(),
+            0, /*min_lines*/
+            "darcs cli: exec",
+            None,
+        )?;
+        Ok(lines.into_iter().next())
+    }
+}
+
+/// Parses one line of `--summary` output, eg: `M ./path -1 +2` or ` ./old -> ./new`.
+fn parse_summary_line(line: &str) -> Option<FileStatus> {
+    let strip = |path: &str| PathBuf::from(path.strip_prefix(DARCS_PATH_PREFIX).unwrap_or(path));
+    if let Some((from, to)) = line.trim_start().split_once(DARCS_SUMMARY_MOVE_SEP) {
+        return Some(FileStatus {
+            kind: FileStatusKind::Renamed,
+            path: strip(to.trim_end()),
+            original_path: Some(strip(from)),
+            staged: None,
+        });
+    }
+    let (code, rest) = line.split_once(' ')?;
+    let mut path = rest.trim_end();
+    // Modifications are followed by counts of lines removed and added (the path itself can hold
+    // spaces, so it's the rest of the line once those are dropped).
+    if code.starts_with('M') {
+        while let Some((head, count)) = path.rsplit_once(' ') {
+            let is_count = count.len() > 1
+                && count.starts_with(['-', '+'])
+                && count[1..].bytes().all(|b| b.is_ascii_digit());
+            if !is_count {
+                break;
+            }
+            path = head;
+        }
+    }
+    if path.is_empty() {
+        return None;
+    }
+    if path.ends_with('/') {
+        return None;
+    }
+    let kind = if code.ends_with(DARCS_SUMMARY_CONFLICT) {
+        FileStatusKind::Conflicted
+    } else {
+        match code {
+            "M" => FileStatusKind::Modified,
+            "A" => FileStatusKind::Added,
+            "R" => FileStatusKind::Deleted,
+            "a" => FileStatusKind::Untracked,
+            _ => return None,
+        }
+    };
+    Some(FileStatus {
+        kind,
+        path: strip(path),
+        original_path: None,
+        staged: None,
+    })
+}
@@ -0,0 +1,265 @@
+//! Drives any executable named `vcsq-adapter-<brand>` as an adapter of `<brand>`, so a VCS can be
+//! supported without changes to this library (see [`discover`] to find them on `$PATH`).
+//!
+//! Each question is a run of the executable (with no arguments), which is sent one line of JSON
+//! on stdin, and is expected to print one line of JSON to stdout then exit zero:
+//! - request: `{"query": QUERY, "dir": PATH}`, where `dir` is the directory being asked about
+//!   (and is null for `health`).
+//! - response: `{"ok": ANSWER}`, or `{"error": MESSAGE}` if the question couldn't be answered.
+//!
+//! Queries, and what `ANSWER` each expects:
+//! - `detect`: whether `dir` is a repo of this brand, as a boolean.
+//! - `health`: a report of the VCS's availability (eg: its `--version` output), as a string.
+//! - `root`: the repo's root directory, as a string.
+//! - `dirty-files`: list of `{"kind": KIND, "path": PATH, "original_path": PATH|null, "staged":
+//!   BOOL|null}` (as printed by `vcsq dirty-files --status --format json`).
+//! - `tracked-files`: list of paths, as strings.
+//! - `current-id`: the current revision's ID as a string, or null if there's no history yet.
+//! - `current-name`: the current revision's human-readable name as a string, or null if it has
+//!   none.
+//!
+//! Questions outside those are answered with [`DriverError::Unsupported`].
+use crate::plexer::VcsBrand;
+use crate::repo::{
+    AheadBehind, AncestorRef, Driver, DriverError, FileStatus, HistoryRefId, HistoryRefName,
+    QueryDir, RepoOperation, Validator, VcsAvailable,
+};
+use serde_json::{json, Value};
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::num::NonZero;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Prefix of the name of an executable adapter, which is followed by the brand it's an adapter of.
+pub const EXTERNAL_ADAPTER_PREFIX: &str = "vcsq-adapter-";
+
+const QUERY_DETECT: &str = "detect";
+const QUERY_HEALTH: &str = "health";
+const QUERY_ROOT: &str = "root";
+const QUERY_DIRTY_FILES: &str = "dirty-files";
+const QUERY_TRACKED_FILES: &str = "tracked-files";
+const QUERY_CURRENT_ID: &str = "current-id";
+const QUERY_CURRENT_NAME: &str = "current-name";
+
+#[derive(Debug, Clone)]
+pub struct Loader {
+    brand: String,
+    program: PathBuf,
+}
+
+#[derive(Debug)]
+pub struct ExternalDriver {
+    loader: Loader,
+    dir: QueryDir,
+}
+
+impl Loader {
+    /// Adapter of `brand` that runs `program` (eg: for an executable not named, or not on
+    /// `$PATH`, as [`discover`] expects).
+    #[must_use]
+    pub fn new(brand: String, program: PathBuf) -> Self {
+        Self { brand, program }
+    }
+
+    /// Asks `query` of the executable (about `dir`, if given) and returns its answer.
+    fn ask(&self, query: &str, dir: Option<&Path>) -> Result<Value, DriverError> {
+        let context = format!("{}: {}", self.program.display(), query);
+        let request = json!({ "query": query, "dir": dir });
+        let mut child = Command::new(&self.program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        if let Ok(child) = &mut child {
+            let mut stdin = child
+                .stdin
+                .take()
+                .expect("bug: stdin should have been piped");
+            writeln!(stdin, "{request}").map_err(|source| DriverError::Command {
+                context: context.clone(),
+                source,
+            })?;
+        }
+        let output = DriverError::expect_cmd_lossy(
+            context.clone(),
+            child.and_then(std::process::Child::wait_with_output),
+        )?;
+        let line = DriverError::expect_cmd_line(&context, &output)?;
+        let unexpected = || DriverError::UnexpectedOutput {
+            cmd: context.clone(),
+            stdout: output.stdout.clone(),
+        };
+        let mut response: Value = serde_json::from_str(&line).map_err(|_| unexpected())?;
+        if let Some(message) = response.get("error") {
+            return Err(DriverError::Stderr {
+                context: context.clone(),
+                stderr: message.as_str().map_or(message.to_string(), str::to_string),
+            });
+        }
+        response
+            .get_mut("ok")
+            .map(Value::take)
+            .ok_or_else(unexpected)
+    }
+
+    /// Like `ask`, but expects an answer of type `T`.
+    fn ask_for<T: serde::de::DeserializeOwned>(
+        &self,
+        query: &str,
+        dir: Option<&Path>,
+    ) -> Result<T, DriverError> {
+        let answer = self.ask(query, dir)?;
+        serde_json::from_value(answer.clone()).map_err(|_| DriverError::UnexpectedOutput {
+            cmd: format!("{}: {}", self.program.display(), query),
+            stdout: answer.to_string(),
+        })
+    }
+}
+
+impl Validator for Loader {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Other(self.brand.clone())
+    }
+
+    /// Claims `dir` only if the adapter answers `detect` with `true`: an adapter that can't be
+    /// run, errors, or answers with anything else is taken to not recognize `dir` (see
+    /// [`Validator::check_health`] to find out what's wrong with it).
+    fn new_driver(&self, dir: QueryDir) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        if !self
+            .ask_for::<bool>(QUERY_DETECT, Some(&dir))
+            .unwrap_or(false)
+        {
+            return Ok(None);
+        }
+        let repo: Box<dyn Driver> = Box::from(ExternalDriver {
+            loader: self.clone(),
+            dir,
+        });
+        Ok(Some(repo))
+    }
+
+    fn check_health(&self) -> Result<VcsAvailable, DriverError> {
+        let report: String = self.ask_for(QUERY_HEALTH, None)?;
+        Ok(VcsAvailable {
+            status: std::process::ExitStatus::default(),
+            stdout: report,
+            stderr: String::new(),
+        })
+    }
+}
+
+/// Finds every `vcsq-adapter-<brand>` executable on `$PATH` (only the first, for any brand found
+/// more than once, just as a shell would run).
+#[must_use]
+pub fn discover() -> Vec<Loader> {
+    let mut loaders: Vec<Loader> = vec![];
+    let Some(path) = env::var_os("PATH") else {
+        return loaders;
+    };
+    for dir in env::split_paths(&path) {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut found: Vec<Loader> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| is_executable(&entry.path()))
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                let brand = name.strip_prefix(EXTERNAL_ADAPTER_PREFIX)?;
+                let brand = brand.strip_suffix(env::consts::EXE_SUFFIX).unwrap_or(brand);
+                Some(Loader::new(brand.to_string(), entry.path()))
+            })
+            .filter(|loader| !loaders.iter().any(|l| l.brand == loader.brand))
+            .collect();
+        // Directory listings are in no particular order.
+        found.sort_by(|a, b| a.brand.cmp(&b.brand));
+        loaders.append(&mut found);
+    }
+    loaders
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Answer for questions the protocol has no query for.
+fn unsupported(question: &str) -> DriverError {
+    DriverError::Unsupported(format!("{question} (not asked of external adapters)"))
+}
+
+impl Driver for ExternalDriver {
+    fn brand(&self) -> VcsBrand {
+        self.loader.brand()
+    }
+
+    fn root(&self) -> Result<QueryDir, DriverError> {
+        self.loader.ask_for(QUERY_ROOT, Some(&self.dir))
+    }
+
+    fn status(&self) -> Result<Vec<FileStatus>, DriverError> {
+        self.loader.ask_for(QUERY_DIRTY_FILES, Some(&self.dir))
+    }
+
+    fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError> {
+        self.loader.ask_for(QUERY_TRACKED_FILES, Some(&self.dir))
+    }
+
+    fn changed_files(
+        &self,
+        _base: Option<&str>,
+        _head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError> {
+        Err(unsupported("changed files"))
+    }
+
+    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        Err(unsupported("parent id"))
+    }
+
+    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError> {
+        Err(unsupported("parent name"))
+    }
+
+    fn first_ancestor_ref_name(
+        &self,
+        _limit: Option<NonZero<u64>>,
+    ) -> Result<Option<AncestorRef>, DriverError> {
+        Err(unsupported("ancestor names"))
+    }
+
+    fn child_ref_ids(&self, _max: Option<NonZero<u64>>) -> Result<Vec<HistoryRefId>, DriverError> {
+        Err(unsupported("child ids"))
+    }
+
+    fn ahead_behind(&self) -> Result<Option<AheadBehind>, DriverError> {
+        Err(unsupported("upstream divergence"))
+    }
+
+    fn operation(&self) -> Result<Option<RepoOperation>, DriverError> {
+        Err(unsupported("operation in progress"))
+    }
+
+    fn current_ref_id(&self, dirty_ok: bool) -> Result<HistoryRefId, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        let id: Option<HistoryRefId> = self.loader.ask_for(QUERY_CURRENT_ID, Some(&self.dir))?;
+        id.ok_or(DriverError::EmptyHistory)
+    }
+
+    fn current_ref_name(&self, dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        self.loader.ask_for(QUERY_CURRENT_NAME, Some(&self.dir))
+    }
+}
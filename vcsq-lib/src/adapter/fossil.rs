@@ -0,0 +1,437 @@
+use crate::plexer::VcsBrand;
+use crate::repo::{
+    AheadBehind, AncestorRef, Driver, DriverError, FileStatus, FileStatusKind, HistoryRefId,
+    HistoryRefName, QueryDir, RepoOperation, Validator, VcsAvailable,
+};
+use std::num::NonZero;
+use std::path::PathBuf;
+use std::process::Command;
+
+static VCS_BIN_NAME: &str = "fossil";
+
+/// Names Fossil gives the checkout database it keeps at the root of every checkout (the latter
+/// being the legacy name, still used on some platforms).
+const FOSSIL_CHECKOUT_DB_NAMES: [&str; 2] = [".fslckout", "_FOSSIL_"];
+
+/// Labels `fossil info` gives the line carrying the check-in hash: the first when run against
+/// the checkout, the others when run against a given check-in (depending on Fossil's version).
+const FOSSIL_INFO_ID_LABELS: [&str; 3] = ["checkout:", "hash:", "uuid:"];
+
+/// Label of each `fossil info` line carrying a parent's hash (primary parent first).
+const FOSSIL_INFO_PARENT_LABEL: &str = "parent:";
+
+/// Label of each `fossil info` line carrying a child's hash.
+const FOSSIL_INFO_CHILD_LABEL: &str = "child:";
+
+/// Prefix `fossil tag list --raw` gives human-made (ie: symbolic) tags, including the one every
+/// branch gets named after itself.
+const FOSSIL_TAG_SYM_PREFIX: &str = "sym-";
+
+/// Raw name of the tag whose value is the branch a check-in is on, eg: `branch=trunk`.
+const FOSSIL_TAG_BRANCH: &str = "branch";
+
+/// Labels `fossil diff --brief` gives each file it lists.
+const FOSSIL_DIFF_BRIEF_LABELS: [&str; 3] = ["ADDED", "DELETED", "CHANGED"];
+
+/// Labels `fossil changes --merge` gives the check-ins merged into the checkout (but not yet
+/// committed).
+const FOSSIL_MERGE_LABELS: [(&str, RepoOperation); 4] = [
+    ("MERGED_WITH", RepoOperation::Merge),
+    ("INTEGRATE", RepoOperation::Merge),
+    ("CHERRYPICK", RepoOperation::CherryPick),
+    ("BACKOUT", RepoOperation::Revert),
+];
+
+#[derive(Debug)]
+pub struct Repo {
+    /// Root of the checkout (ie: the directory holding one of `FOSSIL_CHECKOUT_DB_NAMES`).
+    root: QueryDir,
+}
+
+#[derive(Debug)]
+pub struct Loader
+where
+    Self: Sized;
+
+impl Validator for Loader {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Fossil
+    }
+
+    /// Looks for a Fossil checkout database in `dir` or any of its ancestors, so this never
+    /// needs to shell out.
+    fn new_driver(&self, dir: QueryDir) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        // Relative paths (eg: `.`) lack the ancestors to search, so canonicalize first.
+        let dir = dir
+            .canonicalize()
+            .map_err(|e| DriverError::Directory(format!("{}: {}", dir.display(), e)))?;
+        let root = dir.ancestors().find(|ancestor| {
+            FOSSIL_CHECKOUT_DB_NAMES
+                .iter()
+                .any(|db_name| ancestor.join(db_name).is_file())
+        });
+        Ok(root.map(|root| {
+            let repo: Box<dyn Driver> = Box::from(Repo {
+                root: root.to_path_buf(),
+            });
+            repo
+        }))
+    }
+
+    fn check_health(&self) -> Result<VcsAvailable, DriverError> {
+        let mut cmd = Command::new(VCS_BIN_NAME);
+        cmd.arg("version");
+        DriverError::expect_cmd_lossy("fossil cli: exec".to_string(), cmd.output())
+    }
+}
+
+/// What `fossil info` reports of a check-in's place in history.
+#[derive(Debug, Default)]
+struct CheckinInfo {
+    /// Hash of the check-in, or `None` if the checkout has no check-in at all (ie: the repo was
+    /// made with `fossil new --empty`).
+    id: Option<HistoryRefId>,
+
+    /// Hashes of the check-in's parents, primary parent first.
+    parents: Vec<HistoryRefId>,
+
+    /// Hashes of the check-in's children.
+    children: Vec<HistoryRefId>,
+}
+
+/// What `fossil tag list --raw` reports of a check-in's names.
+#[derive(Debug, Default)]
+struct CheckinTags {
+    /// Branch the check-in is on.
+    branch: Option<HistoryRefName>,
+
+    /// Human-made tags on the check-in, besides the one named after its branch.
+    names: Vec<HistoryRefName>,
+}
+
+impl CheckinTags {
+    /// Name as reported by `current_ref_name`: a tag if there is one, otherwise the branch.
+    fn name(self) -> Option<HistoryRefName> {
+        self.names.into_iter().next().or(self.branch)
+    }
+}
+
+impl Repo {
+    fn start_shellout(&self) -> Command {
+        let mut cmd = Command::new(VCS_BIN_NAME);
+        cmd.current_dir(self.root.clone());
+        cmd
+    }
+
+    /// Describes `checkin`, or the checkout's own check-in if `None`.
+    fn fossil_info(&self, checkin: Option<&str>) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("info");
+        if let Some(checkin) = checkin {
+            cmd.arg(checkin);
+        }
+        cmd
+    }
+
+    fn fossil_dirty_files(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("changes")
+            .arg("--changed")
+            .arg("--extra")
+            .arg("--classify");
+        cmd
+    }
+
+    fn fossil_merges(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("changes").arg("--merge");
+        cmd
+    }
+
+    fn fossil_tracked_files(&self, checkin: Option<&str>) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("ls");
+        if let Some(checkin) = checkin {
+            cmd.arg("-r").arg(checkin);
+        }
+        cmd
+    }
+
+    fn fossil_tags_of(&self, checkin: &str) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("tag").arg("list").arg("--raw").arg(checkin);
+        cmd
+    }
+
+    fn fossil_files_between(&self, base: &str, head: &str) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("diff")
+            .arg("--brief")
+            .arg("--from")
+            .arg(base)
+            .arg("--to")
+            .arg(head);
+        cmd
+    }
+
+    fn info(&self, checkin: Option<&str>) -> Result<CheckinInfo, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.fossil_info(checkin).output(),
+            1, /*min_lines*/
+            "fossil cli: exec",
+            None,
+        )?;
+        let mut info = CheckinInfo::default();
+        for line in lines {
+            let Some((label, value)) = line.split_once(char::is_whitespace) else {
+                continue;
+            };
+            // Hashes are followed by the check-in's timestamp.
+            let Some(hash) = value.split_whitespace().next() else {
+                continue;
+            };
+            if FOSSIL_INFO_ID_LABELS.contains(&label) && info.id.is_none() {
+                info.id = Some(hash.to_string());
+            } else if label == FOSSIL_INFO_PARENT_LABEL {
+                info.parents.push(hash.to_string());
+            } else if label == FOSSIL_INFO_CHILD_LABEL {
+                info.children.push(hash.to_string());
+            }
+        }
+        Ok(info)
+    }
+
+    /// Hash of the checkout's check-in, or [`DriverError::EmptyHistory`] if there isn't one.
+    fn current_id(&self) -> Result<HistoryRefId, DriverError> {
+        self.info(None)?.id.ok_or(DriverError::EmptyHistory)
+    }
+
+    fn tags_of(&self, checkin: &str) -> Result<CheckinTags, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.fossil_tags_of(checkin).output(),
+            0, /*min_lines*/
+            "fossil cli: exec",
+            None,
+        )?;
+        let mut tags = CheckinTags::default();
+        let mut sym_tags = vec![];
+        for line in lines {
+            if let Some(name) = line.strip_prefix(FOSSIL_TAG_SYM_PREFIX) {
+                sym_tags.push(name.to_string());
+            } else if let Some(branch) = line.strip_prefix(FOSSIL_TAG_BRANCH) {
+                let branch = branch.trim_start_matches(['=', ' ']);
+                if !branch.is_empty() {
+                    tags.branch = Some(branch.to_string());
+                }
+            }
+        }
+        tags.names = sym_tags
+            .into_iter()
+            .filter(|name| tags.branch.as_ref() != Some(name))
+            .collect();
+        Ok(tags)
+    }
+}
+
+impl Driver for Repo {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Fossil
+    }
+
+    fn root(&self) -> Result<QueryDir, DriverError> {
+        Ok(self.root.clone())
+    }
+
+    /// Lists changes to the checkout (Fossil has no index, so nothing is ever staged).
+    fn status(&self) -> Result<Vec<FileStatus>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.fossil_dirty_files().output(),
+            0, /*min_lines*/
+            "fossil cli: exec",
+            None,
+        )?;
+        Ok(lines
+            .iter()
+            .filter_map(|line| parse_changes_line(line))
+            .collect())
+    }
+
+    fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.fossil_tracked_files(None).output(),
+            0, /*min_lines*/
+            "fossil cli: exec",
+            None,
+        )?;
+        Ok(lines.into_iter().map(PathBuf::from).collect())
+    }
+
+    fn changed_files(
+        &self,
+        base: Option<&str>,
+        head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError> {
+        let head = match head {
+            Some(head) => head.to_string(),
+            None => match self.current_id() {
+                Ok(id) => id,
+                Err(DriverError::EmptyHistory) => return Ok(vec![]),
+                Err(err) => return Err(err),
+            },
+        };
+        let base = match base {
+            Some(base) => base.to_string(),
+            None => match self.info(Some(&head))?.parents.into_iter().next() {
+                Some(parent) => parent,
+                // Everything in a root check-in is new.
+                None => {
+                    let lines = DriverError::expect_cmd_lines(
+                        self.fossil_tracked_files(Some(&head)).output(),
+                        0, /*min_lines*/
+                        "fossil cli: exec",
+                        None,
+                    )?;
+                    return Ok(lines.into_iter().map(PathBuf::from).collect());
+                }
+            },
+        };
+        let lines = DriverError::expect_cmd_lines(
+            self.fossil_files_between(&base, &head).output(),
+            0, /*min_lines*/
+            "fossil cli: exec",
+            None,
+        )?;
+        Ok(lines
+            .iter()
+            .filter_map(|line| {
+                let (label, path) = line.split_once(char::is_whitespace)?;
+                FOSSIL_DIFF_BRIEF_LABELS
+                    .contains(&label)
+                    .then(|| PathBuf::from(path.trim_start()))
+            })
+            .collect())
+    }
+
+    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        let info = self.info(None)?;
+        if info.id.is_none() {
+            return Err(DriverError::EmptyHistory);
+        }
+        info.parents.into_iter().next().ok_or(DriverError::NoParent)
+    }
+
+    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError> {
+        let parent_id = self.parent_ref_id()?;
+        Ok(self.tags_of(&parent_id)?.name())
+    }
+
+    /// Returns the nearest Fossil tag (ignoring branches, which name every check-in on them) on
+    /// the primary-parent history of the checkout.
+    fn first_ancestor_ref_name(
+        &self,
+        limit: Option<NonZero<u64>>,
+    ) -> Result<Option<AncestorRef>, DriverError> {
+        let mut ancestor = self.info(None)?;
+        for steps_back in 1.. {
+            if limit.is_some_and(|limit| steps_back > limit.get()) {
+                break;
+            }
+            let Some(parent_id) = ancestor.parents.into_iter().next() else {
+                break;
+            };
+            if let Some(name) = self.tags_of(&parent_id)?.names.into_iter().next() {
+                return Ok(Some(AncestorRef {
+                    id: parent_id,
+                    name,
+                    distance: NonZero::new(steps_back).expect("bug: walk starts at 1"),
+                }));
+            }
+            ancestor = self.info(Some(&parent_id))?;
+        }
+        Ok(None)
+    }
+
+    fn child_ref_ids(&self, max: Option<NonZero<u64>>) -> Result<Vec<HistoryRefId>, DriverError> {
+        let mut children = self.info(None)?.children;
+        if let Some(max) = max {
+            children.truncate(usize::try_from(max.get()).unwrap_or(usize::MAX));
+        }
+        Ok(children)
+    }
+
+    /// Always `None`: Fossil syncs with its remote automatically (see `autosync`), so it doesn't
+    /// track how far the two have diverged.
+    fn ahead_behind(&self) -> Result<Option<AheadBehind>, DriverError> {
+        Ok(None)
+    }
+
+    /// Reports merges (and cherry-picks, and back-outs) that are applied to the checkout, but
+    /// not yet committed.
+    fn operation(&self) -> Result<Option<RepoOperation>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.fossil_merges().output(),
+            0, /*min_lines*/
+            "fossil cli: exec",
+            None,
+        )?;
+        Ok(lines.iter().find_map(|line| {
+            let label = line.split_whitespace().next()?;
+            FOSSIL_MERGE_LABELS
+                .iter()
+                .find(|(merge_label, _)| *merge_label == label)
+                .map(|(_, operation)| *operation)
+        }))
+    }
+
+    /// Returns the hash of the checkout's check-in (note `fossil new` starts every repo with an
+    /// empty check-in, so only repos made with `--empty` have no history).
+    fn current_ref_id(&self, dirty_ok: bool) -> Result<HistoryRefId, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        self.current_id()
+    }
+
+    /// Returns a tag on the checkout's check-in if there is one, otherwise the branch it's on.
+    fn current_ref_name(&self, dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        let current_id = match self.current_id() {
+            Ok(id) => id,
+            Err(DriverError::EmptyHistory) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        Ok(self.tags_of(&current_id)?.name())
+    }
+}
+
+/// Parses one line of `fossil changes --classify` output, eg: `EDITED     path`.
+fn parse_changes_line(line: &str) -> Option<FileStatus> {
+    let (label, path) = line.split_once(char::is_whitespace)?;
+    let kind = match label {
+        "ADDED" | "ADDED_BY_MERGE" | "ADDED_BY_INTEGRATE" => FileStatusKind::Added,
+        "DELETED" | "MISSING" | "NOT_A_FILE" => FileStatusKind::Deleted,
+        "RENAMED" => FileStatusKind::Renamed,
+        "CONFLICT" => FileStatusKind::Conflicted,
+        "EXTRA" => FileStatusKind::Untracked,
+        "EDITED"
+        | "UPDATED_BY_MERGE"
+        | "UPDATED_BY_INTEGRATE"
+        | "EXECUTABLE"
+        | "SYMLINK"
+        | "UNEXEC"
+        | "UNLINK" => FileStatusKind::Modified,
+        _ => return None,
+    };
+    let path = path.trim_start();
+    if path.is_empty() {
+        return None;
+    }
+    Some(FileStatus {
+        kind,
+        path: PathBuf::from(path),
+        original_path: None,
+        staged: None,
+    })
+}
@@ -1,13 +1,23 @@
+use crate::plexer::VcsBrand;
 use crate::repo::{
-    Driver, DriverError, HistoryRefId, HistoryRefName, QueryDir, Validator, VcsAvailable,
-    ERROR_REPO_NOT_CLEAN, ERROR_REPO_NOT_DIRTY,
+    may_be_in_repo, AheadBehind, AncestorRef, Driver, DriverError, FileStatus, FileStatusKind,
+    HistoryRefId, HistoryRefName, QueryDir, RepoOperation, RepoSnapshot, SnapshotId, Validator,
+    VcsAvailable,
 };
-use const_format::concatcp;
+use std::env;
+use std::num::NonZero;
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
 
 static VCS_BIN_NAME: &str = "git";
 
+/// Entry (a directory, or a file pointing at one, eg: in a worktree) at a repo's root holding its
+/// metadata.
+pub(crate) const GIT_META_DIR: &str = ".git";
+
+/// Environment variable pointing git at a repo's metadata, wherever it is.
+const GIT_ENV_DIR: &str = "GIT_DIR";
+
 /// Error string intended to match the case when git prints that "HEAD" is an unknown version
 /// because there's not yet any logs in the repo.
 ///
@@ -21,38 +31,78 @@ static VCS_BIN_NAME: &str = "git";
 ///   'git <command> [<revision>...] -- [<file>...]'
 /// ```
 const GIT_ERROR_NO_LOG_YET: &str = "fatal: ambiguous argument 'HEAD': unknown revision";
-const GIT_SYNTHETIC_FIRST_COMMIT_ID: &str = "00000000000000000000000000000000000000000000000000";
-
-const VCSQ_FIRST_COMMIT_ID: &str = "0ff8325e7d74a838d39cdffff9cddcecdce30f10";
-const VCSQ_UNIQUE_PREFIX: &str = concatcp!("VCSQ_SCRAPING_", VCSQ_FIRST_COMMIT_ID, "_");
-const GIT_LOG_SCRAPABLE_PRETTY_DECOR_PREFIX: &str =
-    concatcp!("prefix=", VCSQ_UNIQUE_PREFIX, "prefix");
-const GIT_LOG_SCRAPABLE_PRETTY_DECOR_POINTER: &str =
-    concatcp!("pointer=", VCSQ_UNIQUE_PREFIX, "pointer");
-const GIT_LOG_SCRAPABLE_PRETTY_DECOR_SUFFIX: &str =
-    concatcp!("suffix=", VCSQ_UNIQUE_PREFIX, "suffix");
-const GIT_LOG_SCRAPABLE_PRETTY_DECOR_TAG: &str = concatcp!("tag=", VCSQ_UNIQUE_PREFIX, "tag");
-const GIT_LOG_SCRAPABLE_PRETTY_DECOR_SEP: &str =
-    concatcp!("separator=", VCSQ_UNIQUE_PREFIX, "separator");
-
-const GIT_LOG_SCRAPABLE_PRETTY_FMT: &str = concatcp!(
-    GIT_LOG_SCRAPABLE_PRETTY_DECOR_PREFIX,
-    ",",
-    GIT_LOG_SCRAPABLE_PRETTY_DECOR_POINTER,
-    ",",
-    GIT_LOG_SCRAPABLE_PRETTY_DECOR_SUFFIX,
-    ",",
-    GIT_LOG_SCRAPABLE_PRETTY_DECOR_TAG,
-    ",",
-    GIT_LOG_SCRAPABLE_PRETTY_DECOR_SEP,
-    ","
-);
-const GIT_LOG_SCRAPABLE_PRETTY_FLAG: &str =
-    concatcp!("--pretty=%(decorate:", GIT_LOG_SCRAPABLE_PRETTY_FMT, ")");
+
+/// Error string intended to match the case when `git tag --points-at` is asked about "HEAD"
+/// before there's any history in the repo.
+///
+/// ```sh
+/// $ git tag --points-at HEAD
+///   error: malformed object name 'HEAD'
+/// ```
+const GIT_ERROR_NO_SUCH_OBJECT: &str = "error: malformed object name";
+
+/// Field separator (tab) for `GIT_LOG_ANCESTOR_TAGS_FLAG` output; git refnames can't contain
+/// whitespace.
+const GIT_LOG_ANCESTOR_TAGS_SEP: char = '\t';
+
+/// Prefix git gives each tag in `%D` output.
+const GIT_LOG_DECORATION_TAG_PREFIX: &str = "tag: ";
+
+/// Lines of the form `ID<tab>tag: NAME, tag: NAME` (meant to be used along with
+/// `--decorate-refs=refs/tags/` so only tags are listed).
+const GIT_LOG_ANCESTOR_TAGS_FLAG: &str = "--format=%H%x09%D";
+
+/// Separator between new and original paths of renames/copies in `git status --porcelain=v2`
+/// output.
+const GIT_STATUS_RENAME_SEP: char = '\t';
+
+/// Pairs of `git status --porcelain` status codes that indicate a merge conflict.
+const GIT_STATUS_CONFLICTS: [&str; 7] = ["DD", "AU", "UD", "UA", "DU", "AA", "UU"];
+
+/// Header of the `git status --porcelain=v2 --branch` line reporting the current commit, eg:
+/// `# branch.oid 0ff8325e...`.
+const GIT_STATUS_OID_HEADER: &str = "# branch.oid ";
+
+/// `GIT_STATUS_OID_HEADER` value for a repo with no history yet.
+const GIT_STATUS_OID_INITIAL: &str = "(initial)";
+
+/// Header of the `git status --porcelain=v2 --branch` line reporting the current branch, eg:
+/// `# branch.head main`.
+const GIT_STATUS_HEAD_HEADER: &str = "# branch.head ";
+
+/// `GIT_STATUS_HEAD_HEADER` value when HEAD isn't on any branch.
+const GIT_STATUS_HEAD_DETACHED: &str = "(detached)";
+
+/// Header of the `git status --porcelain=v2 --branch` line reporting ahead/behind counts, eg:
+/// `# branch.ab +1 -2` (only printed when the current branch has an upstream).
+const GIT_STATUS_AHEAD_BEHIND_HEADER: &str = "# branch.ab ";
+
+/// Files (or directories) git leaves in its git-dir while an operation is in progress.
+const GIT_OPERATION_MARKERS: [(&str, RepoOperation); 6] = [
+    ("rebase-merge", RepoOperation::Rebase),
+    ("rebase-apply", RepoOperation::Rebase),
+    ("MERGE_HEAD", RepoOperation::Merge),
+    ("CHERRY_PICK_HEAD", RepoOperation::CherryPick),
+    ("REVERT_HEAD", RepoOperation::Revert),
+    ("BISECT_LOG", RepoOperation::Bisect),
+];
+
+/// Everything `git status --porcelain=v2 --branch` says about HEAD and the working tree.
+struct BranchStatus {
+    /// Commit HEAD points at, unless there's no history yet.
+    oid: Option<HistoryRefId>,
+
+    /// Branch HEAD points at, unless HEAD is detached.
+    head: Option<HistoryRefName>,
+
+    ahead_behind: Option<AheadBehind>,
+
+    files: Vec<FileStatus>,
+}
 
 #[derive(Debug)]
 pub struct Repo {
-    dir: QueryDir,
+    pub(crate) dir: QueryDir,
 }
 
 #[derive(Debug)]
@@ -61,6 +111,10 @@ where
     Self: Sized;
 
 impl Validator for Loader {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Git
+    }
+
     /// Whether `dir` is a git repo (if so: wraps it in an object you can call for more
     /// questions.
     ///
@@ -69,6 +123,10 @@ impl Validator for Loader {
     /// ( cd "$1"; git rev-parse --show-toplevel >/dev/null 2>&1; )
     /// ```
     fn new_driver(&self, dir: QueryDir) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        // `$GIT_DIR` lets git find a repo from anywhere.
+        if env::var_os(GIT_ENV_DIR).is_none() && !may_be_in_repo(&dir, GIT_META_DIR) {
+            return Ok(None);
+        }
         let repo = Repo { dir };
         let is_ok = DriverError::unwrap_cmd_lossy(
             "git cli".to_string(),
@@ -108,9 +166,20 @@ impl Repo {
         cmd
     }
 
-    fn git_dirty_files(&self) -> Command {
+    /// Lists every dirty file (by its path relative to the repo root, even when run from a
+    /// subdirectory), preceded by headers describing HEAD (see `BranchStatus`).
+    fn git_branch_status(&self) -> Command {
         let mut cmd = self.start_shellout();
-        cmd.arg("status").arg("--porcelain");
+        cmd.args(["-c", "status.relativePaths=false"])
+            .arg("status")
+            .arg("--porcelain=v2")
+            .arg("--branch");
+        cmd
+    }
+
+    fn git_dir(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("rev-parse").arg("--absolute-git-dir");
         cmd
     }
 
@@ -122,29 +191,163 @@ impl Repo {
         cmd
     }
 
-    fn git_current_ref_id(&self) -> Command {
+    /// Lists files `rev` changed relative to its first parent (or all of its files, if it's a
+    /// root commit).
+    fn git_files_of(&self, rev: &str) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("diff-tree")
+            .arg("-r")
+            .arg("--name-only")
+            .arg("--no-commit-id")
+            .arg("--root")
+            .arg("--diff-merges=first-parent")
+            .arg(rev);
+        cmd
+    }
+
+    fn git_files_between(&self, base: &str, head: &str) -> Command {
         let mut cmd = self.start_shellout();
-        cmd.arg("rev-parse").arg("HEAD");
+        cmd.arg("diff").arg("--name-only").arg(base).arg(head);
         cmd
     }
 
-    fn git_current_ref_name(&self) -> Command {
+    /// Lists tags pointing at `rev`, sorted by name.
+    fn git_tags_at(&self, rev: &str) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("tag").arg("--points-at").arg(rev);
+        cmd
+    }
+
+    fn git_branches_at(&self, rev: &str) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("branch")
+            .arg("--format=%(refname:short)")
+            .arg("--points-at")
+            .arg(rev);
+        cmd
+    }
+
+    /// Lists `rev` followed by all of its parents (first-parent first) on a single line.
+    fn git_parents_of(&self, rev: &str) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("rev-list")
+            .arg("--parents")
+            .arg("-n")
+            .arg("1")
+            .arg(rev);
+        cmd
+    }
+
+    fn git_first_parent_tags(&self, limit: Option<NonZero<u64>>) -> Command {
         let mut cmd = self.start_shellout();
         cmd.arg("log")
-            .arg(GIT_LOG_SCRAPABLE_PRETTY_FLAG)
-            .arg("HEAD")
-            .arg("-1");
+            .arg("--first-parent")
+            .arg("--skip=1") // HEAD itself
+            .arg("--decorate-refs=refs/tags/")
+            .arg(GIT_LOG_ANCESTOR_TAGS_FLAG);
+        if let Some(limit) = limit {
+            cmd.arg(format!("--max-count={limit}"));
+        }
+        cmd.arg("HEAD");
         cmd
     }
 
-    fn git_current_branch(&self) -> Command {
+    /// Lists every commit reachable from any ref, each followed by its children, one per line.
+    fn git_all_children(&self) -> Command {
         let mut cmd = self.start_shellout();
-        cmd.arg("branch").arg("--show-current");
+        cmd.arg("rev-list").arg("--children").arg("--all");
         cmd
     }
+
+    /// Returns the tag pointing at `rev` (the lowest-sorting one, if there are several).
+    fn tag_at(&self, rev: &str) -> Result<Option<HistoryRefName>, DriverError> {
+        let out = DriverError::expect_cmd_lossy(
+            "git cli :exec".to_string(),
+            self.git_tags_at(rev).output(),
+        );
+        let output = match out {
+            Ok(output) => output,
+            Err(DriverError::Stderr {
+                context: _,
+                ref stderr,
+            }) if stderr.contains(GIT_ERROR_NO_SUCH_OBJECT) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        Ok(output.stdout_strings().into_iter().next())
+    }
+
+    /// Names HEAD (at commit `oid`, on branch `head`) by its tag if it has one, else its branch.
+    fn name_of(
+        &self,
+        oid: Option<&HistoryRefId>,
+        head: Option<HistoryRefName>,
+    ) -> Result<Option<HistoryRefName>, DriverError> {
+        let tag = match oid {
+            Some(_) => self.tag_at("HEAD")?,
+            None => None,
+        };
+        Ok(tag.or(head))
+    }
+
+    fn branch_status(&self) -> Result<BranchStatus, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.git_branch_status().output(),
+            0, /*min_lines*/
+            "git cli: exec",
+            None,
+        )?;
+        let mut branch_status = BranchStatus {
+            oid: None,
+            head: None,
+            ahead_behind: None,
+            files: vec![],
+        };
+        for line in &lines {
+            if let Some(oid) = line.strip_prefix(GIT_STATUS_OID_HEADER) {
+                branch_status.oid = Some(oid.to_string()).filter(|o| o != GIT_STATUS_OID_INITIAL);
+            } else if let Some(head) = line.strip_prefix(GIT_STATUS_HEAD_HEADER) {
+                branch_status.head =
+                    Some(head.to_string()).filter(|h| h != GIT_STATUS_HEAD_DETACHED);
+            } else if let Some(counts) = line.strip_prefix(GIT_STATUS_AHEAD_BEHIND_HEADER) {
+                branch_status.ahead_behind = Some(parse_ahead_behind(counts)?);
+            } else {
+                branch_status.files.extend(parse_porcelain_line(line));
+            }
+        }
+        Ok(branch_status)
+    }
+
+    /// Returns the first-parent of HEAD, or an error if HEAD is the root of history (or there's
+    /// no history at all).
+    fn first_parent_id(&self) -> Result<HistoryRefId, DriverError> {
+        let out = DriverError::expect_cmd_lossy(
+            "git cli :exec".to_string(),
+            self.git_parents_of("HEAD").output(),
+        );
+        let output = match out {
+            Ok(output) => output,
+            Err(DriverError::Stderr {
+                context: _,
+                ref stderr,
+            }) if stderr.contains(GIT_ERROR_NO_LOG_YET) => {
+                return Err(DriverError::EmptyHistory);
+            }
+            Err(err) => return Err(err),
+        };
+        let line = DriverError::expect_cmd_line("git cli: exec", &output)?;
+        // First ID is HEAD itself, then its parents in order.
+        line.split_whitespace()
+            .nth(1)
+            .map(std::string::ToString::to_string)
+            .ok_or(DriverError::NoParent)
+    }
 }
 
 impl Driver for Repo {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Git
+    }
+
     fn root(&self) -> Result<QueryDir, DriverError> {
         let output = DriverError::expect_cmd_lossy(
             "git cli".to_string(),
@@ -155,24 +358,26 @@ impl Driver for Repo {
         )?))
     }
 
-    fn dirty_files(&self, clean_ok: bool) -> Result<Vec<QueryDir>, DriverError> {
-        let min_lines = u8::from(!clean_ok);
-        let lines = DriverError::expect_cmd_lines(
-            self.git_dirty_files().output(),
-            min_lines,
-            "git cli: exec",
-            Some(ERROR_REPO_NOT_DIRTY.to_string()),
-        )?;
-        let files = lines
-            .into_iter()
-            .map(|ln| {
-                // first 3 chars are modification-indicators like "?? " to indicate the file is
-                // untracked.
-                ln.chars().skip(3).collect::<String>()
-            })
-            .map(PathBuf::from)
-            .collect();
-        Ok(files)
+    /// Answers everything from a single `git status` (plus finding the root, and a tag if HEAD
+    /// has one).
+    fn snapshot(&self) -> Result<RepoSnapshot, DriverError> {
+        let BranchStatus {
+            oid,
+            head,
+            ahead_behind: _,
+            files,
+        } = self.branch_status()?;
+        Ok(RepoSnapshot {
+            brand: self.brand(),
+            root: self.root()?,
+            name: self.name_of(oid.as_ref(), head)?,
+            id: oid.map_or(SnapshotId::EmptyHistory, SnapshotId::Id),
+            status: files,
+        })
+    }
+
+    fn status(&self) -> Result<Vec<FileStatus>, DriverError> {
+        Ok(self.branch_status()?.files)
     }
 
     fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError> {
@@ -186,78 +391,239 @@ impl Driver for Repo {
         Ok(files)
     }
 
-    fn current_ref_id(&self, dirty_ok: bool) -> Result<HistoryRefId, DriverError> {
-        if !dirty_ok && !self.is_clean()? {
-            return Err(ERROR_REPO_NOT_CLEAN.to_string().into());
+    fn changed_files(
+        &self,
+        base: Option<&str>,
+        head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError> {
+        let head_rev = head.unwrap_or("HEAD");
+        let mut cmd = match base {
+            Some(base) => self.git_files_between(base, head_rev),
+            None => self.git_files_of(head_rev),
+        };
+        let out = DriverError::expect_cmd_lossy("git cli :exec".to_string(), cmd.output());
+        let lines = match out {
+            Ok(output) => output.stdout_strings(),
+            Err(DriverError::Stderr {
+                context: _,
+                ref stderr,
+            }) if head.is_none() && stderr.contains(GIT_ERROR_NO_LOG_YET) => return Ok(vec![]),
+            Err(err) => return Err(err),
+        };
+        Ok(lines.into_iter().map(PathBuf::from).collect())
+    }
+
+    /// Returns the first parent of HEAD (so for merge commits: the commit that was checked out
+    /// when the merge was made).
+    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        self.first_parent_id()
+    }
+
+    /// Returns a git tag on the first parent if available, otherwise the first of any branches
+    /// pointing at it.
+    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError> {
+        let parent_id = self.first_parent_id()?;
+        let tag = self.tag_at(&parent_id)?;
+        if tag.is_some() {
+            return Ok(tag);
         }
 
+        let branches = DriverError::expect_cmd_lines(
+            self.git_branches_at(&parent_id).output(),
+            0, /*min_lines*/
+            "git cli: exec",
+            None,
+        )?;
+        Ok(branches.into_iter().next())
+    }
+
+    /// Returns the nearest git tag on HEAD's first-parent history.
+    fn first_ancestor_ref_name(
+        &self,
+        limit: Option<NonZero<u64>>,
+    ) -> Result<Option<AncestorRef>, DriverError> {
         let out = DriverError::expect_cmd_lossy(
             "git cli :exec".to_string(),
-            self.git_current_ref_id().output(),
+            self.git_first_parent_tags(limit).output(),
         );
-        match out {
-            Ok(output) => DriverError::expect_cmd_line("git cli: exec", &output),
-            Err(err) => {
-                match err {
-                    DriverError::Stderr {
-                        context: _,
-                        ref stderr,
-                    } => {
-                        if stderr.contains(GIT_ERROR_NO_LOG_YET) {
-                            // TODO: (feature) reocnsider this approach; perhaps better to do the inverse: make
-                            // other adapters return a special signal "first commit" back up, and let our CLI
-                            // add options for this case (and handle uniformly). As is, this synthetic commit
-                            // might lead to some confusion down the line if it gets passed back to git.
-                            return Ok(GIT_SYNTHETIC_FIRST_COMMIT_ID.into());
-                        }
-                        Err(err)
-                    }
-                    DriverError::Directory(_)
-                    | DriverError::Command {
-                        context: _,
-                        source: _,
-                    }
-                    | DriverError::RootName(_)
-                    | DriverError::Unknown(_) => Err(err),
-                }
+        let lines = match out {
+            Ok(output) => output.stdout_strings(),
+            Err(DriverError::Stderr {
+                context: _,
+                ref stderr,
+            }) if stderr.contains(GIT_ERROR_NO_LOG_YET) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        for (steps_back, line) in (1..).zip(lines) {
+            let Some((id, decorations)) = line.split_once(GIT_LOG_ANCESTOR_TAGS_SEP) else {
+                continue;
+            };
+            let tag = decorations
+                .split(", ")
+                .find_map(|item| item.strip_prefix(GIT_LOG_DECORATION_TAG_PREFIX));
+            if let Some(name) = tag {
+                return Ok(Some(AncestorRef {
+                    id: id.to_string(),
+                    name: name.to_string(),
+                    distance: NonZero::new(steps_back).expect("bug: walk starts at 1"),
+                }));
             }
         }
+        Ok(None)
     }
 
-    /// Returns a git tag if available, otherwise the current branch if available.
-    fn current_ref_name(&self, dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
-        if !dirty_ok && !self.is_clean()? {
-            return Err(ERROR_REPO_NOT_CLEAN.to_string().into());
-        }
-        let output = DriverError::expect_cmd_lossy(
-            "git cli :exec".to_string(),
-            self.git_current_ref_name().output(),
+    /// Returns children of HEAD that are reachable from any ref (git has no way to find commits
+    /// that are otherwise unreferenced).
+    fn child_ref_ids(&self, max: Option<NonZero<u64>>) -> Result<Vec<HistoryRefId>, DriverError> {
+        let head = match self.current_ref_id(true /*dirty_ok*/) {
+            Ok(head) => head,
+            Err(DriverError::EmptyHistory) => return Ok(vec![]),
+            Err(err) => return Err(err),
+        };
+        let lines = DriverError::expect_cmd_lines(
+            self.git_all_children().output(),
+            0, /*min_lines*/
+            "git cli: exec",
+            None,
         )?;
-        let line = DriverError::expect_cmd_line("git cli: exec", &output)?;
-        let tag = line
-            .split(GIT_LOG_SCRAPABLE_PRETTY_DECOR_SEP)
-            .filter(|item| item.starts_with(GIT_LOG_SCRAPABLE_PRETTY_DECOR_TAG))
-            .map(|tag| {
-                // strip our custom prefix
-                tag.chars()
-                    .skip(GIT_LOG_SCRAPABLE_PRETTY_DECOR_TAG.len())
-                    .collect::<String>()
+        let max = max.map_or(usize::MAX, |max| {
+            usize::try_from(max.get()).unwrap_or(usize::MAX)
+        });
+        Ok(lines
+            .iter()
+            .map(|line| line.split_whitespace())
+            .find_map(|mut ids| (ids.next() == Some(head.as_str())).then_some(ids))
+            .map(|children| {
+                children
+                    .take(max)
+                    .map(std::string::ToString::to_string)
+                    .collect()
             })
-            .collect::<Vec<String>>()
-            .pop();
-        if tag.is_some() {
-            return Ok(tag);
+            .unwrap_or_default())
+    }
+
+    /// Compares against the current branch's upstream, if it has one configured.
+    fn ahead_behind(&self) -> Result<Option<AheadBehind>, DriverError> {
+        Ok(self.branch_status()?.ahead_behind)
+    }
+
+    fn operation(&self) -> Result<Option<RepoOperation>, DriverError> {
+        let output =
+            DriverError::expect_cmd_lossy("git cli: exec".to_string(), self.git_dir().output())?;
+        let git_dir = PathBuf::from(DriverError::expect_cmd_line("git cli: exec", &output)?);
+        Ok(GIT_OPERATION_MARKERS
+            .iter()
+            .find(|(marker, _)| git_dir.join(marker).exists())
+            .map(|(_, operation)| *operation))
+    }
+
+    /// Answered by the same `git status` run as `snapshot` (which also says if the repo's dirty).
+    fn current_ref_id(&self, dirty_ok: bool) -> Result<HistoryRefId, DriverError> {
+        let BranchStatus { oid, files, .. } = self.branch_status()?;
+        if !dirty_ok && !files.is_empty() {
+            return Err(DriverError::RepoDirty);
         }
+        oid.ok_or(DriverError::EmptyHistory)
+    }
 
-        let output = DriverError::expect_cmd_lossy(
-            "git cli :exec".to_string(),
-            self.git_current_branch().output(),
-        )?;
-        let branch_line = DriverError::expect_cmd_line("git cli: exec", &output)?;
-        if branch_line.is_empty() {
-            Ok(None)
-        } else {
-            Ok(Some(branch_line))
+    /// Returns a git tag if available, otherwise the current branch if available.
+    fn current_ref_name(&self, dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
+        let BranchStatus {
+            oid, head, files, ..
+        } = self.branch_status()?;
+        if !dirty_ok && !files.is_empty() {
+            return Err(DriverError::RepoDirty);
         }
+        self.name_of(oid.as_ref(), head)
+    }
+}
+
+/// Translates one of `git status --porcelain`'s "XY" codes into a [`FileStatusKind`].
+fn status_kind(code: char) -> Option<FileStatusKind> {
+    match code {
+        'M' | 'T' => Some(FileStatusKind::Modified),
+        'A' => Some(FileStatusKind::Added),
+        'D' => Some(FileStatusKind::Deleted),
+        'R' => Some(FileStatusKind::Renamed),
+        'C' => Some(FileStatusKind::Copied),
+        '?' => Some(FileStatusKind::Untracked),
+        _ => None,
+    }
+}
+
+/// Parses the value of a `GIT_STATUS_AHEAD_BEHIND_HEADER` line, eg: `+1 -2`.
+fn parse_ahead_behind(counts: &str) -> Result<AheadBehind, DriverError> {
+    let parse_count = |count: Option<&str>, sign: char| {
+        count
+            .and_then(|c| c.strip_prefix(sign))
+            .and_then(|c| c.parse::<u64>().ok())
+            .ok_or_else(|| DriverError::UnexpectedOutput {
+                cmd: "git cli: exec".to_string(),
+                stdout: counts.to_string(),
+            })
+    };
+    let mut counts_iter = counts.split_whitespace();
+    Ok(AheadBehind {
+        ahead: parse_count(counts_iter.next(), '+')?,
+        behind: parse_count(counts_iter.next(), '-')?,
+    })
+}
+
+/// Parses one file's line of `git status --porcelain=v2` output, eg: `1 XY ... path`, or for
+/// renames and copies `2 XY ... score path<tab>original` (where each "..." is a fixed number of
+/// space-separated fields we don't need).
+fn parse_porcelain_line(line: &str) -> Vec<FileStatus> {
+    let mut fields = line.splitn(2, ' ');
+    let (Some(kind), Some(rest)) = (fields.next(), fields.next()) else {
+        return vec![];
+    };
+    let (codes, paths) = match kind {
+        "1" => (rest.get(..2), rest.splitn(8, ' ').nth(7)),
+        "2" => (rest.get(..2), rest.splitn(9, ' ').nth(8)),
+        "u" => (rest.get(..2), rest.splitn(10, ' ').nth(9)),
+        "?" => (Some("??"), Some(rest)),
+        _ => (None, None),
+    };
+    let (Some(codes), Some(paths)) = (codes, paths) else {
+        return vec![];
+    };
+    let (path, original_path) = match paths.split_once(GIT_STATUS_RENAME_SEP) {
+        Some((path, original)) => (PathBuf::from(path), Some(PathBuf::from(original))),
+        None => (PathBuf::from(paths), None),
+    };
+
+    if GIT_STATUS_CONFLICTS.contains(&codes) {
+        return vec![FileStatus {
+            kind: FileStatusKind::Conflicted,
+            path,
+            original_path: None,
+            staged: Some(false),
+        }];
+    }
+
+    let mut codes = codes.chars();
+    let (staged, unstaged) = (codes.next(), codes.next());
+    let mut statuses = vec![];
+    if let Some(kind) = staged.and_then(status_kind) {
+        statuses.push(FileStatus {
+            kind,
+            path: path.clone(),
+            original_path: original_path.clone(),
+            // Untracked files are the only case where X is not about the index.
+            staged: Some(kind != FileStatusKind::Untracked),
+        });
+    }
+    // Untracked files repeat their code ("??"), but are only one change.
+    if let Some(kind) = unstaged
+        .filter(|_| staged != Some('?'))
+        .and_then(status_kind)
+    {
+        statuses.push(FileStatus {
+            kind,
+            path,
+            original_path: None,
+            staged: Some(false),
+        });
     }
+    statuses
 }
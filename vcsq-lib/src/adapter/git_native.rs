@@ -0,0 +1,346 @@
+//! Answers the most common questions (root, status, tracked files, current ID and name) by reading
+//! the repo with [gitoxide](https://github.com/GitoxideLabs/gitoxide) rather than running `git`,
+//! and defers everything else to [`git::Repo`].
+//!
+//! Answers are meant to be identical to [`git::Repo`]'s, so either can back `VcsBrand::Git`.
+use crate::adapter::git;
+use crate::plexer::VcsBrand;
+use crate::repo::{
+    AheadBehind, AncestorRef, Driver, DriverError, FileStatus, FileStatusKind, HistoryRefId,
+    HistoryRefName, QueryDir, RepoOperation, Validator, VcsAvailable,
+};
+use gix::bstr::BStr;
+use gix::dir::entry::{Kind as DirEntryKind, Status as DirEntryStatus};
+use gix::status::index_worktree::{Item as WorktreeItem, RewriteSource};
+use gix::status::plumbing::index_as_worktree::{Change as WorktreeChange, EntryStatus};
+use std::num::NonZero;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub struct Repo {
+    gix: gix::Repository,
+
+    /// Directory the repo was loaded from, which (like `git` run from it) `tracked_files` is
+    /// relative to.
+    dir: QueryDir,
+
+    /// Answers everything gitoxide isn't used for.
+    cli: git::Repo,
+}
+
+#[derive(Debug)]
+pub struct Loader
+where
+    Self: Sized;
+
+impl Validator for Loader {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Git
+    }
+
+    /// Whether `dir` is in a git repo's working tree (bare repos don't count, just as they don't
+    /// for `git rev-parse --show-toplevel`).
+    fn new_driver(&self, dir: QueryDir) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        let Ok(repo) = gix::discover(&dir) else {
+            return Ok(None);
+        };
+        if repo.workdir().is_none() {
+            return Ok(None);
+        }
+        let repo: Box<dyn Driver> = Box::from(Repo {
+            gix: repo,
+            dir: dir.clone(),
+            cli: git::Repo { dir },
+        });
+        Ok(Some(repo))
+    }
+
+    /// Checks the `git` CLI, as it's still needed for every question gitoxide isn't used for.
+    fn check_health(&self) -> Result<VcsAvailable, DriverError> {
+        git::Loader {}.check_health()
+    }
+}
+
+impl Repo {
+    /// Root of the working tree, with symlinks resolved (as `git rev-parse --show-toplevel` does).
+    fn workdir(&self) -> Result<QueryDir, DriverError> {
+        let workdir = self
+            .gix
+            .workdir()
+            .expect("bug: Loader should have rejected bare repos");
+        workdir
+            .canonicalize()
+            .map_err(|e| DriverError::Directory(format!("{}: {}", workdir.display(), e)))
+    }
+
+    /// Returns the tag pointing at `id` (the lowest-sorting one, if there are several, just as
+    /// `git tag --points-at` lists first).
+    fn tag_at(&self, id: gix::ObjectId) -> Result<Option<HistoryRefName>, DriverError> {
+        let refs = self
+            .gix
+            .references()
+            .map_err(|e| DriverError::from_library("gix: references", e))?;
+        let tags = refs
+            .tags()
+            .map_err(|e| DriverError::from_library("gix: tags", e))?;
+        let mut names = vec![];
+        for tag in tags {
+            let mut tag = tag.map_err(|e| DriverError::Library {
+                context: "gix: tag".to_string(),
+                source: e,
+            })?;
+            let peeled = tag
+                .peel_to_id()
+                .map_err(|e| DriverError::from_library("gix: peel tag", e))?;
+            if peeled.detach() == id {
+                names.push(tag.name().shorten().to_string());
+            }
+        }
+        Ok(names.into_iter().min())
+    }
+
+    /// Commit HEAD points at, or `None` if there's no history yet.
+    fn head_id(&self) -> Result<Option<gix::ObjectId>, DriverError> {
+        let head = self
+            .gix
+            .head()
+            .map_err(|e| DriverError::from_library("gix: head", e))?;
+        if head.is_unborn() {
+            return Ok(None);
+        }
+        head.into_peeled_id()
+            .map(|id| Some(id.detach()))
+            .map_err(|e| DriverError::from_library("gix: peel head", e))
+    }
+}
+
+impl Driver for Repo {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Git
+    }
+
+    fn root(&self) -> Result<QueryDir, DriverError> {
+        self.workdir()
+    }
+
+    fn status(&self) -> Result<Vec<FileStatus>, DriverError> {
+        let items = self
+            .gix
+            .status(gix::progress::Discard)
+            .map_err(|e| DriverError::from_library("gix: status", e))?
+            .into_iter(None)
+            .map_err(|e| DriverError::from_library("gix: status", e))?;
+        let mut statuses = vec![];
+        for item in items {
+            let item = item.map_err(|e| DriverError::from_library("gix: status", e))?;
+            statuses.extend(match item {
+                gix::status::Item::TreeIndex(change) => Some(tree_index_status(&change)),
+                gix::status::Item::IndexWorktree(item) => index_worktree_status(&item),
+            });
+        }
+        // A conflict is the only thing `git status` reports of a conflicted file.
+        let conflicts: Vec<QueryDir> = statuses
+            .iter()
+            .filter(|status| status.kind == FileStatusKind::Conflicted)
+            .map(|status| status.path.clone())
+            .collect();
+        statuses.retain(|status| {
+            status.kind == FileStatusKind::Conflicted || !conflicts.contains(&status.path)
+        });
+        // `git status` lists untracked files last, and otherwise sorts by path (with a file's
+        // staged change before its unstaged one).
+        statuses.sort_by_key(|status| {
+            (
+                status.kind == FileStatusKind::Untracked,
+                status.path.clone(),
+                status.staged != Some(true),
+            )
+        });
+        Ok(statuses)
+    }
+
+    /// Lists files in the index, relative to (and only from beneath) the directory the repo was
+    /// loaded from (just as `git ls-files` does).
+    fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError> {
+        let index = self
+            .gix
+            .index_or_empty()
+            .map_err(|e| DriverError::from_library("gix: index", e))?;
+        let dir = self
+            .dir
+            .canonicalize()
+            .map_err(|e| DriverError::Directory(format!("{}: {}", self.dir.display(), e)))?;
+        let prefix = dir
+            .strip_prefix(self.workdir()?)
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+        let mut files: Vec<QueryDir> = vec![];
+        for entry in index.entries() {
+            let path = to_path(entry.path(&index));
+            let Ok(path) = path.strip_prefix(&prefix) else {
+                continue;
+            };
+            // Conflicted files have an entry per side of the conflict, but are listed once.
+            if files.last().is_some_and(|last| last == path) {
+                continue;
+            }
+            files.push(path.to_path_buf());
+        }
+        Ok(files)
+    }
+
+    fn changed_files(
+        &self,
+        base: Option<&str>,
+        head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError> {
+        self.cli.changed_files(base, head)
+    }
+
+    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        self.cli.parent_ref_id()
+    }
+
+    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError> {
+        self.cli.parent_ref_name()
+    }
+
+    fn first_ancestor_ref_name(
+        &self,
+        limit: Option<NonZero<u64>>,
+    ) -> Result<Option<AncestorRef>, DriverError> {
+        self.cli.first_ancestor_ref_name(limit)
+    }
+
+    fn child_ref_ids(&self, max: Option<NonZero<u64>>) -> Result<Vec<HistoryRefId>, DriverError> {
+        self.cli.child_ref_ids(max)
+    }
+
+    fn ahead_behind(&self) -> Result<Option<AheadBehind>, DriverError> {
+        self.cli.ahead_behind()
+    }
+
+    fn operation(&self) -> Result<Option<RepoOperation>, DriverError> {
+        self.cli.operation()
+    }
+
+    fn current_ref_id(&self, dirty_ok: bool) -> Result<HistoryRefId, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        self.head_id()?
+            .map(|id| id.to_string())
+            .ok_or(DriverError::EmptyHistory)
+    }
+
+    /// Returns a git tag if available, otherwise the current branch if available.
+    fn current_ref_name(&self, dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        if let Some(id) = self.head_id()? {
+            let tag = self.tag_at(id)?;
+            if tag.is_some() {
+                return Ok(tag);
+            }
+        }
+        let branch = self
+            .gix
+            .head_name()
+            .map_err(|e| DriverError::from_library("gix: head name", e))?;
+        Ok(branch.map(|name| name.shorten().to_string()))
+    }
+}
+
+fn to_path(path: &BStr) -> PathBuf {
+    gix::path::from_bstr(path).into_owned()
+}
+
+/// Translates a change between HEAD and the index (ie: a staged change).
+fn tree_index_status(change: &gix::diff::index::Change) -> FileStatus {
+    use gix::diff::index::ChangeRef;
+    let (kind, original_path) = match change {
+        ChangeRef::Addition { .. } => (FileStatusKind::Added, None),
+        ChangeRef::Deletion { .. } => (FileStatusKind::Deleted, None),
+        ChangeRef::Modification { .. } => (FileStatusKind::Modified, None),
+        ChangeRef::Rewrite {
+            source_location,
+            copy,
+            ..
+        } => (
+            if *copy {
+                FileStatusKind::Copied
+            } else {
+                FileStatusKind::Renamed
+            },
+            Some(to_path(source_location)),
+        ),
+    };
+    FileStatus {
+        kind,
+        path: to_path(change.location()),
+        original_path,
+        staged: Some(true),
+    }
+}
+
+/// Translates a change between the index and the working tree (ie: an unstaged change).
+fn index_worktree_status(item: &WorktreeItem) -> Option<FileStatus> {
+    let (kind, path, original_path) = match item {
+        WorktreeItem::Modification {
+            rela_path, status, ..
+        } => {
+            let kind = match status {
+                EntryStatus::Conflict { .. } => FileStatusKind::Conflicted,
+                EntryStatus::Change(WorktreeChange::Removed) => FileStatusKind::Deleted,
+                EntryStatus::Change(_) => FileStatusKind::Modified,
+                EntryStatus::IntentToAdd => FileStatusKind::Added,
+                EntryStatus::NeedsUpdate(_) => return None,
+            };
+            (kind, to_path(rela_path.as_ref()), None)
+        }
+        WorktreeItem::DirectoryContents { entry, .. } => {
+            if entry.status != DirEntryStatus::Untracked {
+                return None;
+            }
+            let mut path = to_path(entry.rela_path.as_ref());
+            // `git status` marks wholly-untracked directories with a trailing slash.
+            if entry.disk_kind == Some(DirEntryKind::Directory) {
+                path.push("");
+            }
+            (FileStatusKind::Untracked, path, None)
+        }
+        WorktreeItem::Rewrite {
+            source,
+            dirwalk_entry,
+            copy,
+            ..
+        } => {
+            let original = match source {
+                RewriteSource::RewriteFromIndex {
+                    source_rela_path, ..
+                } => to_path(source_rela_path.as_ref()),
+                RewriteSource::CopyFromDirectoryEntry {
+                    source_dirwalk_entry,
+                    ..
+                } => to_path(source_dirwalk_entry.rela_path.as_ref()),
+            };
+            let kind = if *copy {
+                FileStatusKind::Copied
+            } else {
+                FileStatusKind::Renamed
+            };
+            (
+                kind,
+                to_path(dirwalk_entry.rela_path.as_ref()),
+                Some(original),
+            )
+        }
+    };
+    Some(FileStatus {
+        kind,
+        path,
+        original_path,
+        staged: Some(false),
+    })
+}
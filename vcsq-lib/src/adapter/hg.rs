@@ -1,19 +1,65 @@
+use crate::plexer::VcsBrand;
 use crate::repo::{
-    Driver, DriverError, HistoryRefId, HistoryRefName, QueryDir, Validator, VcsAvailable,
-    ERROR_REPO_NOT_CLEAN, ERROR_REPO_NOT_DIRTY,
+    may_be_in_repo, AheadBehind, AncestorRef, Driver, DriverError, FileStatus, FileStatusKind,
+    HistoryRefId, HistoryRefName, QueryDir, RepoOperation, RepoSnapshot, SnapshotId, Validator,
+    VcsAvailable,
 };
+use std::num::NonZero;
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
 
 #[derive(Debug)]
 pub struct Repo {
-    dir: QueryDir,
+    pub(crate) dir: QueryDir,
 }
 
 static VCS_BIN_NAME: &str = "hg";
 
 const HG_LOGID_DIRTY_SUFFIX: &str = "+";
 
+/// Node ID of Mercurial's `null` revision, which is the working directory's parent until the
+/// first commit is made.
+pub(crate) const HG_NULL_ID: &str = "0000000000000000000000000000000000000000";
+
+/// Revset of the first parent of the working directory's parent (ie: of the "current" revision).
+/// Note `p1()` never includes the null revision, so this is an empty set at the root of history.
+const HG_REVSET_PARENT: &str = "p1(.)";
+
+/// Revset of the children of the working directory's parent (ie: of the "current" revision).
+const HG_REVSET_CHILDREN: &str = "children(.)";
+
+/// Indentation `hg status --copies` prints before the origin of a copied file.
+const HG_STATUS_COPY_ORIGIN_INDENT: &str = "  ";
+
+/// Field separator (tab) for `HG_TEMPLATE_NODE_TAGS` output.
+const HG_TEMPLATE_SEP: char = '\t';
+
+/// Lines of the form `NODE<tab>TAG<tab>TAG`.
+const HG_TEMPLATE_NODE_TAGS: &str = "{node}{tags % '\\t{tag}'}\\n";
+
+/// Template printing a revision's node ID, then each name it could go by, one per line: its own
+/// tags, its bookmarks (the active one first) and finally its branch.
+const HG_TEMPLATE_NODE_NAMES: &str =
+    "{node}\\n{tags % '{tag}\\n'}{activebookmark}\\n{bookmarks % '{bookmark}\\n'}{branch}\\n";
+
+/// Pseudo-tag Mercurial always attaches to the newest revision; not human-made.
+const HG_TAG_TIP: &str = "tip";
+
+/// Mercurial's directory of repo metadata, relative to the repo root.
+pub(crate) const HG_META_DIR: &str = ".hg";
+
+/// Files (relative to `HG_META_DIR`) Mercurial leaves behind while an operation is in progress.
+///
+/// Order matters: rebases, histedits and grafts also record a merge state while they await
+/// conflict resolution.
+const HG_OPERATION_MARKERS: [(&str, RepoOperation); 5] = [
+    ("rebasestate", RepoOperation::Rebase),
+    ("histedit-state", RepoOperation::Histedit),
+    ("graftstate", RepoOperation::Graft),
+    ("merge/state", RepoOperation::Merge),
+    ("bisect.state", RepoOperation::Bisect),
+];
+
 fn start_vcs_shellout() -> Command {
     let mut cmd = Command::new(VCS_BIN_NAME);
     cmd.env("HGPLAIN", "1");
@@ -26,7 +72,14 @@ where
     Self: Sized;
 
 impl Validator for Loader {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Mercurial
+    }
+
     fn new_driver(&self, dir: QueryDir) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        if !may_be_in_repo(&dir, HG_META_DIR) {
+            return Ok(None);
+        }
         let repo = Repo { dir };
 
         let is_ok = DriverError::unwrap_cmd_lossy(
@@ -74,7 +127,8 @@ impl Repo {
             .arg("--added")
             .arg("--removed")
             .arg("--deleted")
-            .arg("--unknown");
+            .arg("--unknown")
+            .arg("--copies");
         cmd
     }
 
@@ -84,24 +138,154 @@ impl Repo {
         cmd
     }
 
+    /// Lists files `rev` changed relative to its first parent.
+    fn hg_files_of(&self, rev: &str) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("status")
+            .arg("--no-status")
+            .arg("--change")
+            .arg(rev);
+        cmd
+    }
+
+    fn hg_files_between(&self, base: &str, head: &str) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("status")
+            .arg("--no-status")
+            .arg("--rev")
+            .arg(base)
+            .arg("--rev")
+            .arg(head);
+        cmd
+    }
+
     fn hg_current_id(&self) -> Command {
         let mut cmd = self.start_shellout();
         cmd.arg("--debug").arg("id").arg("--id");
         cmd
     }
 
-    fn hg_current_name(&self) -> Command {
+    fn hg_log_template(&self, revset: &str, template: &str) -> Command {
         let mut cmd = self.start_shellout();
         cmd.arg("log")
             .arg("--rev")
-            .arg(".")
+            .arg(revset)
             .arg("--template")
-            .arg("{latesttag}");
+            .arg(template);
         cmd
     }
+
+    fn hg_first_parent_tags(&self, limit: Option<NonZero<u64>>) -> Command {
+        let mut cmd = self.start_shellout();
+        // NOTE: --follow-first is deprecated in favor of a revset, but the only revset equivalent
+        // (`_firstancestors()`) is private, so we stick with the flag.
+        cmd.arg("log")
+            .arg("--follow-first")
+            .arg("--rev")
+            .arg(HG_REVSET_PARENT)
+            .arg("--template")
+            .arg(HG_TEMPLATE_NODE_TAGS);
+        if let Some(limit) = limit {
+            cmd.arg("--limit").arg(limit.to_string());
+        }
+        cmd
+    }
+
+    fn hg_children(&self, max: Option<NonZero<u64>>) -> Command {
+        let mut cmd = self.hg_log_template(HG_REVSET_CHILDREN, "{node}\\n");
+        if let Some(max) = max {
+            cmd.arg("--limit").arg(max.to_string());
+        }
+        cmd
+    }
+
+    /// Returns the node ID of `revset`, and the name it goes by: its own tag if it has one, else
+    /// its bookmark, else its branch (and never one for the null revision, ie: no history yet).
+    fn node_and_name(
+        &self,
+        revset: &str,
+    ) -> Result<(HistoryRefId, Option<HistoryRefName>), DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.hg_log_template(revset, HG_TEMPLATE_NODE_NAMES)
+                .output(),
+            1, /*min_lines*/
+            "hg cli: exec",
+            None,
+        )?;
+        let mut lines = lines.into_iter();
+        let node = lines
+            .next()
+            .expect("bug: expect_cmd_lines should have enforced min_lines");
+        if node == HG_NULL_ID {
+            return Ok((node, None));
+        }
+        let name = lines.find(|name| !name.is_empty() && name != HG_TAG_TIP);
+        Ok((node, name))
+    }
+
+    /// Returns the full node ID of `revset`, or `empty_err` if the revset resolved to nothing.
+    fn node_of(&self, revset: &str, empty_err: DriverError) -> Result<HistoryRefId, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.hg_log_template(revset, "{node}\\n").output(),
+            1, /*min_lines*/
+            "hg cli: exec",
+            Some(empty_err),
+        )?;
+        Ok(lines
+            .into_iter()
+            .next()
+            .expect("bug: expect_cmd_lines should have enforced min_lines"))
+    }
+}
+
+/// Turns each copy whose origin was also removed (ie: really a rename) into a single
+/// [`FileStatusKind::Renamed`], as `hg status` reports renames as a copy and a removal.
+pub(crate) fn fold_renames(statuses: &mut Vec<FileStatus>) {
+    let renamed_from = statuses
+        .iter()
+        .filter(|status| status.kind == FileStatusKind::Copied)
+        .filter_map(|status| status.original_path.clone())
+        .filter(|origin| {
+            statuses
+                .iter()
+                .any(|status| status.kind == FileStatusKind::Deleted && status.path == *origin)
+        })
+        .collect::<Vec<QueryDir>>();
+    statuses.retain(|status| {
+        !(status.kind == FileStatusKind::Deleted && renamed_from.contains(&status.path))
+    });
+    for status in statuses.iter_mut() {
+        if status
+            .original_path
+            .as_ref()
+            .is_some_and(|origin| renamed_from.contains(origin))
+        {
+            status.kind = FileStatusKind::Renamed;
+        }
+    }
 }
 
 impl Driver for Repo {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Mercurial
+    }
+
+    /// Answers the current revision's ID and name with a single `hg log`.
+    fn snapshot(&self) -> Result<RepoSnapshot, DriverError> {
+        let (node, name) = self.node_and_name(".")?;
+        Ok(RepoSnapshot {
+            brand: self.brand(),
+            root: self.root()?,
+            id: if node == HG_NULL_ID {
+                SnapshotId::EmptyHistory
+            } else {
+                SnapshotId::Id(node)
+            },
+            name,
+            status: self.status()?,
+        })
+    }
+
     fn root(&self) -> Result<QueryDir, DriverError> {
         let output =
             DriverError::expect_cmd_lossy("hg cli: exec".to_string(), self.hg_root().output())?;
@@ -110,24 +294,42 @@ impl Driver for Repo {
         )?))
     }
 
-    fn dirty_files(&self, clean_ok: bool) -> Result<Vec<QueryDir>, DriverError> {
-        let min_lines = u8::from(!clean_ok);
+    fn status(&self) -> Result<Vec<FileStatus>, DriverError> {
         let lines = DriverError::expect_cmd_lines(
             self.hg_dirty_files().output(),
-            min_lines,
+            0, /*min_lines*/
             "hg cli: exec",
-            Some(ERROR_REPO_NOT_DIRTY.to_string()),
+            None,
         )?;
-        let dirty_files = lines
-            .into_iter()
-            .map(|ln| {
-                // first 2 chars are modification-indicators like "?? " to indicate the file is
-                // untracked.
-                ln.chars().skip(2).collect::<String>()
-            })
-            .map(PathBuf::from)
-            .collect();
-        Ok(dirty_files)
+        let mut statuses: Vec<FileStatus> = vec![];
+        for ln in lines {
+            // With --copies, the origin of a copy is printed on the line after it, indented.
+            if let Some(origin) = ln.strip_prefix(HG_STATUS_COPY_ORIGIN_INDENT) {
+                if let Some(copy) = statuses.last_mut() {
+                    copy.kind = FileStatusKind::Copied;
+                    copy.original_path = Some(PathBuf::from(origin));
+                }
+                continue;
+            }
+            // first 2 chars are modification-indicators like "? " to indicate the file is
+            // untracked.
+            let kind = match ln.chars().next() {
+                Some('M') => FileStatusKind::Modified,
+                Some('A') => FileStatusKind::Added,
+                Some('R' | '!') => FileStatusKind::Deleted,
+                Some('?') => FileStatusKind::Untracked,
+                _ => continue,
+            };
+            statuses.push(FileStatus {
+                kind,
+                path: PathBuf::from(ln.chars().skip(2).collect::<String>()),
+                original_path: None,
+                staged: None,
+            });
+        }
+
+        fold_renames(&mut statuses);
+        Ok(statuses)
     }
 
     fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError> {
@@ -148,42 +350,114 @@ impl Driver for Repo {
         Ok(files)
     }
 
+    fn changed_files(
+        &self,
+        base: Option<&str>,
+        head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError> {
+        let head = head.unwrap_or(".");
+        let mut cmd = match base {
+            Some(base) => self.hg_files_between(base, head),
+            None => self.hg_files_of(head),
+        };
+        let lines = DriverError::expect_cmd_lines(
+            cmd.output(),
+            0, /*min_lines*/
+            "hg cli: exec",
+            None,
+        )?;
+        Ok(lines.into_iter().map(PathBuf::from).collect())
+    }
+
+    /// Returns the first parent (`p1`) of the working directory's parent revision.
+    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        self.node_of(HG_REVSET_PARENT, DriverError::NoParent)
+    }
+
+    /// Returns the first parent's own tag if it has one, else its bookmark, else its branch.
+    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError> {
+        // Resolving the ID first ensures we error the same as `parent_ref_id` at the root of
+        // history, rather than reporting a name for an empty revset.
+        let parent_id = self.parent_ref_id()?;
+        Ok(self.node_and_name(&parent_id)?.1)
+    }
+
+    /// Returns the nearest Mercurial tag on the first-parent history of the working directory's
+    /// parent revision.
+    fn first_ancestor_ref_name(
+        &self,
+        limit: Option<NonZero<u64>>,
+    ) -> Result<Option<AncestorRef>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.hg_first_parent_tags(limit).output(),
+            0, /*min_lines*/
+            "hg cli: exec",
+            None,
+        )?;
+        for (steps_back, line) in (1..).zip(lines) {
+            let mut fields = line.split(HG_TEMPLATE_SEP);
+            let Some(node) = fields.next() else {
+                continue;
+            };
+            if let Some(name) = fields.find(|tag| *tag != HG_TAG_TIP) {
+                return Ok(Some(AncestorRef {
+                    id: node.to_string(),
+                    name: name.to_string(),
+                    distance: NonZero::new(steps_back).expect("bug: walk starts at 1"),
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns children of the working directory's parent revision.
+    fn child_ref_ids(&self, max: Option<NonZero<u64>>) -> Result<Vec<HistoryRefId>, DriverError> {
+        DriverError::expect_cmd_lines(
+            self.hg_children(max).output(),
+            0, /*min_lines*/
+            "hg cli: exec",
+            None,
+        )
+    }
+
+    /// Always `None`: Mercurial doesn't record an upstream's state locally (comparing against one
+    /// takes network access, eg: `hg incoming`).
+    fn ahead_behind(&self) -> Result<Option<AheadBehind>, DriverError> {
+        Ok(None)
+    }
+
+    fn operation(&self) -> Result<Option<RepoOperation>, DriverError> {
+        let meta_dir = self.root()?.join(HG_META_DIR);
+        Ok(HG_OPERATION_MARKERS
+            .iter()
+            .find(|(marker, _)| meta_dir.join(marker).exists())
+            .map(|(_, operation)| *operation))
+    }
+
     fn current_ref_id(&self, dirty_ok: bool) -> Result<HistoryRefId, DriverError> {
         if !dirty_ok && !self.is_clean()? {
-            return Err(ERROR_REPO_NOT_CLEAN.to_string().into());
+            return Err(DriverError::RepoDirty);
         }
         let output = DriverError::expect_cmd_lossy(
             "hg cli: exec".to_string(),
             self.hg_current_id().output(),
         )?;
         let current_id = DriverError::expect_cmd_line("hg cli: exec", &output)?;
-        if !current_id.ends_with(HG_LOGID_DIRTY_SUFFIX) {
-            return Ok(current_id);
+        let current_id = match current_id.strip_suffix(HG_LOGID_DIRTY_SUFFIX) {
+            Some(clean_id) => clean_id.to_string(),
+            None => current_id,
+        };
+        if current_id == HG_NULL_ID {
+            return Err(DriverError::EmptyHistory);
         }
-        Ok(current_id
-            .strip_suffix(HG_LOGID_DIRTY_SUFFIX)
-            .ok_or_else(|| {
-                DriverError::Unknown(format!("hg bug? got just a lone '{HG_LOGID_DIRTY_SUFFIX}'"))
-            })?
-            .to_string())
+        Ok(current_id)
     }
 
-    /// Returns the current Mercurial tag if available.
+    /// Returns the current revision's own tag if it has one, else its bookmark, else its branch.
     fn current_ref_name(&self, dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
         if !dirty_ok && !self.is_clean()? {
-            return Err(ERROR_REPO_NOT_CLEAN.to_string().into());
-        }
-
-        let output = DriverError::expect_cmd_lossy(
-            "hg cli: exec".to_string(),
-            self.hg_current_name().output(),
-        )?;
-        let hg_tag = DriverError::expect_cmd_line("hg cli: exec", &output)?;
-
-        if hg_tag.is_empty() || hg_tag == "null" {
-            Ok(None)
-        } else {
-            Ok(Some(hg_tag))
+            return Err(DriverError::RepoDirty);
         }
+        Ok(self.node_and_name(".")?.1)
     }
 }
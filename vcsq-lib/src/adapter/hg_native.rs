@@ -0,0 +1,1014 @@
+//! Answers the most common questions (root, status, tracked files, current ID and name) by reading
+//! Mercurial's files directly rather than running `hg` (which pays Python's startup cost on every
+//! call), and defers everything else to [`hg::Repo`].
+//!
+//! Files read are:
+//! - `.hg/dirstate` (v1, or v2's docket and data file): the working directory's parents, and what
+//!   `hg` last saw of each tracked file.
+//! - `.hg/store/00changelog.i`: the changelog's revlog index, for the parents of each revision.
+//! - `.hg/cache/tags2-visible`: `hg`'s cache of global tags (ie: of `.hgtags` across heads).
+//! - `.hg/localtags`, `.hg/bookmarks` and `.hg/bookmarks.current`: local tags and bookmarks.
+//! - `.hgignore`, at the root of the repo.
+//!
+//! Whenever those can't settle an answer alone, the question is passed on to [`hg::Repo`]
+//! instead, eg: when a file's size and mode match what `hg` last saw but its modification time
+//! doesn't (so only comparing contents would tell), when the tags cache is stale, when the current
+//! revision is only named by its branch (which is recorded in the changelog's compressed
+//! entries, not its index), or when the repo
+//! uses a format or extension not handled here. Note ignore files configured outside the repo
+//! (ie: `ui.ignore` in a user's hgrc) aren't read.
+use crate::adapter::hg;
+use crate::plexer::VcsBrand;
+use crate::repo::{
+    AheadBehind, AncestorRef, Driver, DriverError, FileStatus, FileStatusKind, HistoryRefId,
+    HistoryRefName, QueryDir, RepoOperation, Validator, VcsAvailable,
+};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::num::NonZero;
+use std::path::{Path, PathBuf};
+
+/// File (in `hg::HG_META_DIR`) holding the dirstate, or for dirstate-v2 the "docket" naming the
+/// file holding it.
+const HG_DIRSTATE: &str = "dirstate";
+
+/// Marker a dirstate-v2 docket starts with (v1 dirstates start with the first parent instead).
+const HG_DIRSTATE_V2_MARKER: &[u8] = b"dirstate-v2\n";
+
+/// Size of a dirstate-v2 docket's "tree metadata" field.
+const HG_DIRSTATE_V2_TREE_METADATA_LEN: usize = 44;
+
+/// Size of each node of a dirstate-v2 data file.
+const HG_DIRSTATE_V2_NODE_LEN: usize = 44;
+
+/// Flags of a dirstate-v2 node (see Mercurial's `internals.dirstate-v2` help).
+const HG_DIRSTATE_V2_WDIR_TRACKED: u16 = 1 << 0;
+const HG_DIRSTATE_V2_P1_TRACKED: u16 = 1 << 1;
+const HG_DIRSTATE_V2_P2_INFO: u16 = 1 << 2;
+const HG_DIRSTATE_V2_MODE_EXEC_PERM: u16 = 1 << 3;
+const HG_DIRSTATE_V2_MODE_IS_SYMLINK: u16 = 1 << 4;
+const HG_DIRSTATE_V2_EXPECTED_STATE_IS_MODIFIED: u16 = 1 << 9;
+const HG_DIRSTATE_V2_HAS_MODE_AND_SIZE: u16 = 1 << 10;
+const HG_DIRSTATE_V2_HAS_MTIME: u16 = 1 << 11;
+const HG_DIRSTATE_V2_MTIME_SECOND_AMBIGUOUS: u16 = 1 << 12;
+
+/// Size of the fields preceding each file name in a v1 dirstate (state, mode, size, mtime and the
+/// name's length).
+const HG_DIRSTATE_V1_ENTRY_LEN: usize = 17;
+
+/// Sizes and mtimes are recorded modulo 2^31.
+const HG_RANGE_MASK: i64 = 0x7fff_ffff;
+
+/// v1 dirstate size meaning `hg` has to compare contents to know if the file changed.
+const HG_DIRSTATE_V1_SIZE_NONNORMAL: i32 = -1;
+
+/// v1 dirstate size meaning the file comes from the second parent of a merge.
+const HG_DIRSTATE_V1_SIZE_FROM_P2: i32 = -2;
+
+/// v1 dirstate mtime meaning the recorded mtime can't be trusted.
+const HG_DIRSTATE_V1_MTIME_UNSET: i32 = -1;
+
+/// Separates a file's name from the origin it was copied from, in a v1 dirstate.
+const HG_DIRSTATE_V1_COPY_SEP: u8 = 0;
+
+/// File (in `hg::HG_META_DIR`) naming the repo this one shares its store with, if any.
+const HG_SHAREDPATH: &str = "sharedpath";
+
+/// File (in `hg::HG_META_DIR`) listing features the repo needs its readers to understand.
+const HG_REQUIRES: &str = "requires";
+
+/// Requirements changing what status reports, which only `hg` itself handles.
+const HG_REQUIRES_UNHANDLED: [&str; 3] = ["largefiles", "exp-sparse", "narrowhg-experimental"];
+
+/// The changelog's revlog index, relative to `hg::HG_META_DIR`.
+const HG_CHANGELOG_INDEX: &str = "store/00changelog.i";
+
+/// Revlog format version this reads.
+const HG_REVLOG_V1: u32 = 1;
+
+/// Revlog header flag for revision data being interleaved with the index.
+const HG_REVLOG_FLAG_INLINE: u32 = 1 << 16;
+
+/// Size of each entry of a v1 revlog index.
+const HG_REVLOG_V1_ENTRY_LEN: usize = 64;
+
+/// Revision number revlogs use for "no such revision" (eg: the missing parent of a root).
+const HG_NULL_REV: i32 = -1;
+
+/// Size of a node ID.
+const HG_NODE_LEN: usize = 20;
+
+/// `hg`'s cache of global tags, relative to `hg::HG_META_DIR`.
+const HG_TAGS_CACHE: &str = "cache/tags2-visible";
+
+/// Local (ie: unversioned) tags, relative to `hg::HG_META_DIR`.
+const HG_LOCAL_TAGS: &str = "localtags";
+
+/// Bookmarks, relative to `hg::HG_META_DIR`.
+const HG_BOOKMARKS: &str = "bookmarks";
+
+/// Name of the active bookmark (if any), relative to `hg::HG_META_DIR`.
+const HG_BOOKMARK_ACTIVE: &str = "bookmarks.current";
+
+/// Ignore file at the root of the repo.
+const HG_IGNORE_FILE: &str = ".hgignore";
+
+/// Line of an ignore file setting the syntax of the lines after it.
+const HG_IGNORE_SYNTAX_PREFIX: &str = "syntax:";
+
+#[derive(Debug)]
+pub struct Repo {
+    /// Root of the repo (ie: the directory holding `hg::HG_META_DIR`).
+    root: QueryDir,
+
+    /// Answers everything these files can't.
+    cli: hg::Repo,
+}
+
+#[derive(Debug)]
+pub struct Loader
+where
+    Self: Sized;
+
+impl Validator for Loader {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Mercurial
+    }
+
+    /// Looks for Mercurial's metadata in `dir` or any of its ancestors, so this never needs to
+    /// shell out.
+    fn new_driver(&self, dir: QueryDir) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        let canonical = dir
+            .canonicalize()
+            .map_err(|e| DriverError::Directory(format!("{}: {}", dir.display(), e)))?;
+        let root = canonical
+            .ancestors()
+            .find(|ancestor| ancestor.join(hg::HG_META_DIR).is_dir())
+            .map(Path::to_path_buf);
+        Ok(root.map(|root| {
+            let repo: Box<dyn Driver> = Box::from(Repo {
+                root,
+                cli: hg::Repo { dir },
+            });
+            repo
+        }))
+    }
+
+    /// Checks the `hg` CLI, as it's still needed for every question that can't be answered
+    /// without it.
+    fn check_health(&self) -> Result<VcsAvailable, DriverError> {
+        hg::Loader {}.check_health()
+    }
+}
+
+/// Node ID of a revision.
+type Node = [u8; HG_NODE_LEN];
+
+/// What the dirstate records of a tracked file.
+#[derive(Debug)]
+enum Recorded {
+    Added,
+    Removed,
+
+    /// Merged in from the second parent, so differing from the first.
+    Merged,
+
+    /// Tracked since the first parent, and unmodified as of `hg` last seeing it as recorded. Any
+    /// field `hg` didn't (or couldn't reliably) record is `None`.
+    Normal {
+        size: Option<i64>,
+        exec: bool,
+        symlink: bool,
+        mtime: Option<(i64, u32)>,
+        known_modified: bool,
+    },
+}
+
+#[derive(Debug)]
+struct DirstateEntry {
+    /// Path relative to the repo root, `/`-separated.
+    path: String,
+    recorded: Recorded,
+    copy_source: Option<String>,
+}
+
+#[derive(Debug)]
+struct Dirstate {
+    p1: Node,
+    p2: Node,
+    entries: Vec<DirstateEntry>,
+}
+
+/// Where a tracked file is at, once compared with what's on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum FileState {
+    // Ordered as `hg status` groups its output.
+    Modified,
+    Added,
+    Removed,
+    Missing,
+    Clean,
+}
+
+#[derive(Debug)]
+enum WorkingParent {
+    /// There's no history yet.
+    Null,
+    Node(Node),
+}
+
+/// Parents of each revision, as read from the changelog's index.
+#[derive(Debug)]
+struct Changelog {
+    nodes: Vec<Node>,
+    parents: Vec<(i32, i32)>,
+}
+
+impl Repo {
+    fn meta_dir(&self) -> PathBuf {
+        self.root.join(hg::HG_META_DIR)
+    }
+
+    fn read_meta(&self, name: &str) -> Result<Option<Vec<u8>>, DriverError> {
+        let path = self.meta_dir().join(name);
+        match fs::read(&path) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(DriverError::Directory(format!("{}: {}", path.display(), e))),
+        }
+    }
+
+    /// Whether the repo needs features only `hg` itself handles (or uses a store elsewhere).
+    fn is_unhandled(&self) -> Result<bool, DriverError> {
+        if self.meta_dir().join(HG_SHAREDPATH).exists() {
+            return Ok(true);
+        }
+        let requires = self.read_meta(HG_REQUIRES)?.unwrap_or_default();
+        Ok(String::from_utf8_lossy(&requires)
+            .lines()
+            .any(|requirement| HG_REQUIRES_UNHANDLED.contains(&requirement)))
+    }
+
+    /// Reads the dirstate, or `None` if it's in a format not handled here.
+    fn dirstate(&self) -> Result<Option<Dirstate>, DriverError> {
+        if self.is_unhandled()? {
+            return Ok(None);
+        }
+        let Some(data) = self.read_meta(HG_DIRSTATE)? else {
+            // `hg init` doesn't write one until there's something to record.
+            return Ok(Some(Dirstate {
+                p1: [0; HG_NODE_LEN],
+                p2: [0; HG_NODE_LEN],
+                entries: vec![],
+            }));
+        };
+        let Some(docket) = data.strip_prefix(HG_DIRSTATE_V2_MARKER) else {
+            return Ok(parse_dirstate_v1(&data));
+        };
+        let Some((p1, p2, tree_metadata, data_name, data_len)) = parse_dirstate_v2_docket(docket)
+        else {
+            return Ok(None);
+        };
+        let Some(data) = self.read_meta(&data_name)? else {
+            return Ok(None);
+        };
+        Ok(data
+            .get(..data_len)
+            .and_then(|data| parse_dirstate_v2(data, tree_metadata))
+            .map(|entries| Dirstate { p1, p2, entries }))
+    }
+
+    /// Reads the changelog's index, or `None` if it's in a format not handled here.
+    fn changelog(&self) -> Result<Option<Changelog>, DriverError> {
+        let data = self.read_meta(HG_CHANGELOG_INDEX)?.unwrap_or_default();
+        Ok(parse_revlog_index(&data))
+    }
+
+    /// Returns global tags by the revision they're on, or `None` if `hg`'s cache of them isn't
+    /// up-to-date with `changelog`.
+    fn global_tags(
+        &self,
+        changelog: &Changelog,
+    ) -> Result<Option<HashMap<usize, Vec<String>>>, DriverError> {
+        let Some(data) = self.read_meta(HG_TAGS_CACHE)? else {
+            return Ok(None);
+        };
+        let data = String::from_utf8_lossy(&data);
+        let mut lines = data.lines();
+
+        // The header is the tip's revision and node, then a hash of any hidden revisions (which
+        // would need obsolescence markers to account for).
+        let header: Vec<&str> = lines.next().unwrap_or_default().split(' ').collect();
+        let tip = changelog.nodes.len().checked_sub(1);
+        let expected_header = [
+            tip.map_or(HG_NULL_REV.to_string(), |rev| rev.to_string()),
+            hex(&tip.map_or([0; HG_NODE_LEN], |rev| changelog.nodes[rev])),
+        ];
+        if header != expected_header {
+            return Ok(None);
+        }
+
+        let mut tags: HashMap<usize, Vec<String>> = HashMap::new();
+        for (node, names) in names_by_node(lines) {
+            if let Some(rev) = changelog.rev_of_hex(&node) {
+                tags.entry(rev).or_default().extend(names);
+            }
+        }
+        Ok(Some(tags))
+    }
+
+    /// Reads a file of `NODE NAME` lines (eg: local tags, or bookmarks) into the names on each
+    /// node.
+    fn names_in(&self, name: &str) -> Result<HashMap<String, Vec<String>>, DriverError> {
+        let data = self.read_meta(name)?.unwrap_or_default();
+        Ok(names_by_node(String::from_utf8_lossy(&data).lines()))
+    }
+
+    /// Compares a tracked file with what's on disk, or `None` if only comparing its contents
+    /// would tell whether it changed.
+    fn file_state(&self, entry: &DirstateEntry) -> Result<Option<FileState>, DriverError> {
+        let path = self.root.join(&entry.path);
+        let meta = match fs::symlink_metadata(&path) {
+            Ok(meta) if meta.is_file() || meta.is_symlink() => Some(meta),
+            Ok(_) => None,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(DriverError::Directory(format!("{}: {}", path.display(), e))),
+        };
+        let Some(meta) = meta else {
+            return Ok(Some(match entry.recorded {
+                Recorded::Removed => FileState::Removed,
+                _ => FileState::Missing,
+            }));
+        };
+        let (size, exec, symlink, mtime, known_modified) = match entry.recorded {
+            Recorded::Added => return Ok(Some(FileState::Added)),
+            Recorded::Removed => return Ok(Some(FileState::Removed)),
+            Recorded::Merged => return Ok(Some(FileState::Modified)),
+            Recorded::Normal {
+                size,
+                exec,
+                symlink,
+                mtime,
+                known_modified,
+            } => (size, exec, symlink, mtime, known_modified),
+        };
+        if known_modified {
+            return Ok(Some(FileState::Modified));
+        }
+        let Some(size) = size else {
+            return Ok(None);
+        };
+        let Some((disk_exec, disk_mtime)) = stat(&meta) else {
+            return Ok(None);
+        };
+        let disk_size = i64::try_from(meta.len()).unwrap_or(i64::MAX) & HG_RANGE_MASK;
+        if symlink != meta.is_symlink() || size != disk_size || (!symlink && exec != disk_exec) {
+            return Ok(Some(FileState::Modified));
+        }
+        match mtime {
+            Some((secs, nanos))
+                if secs == disk_mtime.0 & HG_RANGE_MASK
+                    && (nanos == 0 || nanos == disk_mtime.1) =>
+            {
+                Ok(Some(FileState::Clean))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Compares every tracked file with what's on disk, or `None` if any needs `hg` to tell.
+    fn file_states<'a>(
+        &self,
+        dirstate: &'a Dirstate,
+    ) -> Result<Option<Vec<(FileState, &'a DirstateEntry)>>, DriverError> {
+        let mut states = vec![];
+        for entry in &dirstate.entries {
+            let Some(state) = self.file_state(entry)? else {
+                return Ok(None);
+            };
+            states.push((state, entry));
+        }
+        states.sort_by(|(a_state, a), (b_state, b)| {
+            (a_state, a.path.as_str()).cmp(&(b_state, b.path.as_str()))
+        });
+        Ok(Some(states))
+    }
+
+    /// Lists files under `dir` (relative to the root) that are neither tracked nor ignored, or
+    /// `None` if a path isn't one this can represent.
+    fn unknown_files(
+        &self,
+        dir: &Path,
+        known: &HashSet<&str>,
+        ignore: &Ignore,
+        unknown: &mut Vec<String>,
+    ) -> Result<Option<()>, DriverError> {
+        let abs_dir = self.root.join(dir);
+        let read_dir =
+            |e: io::Error| DriverError::Directory(format!("{}: {}", abs_dir.display(), e));
+        for dirent in fs::read_dir(&abs_dir).map_err(read_dir)? {
+            let dirent = dirent.map_err(read_dir)?;
+            let path = dir.join(dirent.file_name());
+            let Some(rel_path) = path.to_str() else {
+                return Ok(None);
+            };
+            let file_type = dirent.file_type().map_err(read_dir)?;
+            if file_type.is_dir() {
+                // Skips our own metadata, and any nested repo.
+                if dirent.file_name() == hg::HG_META_DIR
+                    || abs_dir
+                        .join(dirent.file_name())
+                        .join(hg::HG_META_DIR)
+                        .exists()
+                    || ignore.is_ignored(rel_path)
+                {
+                    continue;
+                }
+                if self.unknown_files(&path, known, ignore, unknown)?.is_none() {
+                    return Ok(None);
+                }
+            } else if !known.contains(rel_path) && !ignore.is_ignored(rel_path) {
+                unknown.push(rel_path.to_string());
+            }
+        }
+        Ok(Some(()))
+    }
+
+    fn native_status(&self) -> Result<Option<Vec<FileStatus>>, DriverError> {
+        let Some(dirstate) = self.dirstate()? else {
+            return Ok(None);
+        };
+        let Some(ignore) = Ignore::load(&self.root)? else {
+            return Ok(None);
+        };
+        let Some(states) = self.file_states(&dirstate)? else {
+            return Ok(None);
+        };
+        let known: HashSet<&str> = dirstate
+            .entries
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .collect();
+        let mut unknown = vec![];
+        if self
+            .unknown_files(Path::new(""), &known, &ignore, &mut unknown)?
+            .is_none()
+        {
+            return Ok(None);
+        }
+        unknown.sort();
+
+        let mut statuses = vec![];
+        for (state, entry) in states {
+            let kind = match state {
+                FileState::Modified => FileStatusKind::Modified,
+                FileState::Added => FileStatusKind::Added,
+                FileState::Removed | FileState::Missing => FileStatusKind::Deleted,
+                FileState::Clean => continue,
+            };
+            let copy_source = entry
+                .copy_source
+                .as_ref()
+                .filter(|_| matches!(state, FileState::Modified | FileState::Added));
+            statuses.push(FileStatus {
+                kind: if copy_source.is_some() {
+                    FileStatusKind::Copied
+                } else {
+                    kind
+                },
+                path: PathBuf::from(&entry.path),
+                original_path: copy_source.map(PathBuf::from),
+                staged: None,
+            });
+        }
+        statuses.extend(unknown.iter().map(|path| FileStatus {
+            kind: FileStatusKind::Untracked,
+            path: PathBuf::from(path),
+            original_path: None,
+            staged: None,
+        }));
+        hg::fold_renames(&mut statuses);
+        Ok(Some(statuses))
+    }
+
+    fn native_tracked_files(&self) -> Result<Option<Vec<QueryDir>>, DriverError> {
+        let Some(dirstate) = self.dirstate()? else {
+            return Ok(None);
+        };
+        let Some(states) = self.file_states(&dirstate)? else {
+            return Ok(None);
+        };
+        Ok(Some(
+            states
+                .into_iter()
+                .filter(|(state, _)| *state == FileState::Clean)
+                .map(|(_, entry)| PathBuf::from(&entry.path))
+                .collect(),
+        ))
+    }
+
+    /// The working directory's parent, or `None` if there's a merge in progress (which `hg`
+    /// reports both parents of).
+    fn working_parent(&self) -> Result<Option<WorkingParent>, DriverError> {
+        let Some(dirstate) = self.dirstate()? else {
+            return Ok(None);
+        };
+        if dirstate.p2 != [0; HG_NODE_LEN] {
+            return Ok(None);
+        }
+        Ok(Some(if dirstate.p1 == [0; HG_NODE_LEN] {
+            WorkingParent::Null
+        } else {
+            WorkingParent::Node(dirstate.p1)
+        }))
+    }
+
+    /// Returns the same name as `hg::Repo::current_ref_name`, where it's `node`'s own tag or
+    /// bookmark; `None` leaves it to `hg` (eg: to name its branch instead).
+    fn native_name_of(&self, node: &Node) -> Result<Option<HistoryRefName>, DriverError> {
+        let Some(changelog) = self.changelog()? else {
+            return Ok(None);
+        };
+        let Some(rev) = changelog.rev_of(node) else {
+            return Ok(None);
+        };
+        let Some(mut global_tags) = self.global_tags(&changelog)? else {
+            return Ok(None);
+        };
+        let node = hex(node);
+        let mut tags = global_tags.remove(&rev).unwrap_or_default();
+        tags.extend(
+            self.names_in(HG_LOCAL_TAGS)?
+                .remove(&node)
+                .unwrap_or_default(),
+        );
+        tags.sort();
+        if let Some(tag) = tags.into_iter().next() {
+            return Ok(Some(tag));
+        }
+
+        let mut bookmarks = self
+            .names_in(HG_BOOKMARKS)?
+            .remove(&node)
+            .unwrap_or_default();
+        bookmarks.sort();
+        let active = self
+            .read_meta(HG_BOOKMARK_ACTIVE)?
+            .map(|active| String::from_utf8_lossy(&active).trim_end().to_string());
+        Ok(active
+            .filter(|active| bookmarks.contains(active))
+            .or_else(|| bookmarks.into_iter().next()))
+    }
+}
+
+impl Driver for Repo {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Mercurial
+    }
+
+    fn root(&self) -> Result<QueryDir, DriverError> {
+        Ok(self.root.clone())
+    }
+
+    fn status(&self) -> Result<Vec<FileStatus>, DriverError> {
+        match self.native_status()? {
+            Some(statuses) => Ok(statuses),
+            None => self.cli.status(),
+        }
+    }
+
+    fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError> {
+        match self.native_tracked_files()? {
+            Some(files) => Ok(files),
+            None => self.cli.tracked_files(),
+        }
+    }
+
+    fn changed_files(
+        &self,
+        base: Option<&str>,
+        head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError> {
+        self.cli.changed_files(base, head)
+    }
+
+    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        self.cli.parent_ref_id()
+    }
+
+    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError> {
+        self.cli.parent_ref_name()
+    }
+
+    fn first_ancestor_ref_name(
+        &self,
+        limit: Option<NonZero<u64>>,
+    ) -> Result<Option<AncestorRef>, DriverError> {
+        self.cli.first_ancestor_ref_name(limit)
+    }
+
+    fn child_ref_ids(&self, max: Option<NonZero<u64>>) -> Result<Vec<HistoryRefId>, DriverError> {
+        self.cli.child_ref_ids(max)
+    }
+
+    fn ahead_behind(&self) -> Result<Option<AheadBehind>, DriverError> {
+        self.cli.ahead_behind()
+    }
+
+    fn operation(&self) -> Result<Option<RepoOperation>, DriverError> {
+        self.cli.operation()
+    }
+
+    fn current_ref_id(&self, dirty_ok: bool) -> Result<HistoryRefId, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        match self.working_parent()? {
+            Some(WorkingParent::Node(current)) => Ok(hex(&current)),
+            Some(WorkingParent::Null) => Err(DriverError::EmptyHistory),
+            None => self.cli.current_ref_id(true /*dirty_ok*/),
+        }
+    }
+
+    /// Returns the current revision's own tag if it has one, else its bookmark, else its branch.
+    fn current_ref_name(&self, dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        let name = match self.working_parent()? {
+            Some(WorkingParent::Null) => return Ok(None),
+            Some(WorkingParent::Node(current)) => self.native_name_of(&current)?,
+            None => None,
+        };
+        match name {
+            Some(name) => Ok(Some(name)),
+            None => self.cli.current_ref_name(true /*dirty_ok*/),
+        }
+    }
+}
+
+impl Changelog {
+    fn rev_of(&self, node: &Node) -> Option<usize> {
+        self.nodes.iter().rposition(|candidate| candidate == node)
+    }
+
+    fn rev_of_hex(&self, node: &str) -> Option<usize> {
+        self.nodes
+            .iter()
+            .rposition(|candidate| hex(candidate) == node)
+    }
+}
+
+/// Patterns of an ignore file, each matched against paths relative to the repo root.
+#[derive(Debug)]
+struct Ignore {
+    patterns: Vec<Regex>,
+}
+
+impl Ignore {
+    /// Reads the root's ignore file, or `None` if it uses syntax not handled here (eg: `include:`
+    /// lines, or regexps beyond what the `regex` crate and Python's `re` have in common).
+    fn load(root: &Path) -> Result<Option<Self>, DriverError> {
+        let path = root.join(HG_IGNORE_FILE);
+        let data = match fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(DriverError::Directory(format!("{}: {}", path.display(), e))),
+        };
+        let mut syntax = "relre";
+        let mut patterns = vec![];
+        for line in data.lines() {
+            let line = strip_ignore_comment(line);
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix(HG_IGNORE_SYNTAX_PREFIX) {
+                syntax = match rest.trim() {
+                    "re" | "regexp" => "relre",
+                    "glob" => "relglob",
+                    "rootglob" => "rootglob",
+                    _ => return Ok(None),
+                };
+                continue;
+            }
+            let (kind, pattern) = match line.split_once(':') {
+                Some(("re" | "regexp", pattern)) => ("relre", pattern),
+                Some(("glob", pattern)) => ("relglob", pattern),
+                Some(("rootglob", pattern)) => ("rootglob", pattern),
+                Some(("include" | "subinclude" | "path" | "relpath" | "rootfilesin", _)) => {
+                    return Ok(None)
+                }
+                _ => (syntax, line),
+            };
+            let regex = match kind {
+                "relglob" => format!("^(?:|.*/){}(?:/|$)", glob_to_regex(pattern)),
+                "rootglob" => format!("^{}(?:/|$)", glob_to_regex(pattern)),
+                _ => pattern.to_string(),
+            };
+            let Ok(regex) = Regex::new(&regex) else {
+                return Ok(None);
+            };
+            patterns.push(regex);
+        }
+        Ok(Some(Ignore { patterns }))
+    }
+
+    fn is_ignored(&self, path: &str) -> bool {
+        self.patterns.iter().any(|pattern| pattern.is_match(path))
+    }
+}
+
+/// Strips a `#` comment from a line of an ignore file (where `\#` is a literal `#`).
+fn strip_ignore_comment(line: &str) -> String {
+    let mut stripped = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('#') => stripped.push('#'),
+                Some(next) => {
+                    stripped.push(c);
+                    stripped.push(next);
+                }
+                None => stripped.push(c),
+            },
+            '#' => break,
+            _ => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+/// Translates a glob to a regex, the way Mercurial does.
+fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut regex = String::new();
+    let mut groups = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+        match c {
+            '*' if chars.get(i) == Some(&'*') => {
+                i += 1;
+                if chars.get(i) == Some(&'/') {
+                    i += 1;
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push('.'),
+            '[' => match chars[i..].iter().position(|c| *c == ']') {
+                Some(len) => {
+                    let class: String = chars[i..i + len].iter().collect();
+                    i += len + 1;
+                    let class = class.replace('\\', "\\\\");
+                    let class = match class.strip_prefix('!') {
+                        Some(negated) => format!("^{negated}"),
+                        None if class.starts_with('^') => format!("\\{class}"),
+                        None => class,
+                    };
+                    regex.push('[');
+                    regex.push_str(&class);
+                    regex.push(']');
+                }
+                None => regex.push_str("\\["),
+            },
+            '{' => {
+                groups += 1;
+                regex.push_str("(?:");
+            }
+            '}' if groups > 0 => {
+                groups -= 1;
+                regex.push(')');
+            }
+            ',' if groups > 0 => regex.push('|'),
+            '\\' => match chars.get(i) {
+                Some(escaped) => {
+                    i += 1;
+                    regex.push_str(&regex::escape(&escaped.to_string()));
+                }
+                None => regex.push_str("\\\\"),
+            },
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex
+}
+
+/// Returns whether `meta` is executable, and its modification time (as seconds and nanoseconds),
+/// or `None` where `hg` wouldn't compare them.
+#[cfg(unix)]
+fn stat(meta: &fs::Metadata) -> Option<(bool, (i64, u32))> {
+    use std::os::unix::fs::MetadataExt;
+    let nanos = u32::try_from(meta.mtime_nsec()).ok()?;
+    Some((meta.mode() & 0o100 != 0, (meta.mtime(), nanos)))
+}
+
+#[cfg(not(unix))]
+fn stat(_meta: &fs::Metadata) -> Option<(bool, (i64, u32))> {
+    None
+}
+
+/// Names on each node, from lines of `NODE NAME` listed oldest first (so later lines win, and null
+/// nodes are deletions).
+fn names_by_node<'a>(lines: impl Iterator<Item = &'a str>) -> HashMap<String, Vec<String>> {
+    let mut nodes_by_name: HashMap<&str, &str> = HashMap::new();
+    for line in lines {
+        if let Some((node, name)) = line.split_once(' ') {
+            nodes_by_name.insert(name, node);
+        }
+    }
+    let mut names: HashMap<String, Vec<String>> = HashMap::new();
+    for (name, node) in nodes_by_name {
+        names
+            .entry(node.to_string())
+            .or_default()
+            .push(name.to_string());
+    }
+    names
+}
+
+fn hex(node: &Node) -> String {
+    node.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+fn be_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn be_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn be_i32(data: &[u8], at: usize) -> Option<i32> {
+    Some(i32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn node_at(data: &[u8], at: usize) -> Option<Node> {
+    data.get(at..at + HG_NODE_LEN)?.try_into().ok()
+}
+
+/// Parses a v1 dirstate: both parents, then each entry's state, mode, size, mtime and name (with
+/// any copy origin appended).
+fn parse_dirstate_v1(data: &[u8]) -> Option<Dirstate> {
+    let p1 = node_at(data, 0)?;
+    let p2 = node_at(data, HG_NODE_LEN)?;
+    let mut entries = vec![];
+    let mut at = 2 * HG_NODE_LEN;
+    while at < data.len() {
+        let state = *data.get(at)?;
+        let mode = be_u32(data, at + 1)?;
+        let size = be_i32(data, at + 5)?;
+        let mtime = be_i32(data, at + 9)?;
+        let name_len = usize::try_from(be_u32(data, at + 13)?).ok()?;
+        let name_at = at + HG_DIRSTATE_V1_ENTRY_LEN;
+        let name = data.get(name_at..name_at + name_len)?;
+        at = name_at + name_len;
+
+        let (path, copy_source) = match name.iter().position(|b| *b == HG_DIRSTATE_V1_COPY_SEP) {
+            Some(sep) => (&name[..sep], Some(&name[sep + 1..])),
+            None => (name, None),
+        };
+        let recorded = match state {
+            b'a' => Recorded::Added,
+            b'r' => Recorded::Removed,
+            b'm' => Recorded::Merged,
+            b'n' if size == HG_DIRSTATE_V1_SIZE_FROM_P2 => Recorded::Merged,
+            b'n' => Recorded::Normal {
+                size: Some(i64::from(size)).filter(|_| size != HG_DIRSTATE_V1_SIZE_NONNORMAL),
+                exec: mode & 0o100 != 0,
+                symlink: mode & 0o170_000 == 0o120_000,
+                mtime: Some((i64::from(mtime), 0)).filter(|_| mtime != HG_DIRSTATE_V1_MTIME_UNSET),
+                known_modified: false,
+            },
+            _ => return None,
+        };
+        entries.push(DirstateEntry {
+            path: String::from_utf8(path.to_vec()).ok()?,
+            recorded,
+            copy_source: match copy_source {
+                Some(source) => Some(String::from_utf8(source.to_vec()).ok()?),
+                None => None,
+            },
+        });
+    }
+    Some(Dirstate { p1, p2, entries })
+}
+
+/// Parses a dirstate-v2 docket (after its marker) into both parents, the tree metadata, and the
+/// name and used length of the data file.
+fn parse_dirstate_v2_docket(docket: &[u8]) -> Option<(Node, Node, &[u8], String, usize)> {
+    // Parents are padded to 32 bytes, for longer hashes to come.
+    let p1 = node_at(docket, 0)?;
+    let p2 = node_at(docket, 32)?;
+    let tree_metadata = docket.get(64..64 + HG_DIRSTATE_V2_TREE_METADATA_LEN)?;
+    let at = 64 + HG_DIRSTATE_V2_TREE_METADATA_LEN;
+    let data_len = usize::try_from(be_u32(docket, at)?).ok()?;
+    let uuid_len = usize::from(*docket.get(at + 4)?);
+    let uuid = std::str::from_utf8(docket.get(at + 5..at + 5 + uuid_len)?).ok()?;
+    Some((
+        p1,
+        p2,
+        tree_metadata,
+        format!("{HG_DIRSTATE}.{uuid}"),
+        data_len,
+    ))
+}
+
+/// Parses the tree of nodes in a dirstate-v2 data file, from the root nodes named by
+/// `tree_metadata`.
+fn parse_dirstate_v2(data: &[u8], tree_metadata: &[u8]) -> Option<Vec<DirstateEntry>> {
+    let mut entries = vec![];
+    let mut pending = vec![(be_u32(tree_metadata, 0)?, be_u32(tree_metadata, 4)?)];
+    while let Some((start, len)) = pending.pop() {
+        let start = usize::try_from(start).ok()?;
+        for i in 0..usize::try_from(len).ok()? {
+            let node = data.get(start + i * HG_DIRSTATE_V2_NODE_LEN..)?;
+            let path_start = usize::try_from(be_u32(node, 0)?).ok()?;
+            let path_len = usize::from(be_u16(node, 4)?);
+            let copy_start = usize::try_from(be_u32(node, 8)?).ok()?;
+            let copy_len = usize::from(be_u16(node, 12)?);
+            pending.push((be_u32(node, 14)?, be_u32(node, 18)?));
+            let flags = be_u16(node, 30)?;
+            let size = be_i32(node, 32)?;
+            let mtime_secs = be_i32(node, 36)?;
+            let mtime_nanos = be_u32(node, 40)?;
+
+            let has = |flag: u16| flags & flag != 0;
+            let recorded = match (
+                has(HG_DIRSTATE_V2_WDIR_TRACKED),
+                has(HG_DIRSTATE_V2_P1_TRACKED),
+                has(HG_DIRSTATE_V2_P2_INFO),
+            ) {
+                // Directories (and files `hg` only recorded as unknown or ignored).
+                (false, false, false) => continue,
+                (true, false, false) => Recorded::Added,
+                (false, _, _) => Recorded::Removed,
+                (true, _, true) => Recorded::Merged,
+                (true, true, false) => Recorded::Normal {
+                    size: Some(i64::from(size)).filter(|_| has(HG_DIRSTATE_V2_HAS_MODE_AND_SIZE)),
+                    exec: has(HG_DIRSTATE_V2_MODE_EXEC_PERM),
+                    symlink: has(HG_DIRSTATE_V2_MODE_IS_SYMLINK),
+                    mtime: Some((i64::from(mtime_secs), mtime_nanos)).filter(|_| {
+                        has(HG_DIRSTATE_V2_HAS_MTIME) && !has(HG_DIRSTATE_V2_MTIME_SECOND_AMBIGUOUS)
+                    }),
+                    known_modified: has(HG_DIRSTATE_V2_EXPECTED_STATE_IS_MODIFIED),
+                },
+            };
+            let path = data.get(path_start..path_start + path_len)?;
+            let copy_source = match copy_len {
+                0 => None,
+                _ => Some(data.get(copy_start..copy_start + copy_len)?),
+            };
+            entries.push(DirstateEntry {
+                path: String::from_utf8(path.to_vec()).ok()?,
+                recorded,
+                copy_source: match copy_source {
+                    Some(source) => Some(String::from_utf8(source.to_vec()).ok()?),
+                    None => None,
+                },
+            });
+        }
+    }
+    Some(entries)
+}
+
+/// Parses a v1 revlog index (skipping over any revision data interleaved with it).
+fn parse_revlog_index(data: &[u8]) -> Option<Changelog> {
+    let mut changelog = Changelog {
+        nodes: vec![],
+        parents: vec![],
+    };
+    if data.is_empty() {
+        return Some(changelog);
+    }
+    // The first entry's offset (always 0) is replaced by the revlog's header.
+    let header = be_u32(data, 0)?;
+    if header & 0xffff != HG_REVLOG_V1 {
+        return None;
+    }
+    let inline = header & HG_REVLOG_FLAG_INLINE != 0;
+    let mut at = 0;
+    while at < data.len() {
+        let entry = data.get(at..at + HG_REVLOG_V1_ENTRY_LEN)?;
+        changelog
+            .parents
+            .push((be_i32(entry, 24)?, be_i32(entry, 28)?));
+        changelog.nodes.push(node_at(entry, 32)?);
+        at += HG_REVLOG_V1_ENTRY_LEN;
+        if inline {
+            at += usize::try_from(be_u32(entry, 8)?).ok()?;
+        }
+    }
+    Some(changelog)
+}
@@ -1,15 +1,85 @@
+use crate::adapter::git;
+use crate::plexer::VcsBrand;
 use crate::repo::{
-    Driver, DriverError, HistoryRefId, HistoryRefName, QueryDir, Validator, VcsAvailable,
-    ERROR_REPO_NOT_CLEAN, ERROR_REPO_NOT_DIRTY,
+    may_be_in_repo, AheadBehind, AncestorRef, Driver, DriverError, FileStatus, FileStatusKind,
+    HistoryRefId, HistoryRefName, QueryDir, RepoOperation, Validator, VcsAvailable,
 };
+use std::collections::HashMap;
+use std::num::NonZero;
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
 
 static VCS_BIN_NAME: &str = "jj";
 
+/// jj's directory of metadata, relative to the workspace root.
+pub(crate) const JJ_META_DIR: &str = ".jj";
+
+/// Revset of what vcsq considers the "current" point in history: the parent of the working-copy
+/// commit (as the working-copy commit itself is ephemeral).
+const JJ_REVSET_CURRENT: &str = "@-";
+
+/// Revset of the children of the "current" revision, excluding the working-copy commit (which is
+/// always a child of the current revision, as it's where in-progress edits live).
+const JJ_REVSET_CHILDREN: &str = "children(@-) ~ @";
+
+/// Revset of the nearest bookmarked ancestors of the "current" revision, ie: the bookmarks a user
+/// would consider themselves to be "on" (like git's current branch), even when the current revision
+/// itself isn't bookmarked.
+const JJ_REVSET_NEAREST_BOOKMARKED: &str = "heads(::@- & bookmarks())";
+
+/// Revset of the "trunk" jj infers from remote bookmarks (eg: `main@origin`), which vcsq treats as
+/// the upstream of the current revision; empty when jj has no such bookmark to infer it from (as
+/// jj's `trunk()` otherwise falls back to `root()`).
+const JJ_REVSET_UPSTREAM: &str = "trunk() ~ root()";
+
+/// Template printing the commit IDs of a revision's parents, one per line and in order (so the
+/// first line is the first parent). jj's synthetic `root()` commit is not printed, so the root of
+/// history has no parents (consistent with other brands).
+const JJ_TEMPLATE_PARENT_IDS: &str =
+    r#"parents.map(|c| if(c.root(), "", c.commit_id() ++ "\n")).join("")"#;
+
+/// Template printing a revision's commit ID, or nothing for jj's synthetic `root()` commit (which
+/// is the "current" revision of a repo with no history yet).
+const JJ_TEMPLATE_CURRENT_ID: &str = r#"if(root, "", commit_id ++ "\n")"#;
+
+/// Template printing a revision's human-made names, one per line: tags first, then local
+/// bookmarks.
+const JJ_TEMPLATE_REF_NAMES: &str = concat!(
+    r#"tags.map(|t| t.name() ++ "\n").join("")"#,
+    r#" ++ local_bookmarks.map(|b| b.name() ++ "\n").join("")"#,
+);
+
+/// Template printing a revision's local bookmarks, one per line.
+const JJ_TEMPLATE_BOOKMARK_NAMES: &str = r#"local_bookmarks.map(|b| b.name() ++ "\n").join("")"#;
+
+/// Git directory jj keeps alongside `.jj` in the workspace root of a "colocated" repo (ie: one
+/// that plain git commands can be used in too).
+pub(crate) const JJ_COLOCATED_GIT_DIR: &str = ".git";
+
+/// Field separator (tab) for `JJ_TEMPLATE_ANCESTRY` output.
+const JJ_TEMPLATE_SEP: char = '\t';
+
+/// Lines of the form `COMMIT_ID<tab>PARENT_ID PARENT_ID<tab>NAME<tab>NAME`, where names are
+/// ordered like `JJ_TEMPLATE_REF_NAMES`.
+const JJ_TEMPLATE_ANCESTRY: &str = concat!(
+    r#"commit_id ++ "\t" ++ parents.map(|c| c.commit_id()).join(" ")"#,
+    r#" ++ tags.map(|t| "\t" ++ t.name()).join("")"#,
+    r#" ++ local_bookmarks.map(|b| "\t" ++ b.name()).join("")"#,
+    r#" ++ "\n""#,
+);
+
+/// Separator between original and new paths of renames/copies in `jj diff --summary` output.
+const JJ_SUMMARY_RENAME_SEP: &str = " => ";
+
+/// One line of `JJ_TEMPLATE_ANCESTRY` output.
+struct Ancestry {
+    first_parent: Option<HistoryRefId>,
+    names: Vec<HistoryRefName>,
+}
+
 #[derive(Debug)]
 pub struct Repo {
-    dir: QueryDir,
+    pub(crate) dir: QueryDir,
 }
 
 #[derive(Debug)]
@@ -18,7 +88,14 @@ where
     Self: Sized;
 
 impl Validator for Loader {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Jujutsu
+    }
+
     fn new_driver(&self, dir: QueryDir) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        if !may_be_in_repo(&dir, JJ_META_DIR) {
+            return Ok(None);
+        }
         let repo = Repo { dir };
 
         let is_ok = DriverError::unwrap_cmd_lossy(
@@ -61,7 +138,28 @@ impl Repo {
 
     fn jj_dirty_files(&self) -> Command {
         let mut cmd = self.start_shellout();
-        cmd.arg("diff").arg("--name-only");
+        cmd.arg("diff").arg("--summary");
+        cmd
+    }
+
+    /// Lists files `revset` changed relative to its parent(s).
+    fn jj_files_of(&self, revset: &str) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("diff")
+            .arg("--name-only")
+            .arg("--revisions")
+            .arg(revset);
+        cmd
+    }
+
+    fn jj_files_between(&self, base: &str, head: &str) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("diff")
+            .arg("--name-only")
+            .arg("--from")
+            .arg(base)
+            .arg("--to")
+            .arg(head);
         cmd
     }
 
@@ -74,20 +172,105 @@ impl Repo {
         cmd
     }
 
-    fn jj_current_ref_id(&self) -> Command {
+    fn jj_log_template(&self, revset: &str, template: &str) -> Command {
         let mut cmd = self.start_shellout();
         cmd.arg("log")
             .arg("--color=never")
             .arg("--no-graph")
             .arg("--revisions")
-            .arg("@-")
+            .arg(revset)
             .arg("--template")
-            .arg("commit_id");
+            .arg(template);
+        cmd
+    }
+
+    fn jj_current_ref_id(&self) -> Command {
+        self.jj_log_template(JJ_REVSET_CURRENT, JJ_TEMPLATE_CURRENT_ID)
+    }
+
+    /// Returns the first parent of the "current" revision (see `JJ_REVSET_CURRENT`).
+    fn first_parent_id(&self) -> Result<HistoryRefId, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.jj_log_template(JJ_REVSET_CURRENT, JJ_TEMPLATE_PARENT_IDS)
+                .output(),
+            1, /*min_lines*/
+            "jj cli: exec",
+            Some(DriverError::NoParent),
+        )?;
+        Ok(lines
+            .into_iter()
+            .next()
+            .expect("bug: expect_cmd_lines should have enforced min_lines"))
+    }
+
+    /// Lists the current revision's ancestry, deep enough to walk `limit` steps back from it.
+    fn jj_ancestry(&self, limit: Option<NonZero<u64>>) -> Command {
+        let revset = match limit {
+            Some(limit) => format!(
+                "ancestors({JJ_REVSET_CURRENT}, {}) ~ root()",
+                limit.saturating_add(1)
+            ),
+            None => format!("::{JJ_REVSET_CURRENT} ~ root()"),
+        };
+        self.jj_log_template(&revset, JJ_TEMPLATE_ANCESTRY)
+    }
+
+    fn jj_children(&self, max: Option<NonZero<u64>>) -> Command {
+        let mut cmd = self.jj_log_template(JJ_REVSET_CHILDREN, r#"commit_id ++ "\n""#);
+        if let Some(max) = max {
+            cmd.arg("--limit").arg(max.to_string());
+        }
         cmd
     }
+
+    /// Counts the revisions in `revset`.
+    fn count_of(&self, revset: &str) -> Result<u64, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.jj_log_template(revset, r#"commit_id ++ "\n""#)
+                .output(),
+            0, /*min_lines*/
+            "jj cli: exec",
+            None,
+        )?;
+        Ok(lines.len() as u64)
+    }
+
+    /// Lists human-made names (tags, then local bookmarks) pointing at `revset`.
+    fn ref_names_of(&self, revset: &str) -> Result<Vec<HistoryRefName>, DriverError> {
+        DriverError::expect_cmd_lines(
+            self.jj_log_template(revset, JJ_TEMPLATE_REF_NAMES).output(),
+            0, /*min_lines*/
+            "jj cli: exec",
+            None,
+        )
+    }
+
+    /// Lists local bookmarks pointing at `revset`.
+    fn bookmark_names_of(&self, revset: &str) -> Result<Vec<HistoryRefName>, DriverError> {
+        DriverError::expect_cmd_lines(
+            self.jj_log_template(revset, JJ_TEMPLATE_BOOKMARK_NAMES)
+                .output(),
+            0, /*min_lines*/
+            "jj cli: exec",
+            None,
+        )
+    }
+
+    /// Git driver for this same repo, if jj is colocated with git.
+    fn colocated_git(&self) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        let root = self.root()?;
+        if !root.join(JJ_COLOCATED_GIT_DIR).exists() {
+            return Ok(None);
+        }
+        git::Loader {}.new_driver(root)
+    }
 }
 
 impl Driver for Repo {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Jujutsu
+    }
+
     fn root(&self) -> Result<QueryDir, DriverError> {
         let output = DriverError::expect_cmd_lossy("jj cli".to_string(), self.jj_root().output())?;
         Ok(PathBuf::from(DriverError::expect_cmd_line(
@@ -95,16 +278,18 @@ impl Driver for Repo {
         )?))
     }
 
-    fn dirty_files(&self, clean_ok: bool) -> Result<Vec<QueryDir>, DriverError> {
-        let min_lines = u8::from(!clean_ok);
+    /// Lists changes in the working-copy commit (jj has no index, nor untracked files).
+    fn status(&self) -> Result<Vec<FileStatus>, DriverError> {
         let lines = DriverError::expect_cmd_lines(
             self.jj_dirty_files().output(),
-            min_lines,
+            0, /*min_lines*/
             "jj cli: exec",
-            Some(ERROR_REPO_NOT_DIRTY.to_string()),
+            None,
         )?;
-        let dirty_files = lines.into_iter().map(PathBuf::from).collect();
-        Ok(dirty_files)
+        Ok(lines
+            .iter()
+            .filter_map(|ln| parse_summary_line(ln))
+            .collect())
     }
 
     fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError> {
@@ -118,22 +303,214 @@ impl Driver for Repo {
         Ok(files)
     }
 
+    /// Returns the nearest tag or local bookmark on the first-parent history of the current
+    /// revision (tags taking precedence when both are on the same commit).
+    fn first_ancestor_ref_name(
+        &self,
+        limit: Option<NonZero<u64>>,
+    ) -> Result<Option<AncestorRef>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.jj_ancestry(limit).output(),
+            0, /*min_lines*/
+            "jj cli: exec",
+            None,
+        )?;
+
+        // jj logs newest-first, so the current revision itself is the first line.
+        let mut current_id = None;
+        let mut ancestry = HashMap::new();
+        for line in lines {
+            let mut fields = line.split(JJ_TEMPLATE_SEP);
+            let Some(id) = fields.next() else {
+                continue;
+            };
+            let first_parent = fields
+                .next()
+                .and_then(|parents| parents.split_whitespace().next())
+                .map(std::string::ToString::to_string);
+            let names = fields.map(std::string::ToString::to_string).collect();
+            current_id.get_or_insert_with(|| id.to_string());
+            ancestry.insert(
+                id.to_string(),
+                Ancestry {
+                    first_parent,
+                    names,
+                },
+            );
+        }
+
+        let mut next_id = current_id
+            .and_then(|id| ancestry.get(&id))
+            .and_then(|current| current.first_parent.clone());
+        let mut steps_back = NonZero::<u64>::MIN;
+        while let Some(id) = next_id.take() {
+            if limit.is_some_and(|limit| steps_back > limit) {
+                break;
+            }
+            // Missing entries are beyond our revset (ie: the root commit).
+            let Some(ancestor) = ancestry.get(&id) else {
+                break;
+            };
+            if let Some(name) = ancestor.names.first() {
+                return Ok(Some(AncestorRef {
+                    id,
+                    name: name.clone(),
+                    distance: steps_back,
+                }));
+            }
+            next_id.clone_from(&ancestor.first_parent);
+            steps_back = steps_back.saturating_add(1);
+        }
+        Ok(None)
+    }
+
+    /// Note: when `base` is `None` and `head` is a merge, jj compares against the auto-merged
+    /// parents (rather than just the first parent, like other brands).
+    fn changed_files(
+        &self,
+        base: Option<&str>,
+        head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError> {
+        let head = head.unwrap_or(JJ_REVSET_CURRENT);
+        let mut cmd = match base {
+            Some(base) => self.jj_files_between(base, head),
+            None => self.jj_files_of(head),
+        };
+        let lines = DriverError::expect_cmd_lines(
+            cmd.output(),
+            0, /*min_lines*/
+            "jj cli: exec",
+            None,
+        )?;
+        Ok(lines.into_iter().map(PathBuf::from).collect())
+    }
+
+    /// Returns the backing store's "commit id" of each child of the current revision, ignoring the
+    /// working-copy commit.
+    fn child_ref_ids(&self, max: Option<NonZero<u64>>) -> Result<Vec<HistoryRefId>, DriverError> {
+        DriverError::expect_cmd_lines(
+            self.jj_children(max).output(),
+            0, /*min_lines*/
+            "jj cli: exec",
+            None,
+        )
+    }
+
+    /// Returns the backing store's "commit id" of the first parent of the current revision.
+    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        self.first_parent_id()
+    }
+
+    /// Returns a tag on the first parent if available, otherwise its first local bookmark.
+    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError> {
+        let parent_id = self.first_parent_id()?;
+        Ok(self.ref_names_of(&parent_id)?.into_iter().next())
+    }
+
+    /// Compares the current revision against jj's `trunk()`, if jj could infer one.
+    fn ahead_behind(&self) -> Result<Option<AheadBehind>, DriverError> {
+        if self.count_of(JJ_REVSET_UPSTREAM)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(AheadBehind {
+            ahead: self.count_of(&format!("({JJ_REVSET_UPSTREAM})..{JJ_REVSET_CURRENT}"))?,
+            behind: self.count_of(&format!("{JJ_REVSET_CURRENT}..({JJ_REVSET_UPSTREAM})"))?,
+        }))
+    }
+
+    /// Always `None`: jj operations are atomic (eg: conflicts are recorded in commits, rather than
+    /// leaving the repo mid-rebase).
+    fn operation(&self) -> Result<Option<RepoOperation>, DriverError> {
+        Ok(None)
+    }
+
     /// Returns the backing store's "commit id" (as opposed to the more ephemeral "change id").
     fn current_ref_id(&self, dirty_ok: bool) -> Result<HistoryRefId, DriverError> {
         if !dirty_ok && !self.is_clean()? {
-            return Err(ERROR_REPO_NOT_CLEAN.to_string().into());
+            return Err(DriverError::RepoDirty);
         }
-        let output =
-            DriverError::expect_cmd_lossy("jj cli".to_string(), self.jj_current_ref_id().output())?;
-        DriverError::expect_cmd_line("jj cli", &output)
+        let lines = DriverError::expect_cmd_lines(
+            self.jj_current_ref_id().output(),
+            1, /*min_lines*/
+            "jj cli: exec",
+            Some(DriverError::EmptyHistory),
+        )?;
+        Ok(lines
+            .into_iter()
+            .next()
+            .expect("bug: expect_cmd_lines should have enforced min_lines"))
     }
 
-    /// Returns the current bookmark if there one.
-    // TODO: (feature) when jj is more stable, do more advanced things: determine if we're
-    // git-backed, and then translate our answers into the answer a user would expect if this
-    // wasn't jj-on-git but just git. For now we just keep it simple (and less feature-full) then
-    // our git counterpart driver.
-    fn current_ref_name(&self, _dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
-        todo!(); // TODO: (feature) implement
+    /// Returns the first name found, trying in order:
+    /// 1. a tag on the current revision, otherwise its first local bookmark
+    /// 2. for repos colocated with git: the tag or branch git reports (see the git driver)
+    /// 3. the bookmark of the nearest bookmarked ancestor (see `JJ_REVSET_NEAREST_BOOKMARKED`)
+    fn current_ref_name(&self, dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        if let Some(name) = self.ref_names_of(JJ_REVSET_CURRENT)?.into_iter().next() {
+            return Ok(Some(name));
+        }
+        if let Some(git) = self.colocated_git()? {
+            if let Some(name) = git.current_ref_name(true /*dirty_ok*/)? {
+                return Ok(Some(name));
+            }
+        }
+        Ok(self
+            .bookmark_names_of(JJ_REVSET_NEAREST_BOOKMARKED)?
+            .into_iter()
+            .next())
     }
 }
+
+/// Parses one line of `jj diff --summary` output, eg: `M path`, or for renames and copies
+/// `R common/{original => new}/path` (where the braces are omitted if nothing is in common).
+fn parse_summary_line(line: &str) -> Option<FileStatus> {
+    let kind = match line.chars().next()? {
+        'M' => FileStatusKind::Modified,
+        'A' => FileStatusKind::Added,
+        'D' => FileStatusKind::Deleted,
+        'R' => FileStatusKind::Renamed,
+        'C' => FileStatusKind::Copied,
+        _ => return None,
+    };
+    let paths = line.chars().skip(2).collect::<String>();
+    let (original_path, path) = match kind {
+        FileStatusKind::Renamed | FileStatusKind::Copied => {
+            let (original, path) = split_summary_rename(&paths)?;
+            (Some(PathBuf::from(original)), PathBuf::from(path))
+        }
+        _ => (None, PathBuf::from(paths)),
+    };
+    Some(FileStatus {
+        kind,
+        path,
+        original_path,
+        staged: None,
+    })
+}
+
+/// Splits jj's `prefix{original => new}suffix` rename notation into full (original, new) paths.
+fn split_summary_rename(paths: &str) -> Option<(String, String)> {
+    let Some((prefix, rest)) = paths.split_once('{') else {
+        let (original, new) = paths.split_once(JJ_SUMMARY_RENAME_SEP)?;
+        return Some((original.to_string(), new.to_string()));
+    };
+    let (renamed, suffix) = rest.split_once('}')?;
+    let (original, new) = renamed.split_once(JJ_SUMMARY_RENAME_SEP)?;
+    // jj keeps the path separator around an empty side (eg: `{ => dir}/file`), so don't double
+    // them up.
+    let join = |middle: &str| {
+        let joined = format!("{prefix}{middle}{suffix}");
+        if middle.is_empty() {
+            joined
+                .replacen("//", "/", 1)
+                .trim_start_matches('/')
+                .to_string()
+        } else {
+            joined
+        }
+    };
+    Some((join(original), join(new)))
+}
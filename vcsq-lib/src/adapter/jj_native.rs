@@ -0,0 +1,361 @@
+//! Answers the most common questions (root, status, tracked files, current ID and name) by reading
+//! the repo with [jj-lib](https://docs.rs/jj-lib) rather than running `jj`, and defers everything
+//! else to [`jj::Repo`].
+//!
+//! Unlike `jj` itself, nothing here records an operation, so asking questions doesn't grow the op
+//! log. It isn't strictly read-only though: to see edits jj hasn't yet, `Repo::status` (and so
+//! every dirtiness check) snapshots the working copy, which holds its lock for the duration and
+//! writes the contents of new or changed files into the store (as unreferenced objects).
+use crate::adapter::{git, jj};
+use crate::plexer::VcsBrand;
+use crate::repo::{
+    AheadBehind, AncestorRef, Driver, DriverError, FileStatus, FileStatusKind, HistoryRefId,
+    HistoryRefName, QueryDir, RepoOperation, Validator, VcsAvailable,
+};
+use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
+use jj_lib::config::StackedConfig;
+use jj_lib::conflicts::ConflictMarkerStyle;
+use jj_lib::file_util;
+use jj_lib::fsmonitor::FsmonitorSettings;
+use jj_lib::gitignore::GitIgnoreFile;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::merged_tree::TreeDiffIterator;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::{ReadonlyRepo, Repo as _, StoreFactories};
+use jj_lib::repo_path::RepoPath;
+use jj_lib::revset::ResolvedRevsetExpression;
+use jj_lib::settings::UserSettings;
+use jj_lib::working_copy::SnapshotOptions;
+use jj_lib::workspace::{default_working_copy_factories, Workspace};
+use std::num::NonZero;
+use std::sync::Arc;
+
+/// Largest new file `jj` starts tracking, by default (see its `snapshot.max-new-file-size`).
+const JJ_DEFAULT_MAX_NEW_FILE_SIZE: u64 = 1024 * 1024;
+
+#[derive(Debug)]
+pub struct Repo {
+    /// Root of the workspace (ie: the directory holding `jj::JJ_META_DIR`).
+    root: QueryDir,
+
+    /// Directory the repo was loaded from, which (like `jj` run from it) paths are relative to.
+    dir: QueryDir,
+
+    settings: UserSettings,
+
+    /// Answers everything jj-lib isn't used for.
+    cli: jj::Repo,
+}
+
+#[derive(Debug)]
+pub struct Loader
+where
+    Self: Sized;
+
+impl Validator for Loader {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Jujutsu
+    }
+
+    /// Whether `dir` is in a jj workspace that jj-lib can load.
+    fn new_driver(&self, dir: QueryDir) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        let canonical = dir
+            .canonicalize()
+            .map_err(|e| DriverError::Directory(format!("{}: {}", dir.display(), e)))?;
+        let Some(root) = canonical
+            .ancestors()
+            .find(|ancestor| ancestor.join(jj::JJ_META_DIR).is_dir())
+        else {
+            return Ok(None);
+        };
+        let settings = UserSettings::from_config(StackedConfig::with_defaults())
+            .map_err(|e| DriverError::from_library("jj-lib: settings", e))?;
+        let repo = Repo {
+            root: root.to_path_buf(),
+            dir: canonical.clone(),
+            settings,
+            cli: jj::Repo { dir },
+        };
+        if repo.workspace().is_err() {
+            return Ok(None);
+        }
+        let repo: Box<dyn Driver> = Box::from(repo);
+        Ok(Some(repo))
+    }
+
+    /// Checks the `jj` CLI, as it's still needed for every question jj-lib isn't used for.
+    fn check_health(&self) -> Result<VcsAvailable, DriverError> {
+        jj::Loader {}.check_health()
+    }
+}
+
+impl Repo {
+    fn workspace(&self) -> Result<Workspace, DriverError> {
+        Workspace::load(
+            &self.settings,
+            &self.root,
+            &StoreFactories::default(),
+            &default_working_copy_factories(),
+        )
+        .map_err(|e| DriverError::from_library("jj-lib: load workspace", e))
+    }
+
+    /// Loads the workspace and the repo as of its latest operation.
+    fn load(&self) -> Result<(Workspace, Arc<ReadonlyRepo>), DriverError> {
+        let workspace = self.workspace()?;
+        let repo = workspace
+            .repo_loader()
+            .load_at_head()
+            .map_err(|e| DriverError::from_library("jj-lib: load repo", e))?;
+        Ok((workspace, repo))
+    }
+
+    fn wc_commit(workspace: &Workspace, repo: &ReadonlyRepo) -> Result<Commit, DriverError> {
+        let id = repo
+            .view()
+            .get_wc_commit_id(workspace.workspace_name())
+            .ok_or_else(|| DriverError::UnexpectedOutput {
+                cmd: "jj-lib: view".to_string(),
+                stdout: "no working-copy commit for this workspace".to_string(),
+            })?;
+        Self::commit(repo, id)
+    }
+
+    fn commit(repo: &ReadonlyRepo, id: &CommitId) -> Result<Commit, DriverError> {
+        repo.store()
+            .get_commit(id)
+            .map_err(|e| DriverError::from_library("jj-lib: commit", e))
+    }
+
+    /// The "current" revision (the first parent of the working-copy commit, as the working-copy
+    /// commit itself is ephemeral), or `None` if that's jj's synthetic `root()` commit.
+    fn current_commit(
+        workspace: &Workspace,
+        repo: &ReadonlyRepo,
+    ) -> Result<Option<Commit>, DriverError> {
+        let wc = Self::wc_commit(workspace, repo)?;
+        match wc.parent_ids().first() {
+            Some(id) if id != repo.store().root_commit_id() => Self::commit(repo, id).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Ignores that apply on top of the workspace's own `.gitignore`s, as `jj` would use them.
+    fn base_ignores(repo: &ReadonlyRepo) -> Result<Arc<GitIgnoreFile>, DriverError> {
+        let ignores = GitIgnoreFile::empty();
+        let Ok(git_backend) = jj_lib::git::get_git_backend(repo.store()) else {
+            return Ok(ignores);
+        };
+        ignores
+            .chain_with_file("", git_backend.git_repo_path().join("info").join("exclude"))
+            .map_err(|e| DriverError::from_library("jj-lib: ignores", e))
+    }
+
+    /// `path` relative to the directory the repo was loaded from (as `jj` prints paths).
+    fn display_path(&self, path: &RepoPath) -> QueryDir {
+        file_util::relative_path(&self.dir, &path.to_fs_path_unchecked(&self.root))
+    }
+
+    /// Returns human-made names pointing at `id`: tags first, then local bookmarks.
+    fn ref_names_of(repo: &ReadonlyRepo, id: &CommitId) -> Vec<HistoryRefName> {
+        let view = repo.view();
+        let tags = view
+            .tags()
+            .iter()
+            .filter(|(_, target)| target.added_ids().any(|added| added == id))
+            .map(|(name, _)| name.as_str().to_string());
+        let bookmarks = view
+            .local_bookmarks_for_commit(id)
+            .map(|(name, _)| name.as_str().to_string());
+        tags.chain(bookmarks).collect()
+    }
+
+    /// Returns the first bookmark of the nearest bookmarked ancestors of `id` (ie: `jj`'s
+    /// `heads(::id & bookmarks())`, newest first).
+    fn nearest_bookmark(
+        repo: &ReadonlyRepo,
+        id: &CommitId,
+    ) -> Result<Option<HistoryRefName>, DriverError> {
+        let bookmarked: Vec<CommitId> = repo
+            .view()
+            .local_bookmarks()
+            .flat_map(|(_, target)| target.added_ids().cloned())
+            .collect();
+        let revset = ResolvedRevsetExpression::commit(id.clone())
+            .ancestors()
+            .intersection(&ResolvedRevsetExpression::commits(bookmarked))
+            .heads()
+            .evaluate(repo)
+            .map_err(|e| DriverError::from_library("jj-lib: revset", e))?;
+        for head in revset.iter() {
+            let head = head.map_err(|e| DriverError::from_library("jj-lib: revset", e))?;
+            let name = repo
+                .view()
+                .local_bookmarks_for_commit(&head)
+                .next()
+                .map(|(name, _)| name.as_str().to_string());
+            if name.is_some() {
+                return Ok(name);
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Driver for Repo {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Jujutsu
+    }
+
+    fn root(&self) -> Result<QueryDir, DriverError> {
+        Ok(self.root.clone())
+    }
+
+    /// Lists changes in the working-copy commit (jj has no index, nor untracked files).
+    ///
+    /// Note: unlike `jj diff`, renames and copies are reported as a deletion and an addition, as
+    /// jj only detects them between commits (and the snapshot taken here isn't committed).
+    ///
+    /// WARNING: this takes the working-copy lock while snapshotting, so it blocks on (and blocks)
+    /// a concurrent `jj` command, and it writes blobs and trees for any edits into the store.
+    fn status(&self) -> Result<Vec<FileStatus>, DriverError> {
+        let (mut workspace, repo) = self.load()?;
+        let wc = Self::wc_commit(&workspace, &repo)?;
+        let options = SnapshotOptions {
+            base_ignores: Self::base_ignores(&repo)?,
+            fsmonitor_settings: FsmonitorSettings::None,
+            progress: None,
+            start_tracking_matcher: &EverythingMatcher,
+            max_new_file_size: JJ_DEFAULT_MAX_NEW_FILE_SIZE,
+            conflict_marker_style: ConflictMarkerStyle::default(),
+        };
+        let tree_id = {
+            let mut locked = workspace
+                .start_working_copy_mutation()
+                .map_err(|e| DriverError::from_library("jj-lib: lock working copy", e))?;
+            // Dropped without being `finish()`ed, so the working copy's state is left as it was.
+            let (tree_id, _stats) = locked
+                .locked_wc()
+                .snapshot(&options)
+                .map_err(|e| DriverError::from_library("jj-lib: snapshot", e))?;
+            tree_id
+        };
+        let after = repo
+            .store()
+            .get_root_tree(&tree_id)
+            .map_err(|e| DriverError::from_library("jj-lib: tree", e))?;
+        let before = wc
+            .parent_tree(repo.as_ref())
+            .map_err(|e| DriverError::from_library("jj-lib: tree", e))?;
+
+        let mut statuses = vec![];
+        for entry in TreeDiffIterator::new(before.as_merge(), after.as_merge(), &EverythingMatcher)
+        {
+            let (before, after) = entry
+                .values
+                .map_err(|e| DriverError::from_library("jj-lib: diff", e))?;
+            let kind = if before.is_absent() {
+                FileStatusKind::Added
+            } else if after.is_absent() {
+                FileStatusKind::Deleted
+            } else {
+                FileStatusKind::Modified
+            };
+            statuses.push(FileStatus {
+                kind,
+                path: self.display_path(&entry.path),
+                original_path: None,
+                staged: None,
+            });
+        }
+        Ok(statuses)
+    }
+
+    /// Lists files of the current revision (see `current_ref_id`).
+    fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError> {
+        let (workspace, repo) = self.load()?;
+        let Some(current) = Self::current_commit(&workspace, &repo)? else {
+            return Ok(vec![]);
+        };
+        let tree = current
+            .tree()
+            .map_err(|e| DriverError::from_library("jj-lib: tree", e))?;
+        Ok(tree
+            .entries()
+            .map(|(path, _)| self.display_path(&path))
+            .collect())
+    }
+
+    fn changed_files(
+        &self,
+        base: Option<&str>,
+        head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError> {
+        self.cli.changed_files(base, head)
+    }
+
+    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        self.cli.parent_ref_id()
+    }
+
+    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError> {
+        self.cli.parent_ref_name()
+    }
+
+    fn first_ancestor_ref_name(
+        &self,
+        limit: Option<NonZero<u64>>,
+    ) -> Result<Option<AncestorRef>, DriverError> {
+        self.cli.first_ancestor_ref_name(limit)
+    }
+
+    fn child_ref_ids(&self, max: Option<NonZero<u64>>) -> Result<Vec<HistoryRefId>, DriverError> {
+        self.cli.child_ref_ids(max)
+    }
+
+    fn ahead_behind(&self) -> Result<Option<AheadBehind>, DriverError> {
+        self.cli.ahead_behind()
+    }
+
+    fn operation(&self) -> Result<Option<RepoOperation>, DriverError> {
+        self.cli.operation()
+    }
+
+    /// Returns the backing store's "commit id" of the first parent of the working-copy commit.
+    fn current_ref_id(&self, dirty_ok: bool) -> Result<HistoryRefId, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        let (workspace, repo) = self.load()?;
+        Self::current_commit(&workspace, &repo)?
+            .map(|current| current.id().hex())
+            .ok_or(DriverError::EmptyHistory)
+    }
+
+    /// Returns the first name found, in the same order as [`jj::Repo`] does.
+    ///
+    /// Note: in a colocated repo, asking for git's branch or tag shells out to `git` (via
+    /// [`git::Repo`]), just as [`jj::Repo`] does.
+    fn current_ref_name(&self, dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        let (workspace, repo) = self.load()?;
+        let Some(current) = Self::current_commit(&workspace, &repo)? else {
+            return Ok(None);
+        };
+        if let Some(name) = Self::ref_names_of(&repo, current.id()).into_iter().next() {
+            return Ok(Some(name));
+        }
+        if self.root.join(jj::JJ_COLOCATED_GIT_DIR).exists() {
+            let git = git::Loader {}.new_driver(self.root.clone())?;
+            if let Some(git) = git {
+                if let Some(name) = git.current_ref_name(true /*dirty_ok*/)? {
+                    return Ok(Some(name));
+                }
+            }
+        }
+        Self::nearest_bookmark(&repo, current.id())
+    }
+}
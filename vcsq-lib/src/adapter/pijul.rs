@@ -0,0 +1,268 @@
+use crate::plexer::VcsBrand;
+use crate::repo::{
+    AheadBehind, AncestorRef, Driver, DriverError, FileStatus, FileStatusKind, HistoryRefId,
+    HistoryRefName, QueryDir, RepoOperation, Validator, VcsAvailable,
+};
+use std::collections::HashSet;
+use std::num::NonZero;
+use std::path::PathBuf;
+use std::process::Command;
+
+static VCS_BIN_NAME: &str = "pijul";
+
+/// Pijul's directory of repo metadata, relative to the repo root.
+const PIJUL_META_DIR: &str = ".pijul";
+
+/// Prefix `pijul channel` gives the current channel, eg: `* main`.
+const PIJUL_CHANNEL_CURRENT_PREFIX: &str = "* ";
+
+/// Prefix `pijul log --state` gives the state each change left the channel in.
+const PIJUL_LOG_STATE_PREFIX: &str = "State:";
+
+#[derive(Debug)]
+pub struct Repo {
+    /// Root of the repo (ie: the directory holding `PIJUL_META_DIR`).
+    root: QueryDir,
+}
+
+#[derive(Debug)]
+pub struct Loader
+where
+    Self: Sized;
+
+impl Validator for Loader {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Pijul
+    }
+
+    /// Looks for Pijul's metadata in `dir` or any of its ancestors, so this never needs to shell
+    /// out.
+    fn new_driver(&self, dir: QueryDir) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        // Relative paths (eg: `.`) lack the ancestors to search, so canonicalize first.
+        let dir = dir
+            .canonicalize()
+            .map_err(|e| DriverError::Directory(format!("{}: {}", dir.display(), e)))?;
+        let root = dir
+            .ancestors()
+            .find(|ancestor| ancestor.join(PIJUL_META_DIR).is_dir());
+        Ok(root.map(|root| {
+            let repo: Box<dyn Driver> = Box::from(Repo {
+                root: root.to_path_buf(),
+            });
+            repo
+        }))
+    }
+
+    fn check_health(&self) -> Result<VcsAvailable, DriverError> {
+        let mut cmd = Command::new(VCS_BIN_NAME);
+        cmd.arg("--version");
+        DriverError::expect_cmd_lossy("pijul cli: exec".to_string(), cmd.output())
+    }
+}
+
+impl Repo {
+    fn start_shellout(&self) -> Command {
+        let mut cmd = Command::new(VCS_BIN_NAME);
+        cmd.current_dir(self.root.clone());
+        cmd
+    }
+
+    fn pijul_dirty_files(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("diff").arg("--short").arg("--untracked");
+        cmd
+    }
+
+    fn pijul_tracked_files(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("ls");
+        cmd
+    }
+
+    fn pijul_channels(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("channel");
+        cmd
+    }
+
+    /// Logs (newest first) the states the newest `limit` changes left the current channel in.
+    fn pijul_states(&self, limit: NonZero<u64>) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("log")
+            .arg("--state")
+            .arg("--limit")
+            .arg(limit.to_string());
+        cmd
+    }
+
+    /// States of the current channel, newest first, and at most `limit` of them.
+    fn states(&self, limit: NonZero<u64>) -> Result<Vec<HistoryRefId>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.pijul_states(limit).output(),
+            0, /*min_lines*/
+            "pijul cli: exec",
+            None,
+        )?;
+        Ok(lines
+            .iter()
+            .filter_map(|line| line.strip_prefix(PIJUL_LOG_STATE_PREFIX))
+            .map(|state| state.trim().to_string())
+            .collect())
+    }
+}
+
+impl Driver for Repo {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Pijul
+    }
+
+    fn root(&self) -> Result<QueryDir, DriverError> {
+        Ok(self.root.clone())
+    }
+
+    /// Lists changes not yet recorded to the current channel (Pijul has no index, so nothing is
+    /// ever staged).
+    fn status(&self) -> Result<Vec<FileStatus>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.pijul_dirty_files().output(),
+            0, /*min_lines*/
+            "pijul cli: exec",
+            None,
+        )?;
+        // Each hunk of a change gets its own line, so a file can be listed more than once.
+        let mut seen = HashSet::new();
+        Ok(lines
+            .iter()
+            .filter_map(|line| parse_diff_short_line(line))
+            .filter(|status| seen.insert(status.path.clone()))
+            .collect())
+    }
+
+    /// Lists files tracked in the working copy (which includes files added, but not yet
+    /// recorded).
+    fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.pijul_tracked_files().output(),
+            0, /*min_lines*/
+            "pijul cli: exec",
+            None,
+        )?;
+        Ok(lines
+            .into_iter()
+            .map(PathBuf::from)
+            // directories are tracked too
+            .filter(|path| !self.root.join(path).is_dir())
+            .collect())
+    }
+
+    /// Always [`DriverError::Unsupported`]: Pijul states (see `current_ref_id`) are sets of
+    /// changes rather than snapshots of the tree, so there's no tree to compare them by.
+    fn changed_files(
+        &self,
+        _base: Option<&str>,
+        _head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError> {
+        Err(DriverError::Unsupported(
+            "pijul has no trees to compare states by".to_string(),
+        ))
+    }
+
+    /// Returns the state the current channel was in before its newest change was applied.
+    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        let mut states = self
+            .states(NonZero::new(2).expect("bug: 2 is non-zero"))?
+            .into_iter();
+        if states.next().is_none() {
+            return Err(DriverError::EmptyHistory);
+        }
+        states.next().ok_or(DriverError::NoParent)
+    }
+
+    /// Always `None` (once a parent is confirmed to exist): Pijul's names are channels, and
+    /// channels only ever name their newest state.
+    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError> {
+        self.parent_ref_id()?;
+        Ok(None)
+    }
+
+    /// Always `None`, for the same reason as `parent_ref_name`.
+    fn first_ancestor_ref_name(
+        &self,
+        _limit: Option<NonZero<u64>>,
+    ) -> Result<Option<AncestorRef>, DriverError> {
+        Ok(None)
+    }
+
+    /// Always empty: the current state is always the newest state of the current channel.
+    fn child_ref_ids(&self, _max: Option<NonZero<u64>>) -> Result<Vec<HistoryRefId>, DriverError> {
+        Ok(vec![])
+    }
+
+    /// Always `None`: Pijul doesn't track remote channels locally.
+    fn ahead_behind(&self) -> Result<Option<AheadBehind>, DriverError> {
+        Ok(None)
+    }
+
+    /// Always `None`: Pijul has no multi-step operations (conflicts are recorded like any other
+    /// change, and reported by `status()`).
+    fn operation(&self) -> Result<Option<RepoOperation>, DriverError> {
+        Ok(None)
+    }
+
+    /// Returns the state hash of the current channel.
+    ///
+    /// Unlike other brands' commit IDs, a Pijul state identifies the _set_ of changes applied to
+    /// a channel, regardless of the order they were applied in: two channels (or two clones) with
+    /// the same changes share a state, even if they were applied in different orders. There's
+    /// also no way to "check out" a past state; only to unrecord changes until it's reached.
+    fn current_ref_id(&self, dirty_ok: bool) -> Result<HistoryRefId, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        self.states(NonZero::new(1).expect("bug: 1 is non-zero"))?
+            .into_iter()
+            .next()
+            .ok_or(DriverError::EmptyHistory)
+    }
+
+    /// Returns the name of the current channel.
+    fn current_ref_name(&self, dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        let lines = DriverError::expect_cmd_lines(
+            self.pijul_channels().output(),
+            1, /*min_lines*/
+            "pijul cli: exec",
+            None,
+        )?;
+        Ok(lines.iter().find_map(|line| {
+            line.strip_prefix(PIJUL_CHANNEL_CURRENT_PREFIX)
+                .map(|channel| channel.trim().to_string())
+        }))
+    }
+}
+
+/// Parses one line of `pijul diff --short --untracked` output, eg: `M  path`.
+fn parse_diff_short_line(line: &str) -> Option<FileStatus> {
+    let (code, path) = line.split_once(char::is_whitespace)?;
+    let kind = match code {
+        "A" | "UD" => FileStatusKind::Added,
+        "D" => FileStatusKind::Deleted,
+        "MV" => FileStatusKind::Renamed,
+        "M" | "R" | "RZ" => FileStatusKind::Modified,
+        "SC" | "UC" => FileStatusKind::Conflicted,
+        "U" => FileStatusKind::Untracked,
+        _ => return None,
+    };
+    let path = path.trim_start();
+    if path.is_empty() {
+        return None;
+    }
+    Some(FileStatus {
+        kind,
+        path: PathBuf::from(path),
+        original_path: None,
+        staged: None,
+    })
+}
@@ -0,0 +1,401 @@
+use crate::plexer::VcsBrand;
+use crate::repo::{
+    may_be_in_repo, AheadBehind, AncestorRef, Driver, DriverError, FileStatus, FileStatusKind,
+    HistoryRefId, HistoryRefName, QueryDir, RepoOperation, Validator, VcsAvailable,
+};
+use std::num::NonZero;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+#[derive(Debug)]
+pub struct Repo {
+    /// Root of the repo (as reported by `sl root` when the repo was loaded).
+    root: QueryDir,
+}
+
+static VCS_BIN_NAME: &str = "sl";
+
+/// Node ID of Sapling's `null` commit, which `sl whereami` reports until the first commit is made.
+const SL_NULL_ID: &str = "0000000000000000000000000000000000000000";
+
+/// Revset of the first parent of the working copy's parent (ie: of the "current" commit).
+/// Note `p1()` never includes the null commit, so this is an empty set at the root of history.
+const SL_REVSET_PARENT: &str = "p1(.)";
+
+/// Revset of the children of the working copy's parent (ie: of the "current" commit).
+const SL_REVSET_CHILDREN: &str = "children(.)";
+
+/// Indentation `sl status --copies` prints before the origin of a copied file.
+const SL_STATUS_COPY_ORIGIN_INDENT: &str = "  ";
+
+/// Field separator (tab) for `SL_TEMPLATE_NODE_BOOKMARKS` and `SL_TEMPLATE_BOOKMARKS` output.
+const SL_TEMPLATE_SEP: char = '\t';
+
+/// Lines of the form `NODE<tab>BOOKMARK<tab>BOOKMARK`.
+const SL_TEMPLATE_NODE_BOOKMARKS: &str = "{node}{bookmarks % '\\t{bookmark}'}\\n";
+
+/// Lines of the form `ACTIVE<tab>BOOKMARK<tab>BOOKMARK`, where `ACTIVE` is empty unless the active
+/// bookmark is on the commit.
+const SL_TEMPLATE_BOOKMARKS: &str = "{activebookmark}{bookmarks % '\\t{bookmark}'}\\n";
+
+/// Sapling's directory of repo metadata, relative to the repo root.
+const SL_META_DIR: &str = ".sl";
+
+/// Files (relative to `SL_META_DIR`) Sapling leaves behind while an operation is in progress.
+///
+/// Order matters: rebases, histedits and grafts also record a merge state while they await
+/// conflict resolution.
+const SL_OPERATION_MARKERS: [(&str, RepoOperation); 5] = [
+    ("rebasestate", RepoOperation::Rebase),
+    ("histedit-state", RepoOperation::Histedit),
+    ("graftstate", RepoOperation::Graft),
+    ("merge/state", RepoOperation::Merge),
+    ("bisect.state", RepoOperation::Bisect),
+];
+
+fn start_vcs_shellout() -> Command {
+    let mut cmd = Command::new(VCS_BIN_NAME);
+    cmd.env("SL_AUTOMATION", "1");
+    cmd
+}
+
+#[derive(Debug)]
+pub struct Loader
+where
+    Self: Sized;
+
+impl Validator for Loader {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Sapling
+    }
+
+    fn new_driver(&self, dir: QueryDir) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        if !may_be_in_repo(&dir, SL_META_DIR) {
+            return Ok(None);
+        }
+        let mut cmd = start_vcs_shellout();
+        cmd.current_dir(dir).arg("root").stderr(Stdio::null());
+        let output = DriverError::unwrap_cmd_lossy("sl cli".to_string(), cmd.output())?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let root = DriverError::expect_cmd_line("sl cli", &output)?;
+        let repo: Box<dyn Driver> = Box::from(Repo {
+            root: PathBuf::from(root),
+        });
+        Ok(Some(repo))
+    }
+
+    fn check_health(&self) -> Result<VcsAvailable, DriverError> {
+        let mut cmd = start_vcs_shellout();
+        cmd.arg("--version");
+        DriverError::expect_cmd_lossy("sl cli: exec".to_string(), cmd.output())
+    }
+}
+
+impl Repo {
+    fn start_shellout(&self) -> Command {
+        let mut cmd = start_vcs_shellout();
+        cmd.current_dir(self.root.clone());
+        cmd
+    }
+
+    fn sl_dirty_files(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("status")
+            .arg("--modified")
+            .arg("--added")
+            .arg("--removed")
+            .arg("--deleted")
+            .arg("--unknown")
+            .arg("--copies");
+        cmd
+    }
+
+    fn sl_tracked_files(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("files");
+        cmd
+    }
+
+    /// Lists files `rev` changed relative to its first parent.
+    fn sl_files_of(&self, rev: &str) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("status")
+            .arg("--no-status")
+            .arg("--change")
+            .arg(rev);
+        cmd
+    }
+
+    fn sl_files_between(&self, base: &str, head: &str) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("status")
+            .arg("--no-status")
+            .arg("--rev")
+            .arg(base)
+            .arg("--rev")
+            .arg(head);
+        cmd
+    }
+
+    fn sl_current_id(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("whereami");
+        cmd
+    }
+
+    fn sl_log_template(&self, revset: &str, template: &str) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("log")
+            .arg("--rev")
+            .arg(revset)
+            .arg("--template")
+            .arg(template);
+        cmd
+    }
+
+    fn sl_first_parent_bookmarks(&self, limit: Option<NonZero<u64>>) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("log")
+            .arg("--follow-first")
+            .arg("--rev")
+            .arg(SL_REVSET_PARENT)
+            .arg("--template")
+            .arg(SL_TEMPLATE_NODE_BOOKMARKS);
+        if let Some(limit) = limit {
+            cmd.arg("--limit").arg(limit.to_string());
+        }
+        cmd
+    }
+
+    fn sl_children(&self, max: Option<NonZero<u64>>) -> Command {
+        let mut cmd = self.sl_log_template(SL_REVSET_CHILDREN, "{node}\\n");
+        if let Some(max) = max {
+            cmd.arg("--limit").arg(max.to_string());
+        }
+        cmd
+    }
+
+    /// Returns the Sapling bookmark for `revset` if available, preferring the active bookmark
+    /// when more than one points at it.
+    fn name_of(&self, revset: &str) -> Result<Option<HistoryRefName>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.sl_log_template(revset, SL_TEMPLATE_BOOKMARKS).output(),
+            0, /*min_lines*/
+            "sl cli: exec",
+            None,
+        )?;
+        let Some(line) = lines.into_iter().next() else {
+            return Ok(None);
+        };
+        let mut fields = line.split(SL_TEMPLATE_SEP);
+        let active = fields.next().filter(|active| !active.is_empty());
+        Ok(active
+            .or_else(|| fields.find(|bookmark| !bookmark.is_empty()))
+            .map(str::to_string))
+    }
+
+    /// Returns the full node ID of `revset`, or `empty_err` if the revset resolved to nothing.
+    fn node_of(&self, revset: &str, empty_err: DriverError) -> Result<HistoryRefId, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.sl_log_template(revset, "{node}\\n").output(),
+            1, /*min_lines*/
+            "sl cli: exec",
+            Some(empty_err),
+        )?;
+        Ok(lines
+            .into_iter()
+            .next()
+            .expect("bug: expect_cmd_lines should have enforced min_lines"))
+    }
+}
+
+impl Driver for Repo {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Sapling
+    }
+
+    fn root(&self) -> Result<QueryDir, DriverError> {
+        Ok(self.root.clone())
+    }
+
+    fn status(&self) -> Result<Vec<FileStatus>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.sl_dirty_files().output(),
+            0, /*min_lines*/
+            "sl cli: exec",
+            None,
+        )?;
+        let mut statuses: Vec<FileStatus> = vec![];
+        for ln in lines {
+            // With --copies, the origin of a copy is printed on the line after it, indented.
+            if let Some(origin) = ln.strip_prefix(SL_STATUS_COPY_ORIGIN_INDENT) {
+                if let Some(copy) = statuses.last_mut() {
+                    copy.kind = FileStatusKind::Copied;
+                    copy.original_path = Some(PathBuf::from(origin));
+                }
+                continue;
+            }
+            // first 2 chars are modification-indicators like "? " to indicate the file is
+            // untracked.
+            let kind = match ln.chars().next() {
+                Some('M') => FileStatusKind::Modified,
+                Some('A') => FileStatusKind::Added,
+                Some('R' | '!') => FileStatusKind::Deleted,
+                Some('?') => FileStatusKind::Untracked,
+                _ => continue,
+            };
+            statuses.push(FileStatus {
+                kind,
+                path: PathBuf::from(ln.chars().skip(2).collect::<String>()),
+                original_path: None,
+                staged: None,
+            });
+        }
+
+        // A copy whose origin was also removed is really a rename.
+        let renamed_from = statuses
+            .iter()
+            .filter(|status| status.kind == FileStatusKind::Copied)
+            .filter_map(|status| status.original_path.clone())
+            .filter(|origin| {
+                statuses
+                    .iter()
+                    .any(|status| status.kind == FileStatusKind::Deleted && status.path == *origin)
+            })
+            .collect::<Vec<QueryDir>>();
+        statuses.retain(|status| {
+            !(status.kind == FileStatusKind::Deleted && renamed_from.contains(&status.path))
+        });
+        for status in &mut statuses {
+            if status
+                .original_path
+                .as_ref()
+                .is_some_and(|origin| renamed_from.contains(origin))
+            {
+                status.kind = FileStatusKind::Renamed;
+            }
+        }
+        Ok(statuses)
+    }
+
+    /// Lists files tracked in the working copy (which includes files added, but not yet
+    /// committed).
+    fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.sl_tracked_files().output(),
+            0, /*min_lines*/
+            "sl cli: exec",
+            None,
+        )?;
+        Ok(lines.into_iter().map(PathBuf::from).collect())
+    }
+
+    fn changed_files(
+        &self,
+        base: Option<&str>,
+        head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError> {
+        let head = head.unwrap_or(".");
+        let mut cmd = match base {
+            Some(base) => self.sl_files_between(base, head),
+            None => self.sl_files_of(head),
+        };
+        let lines = DriverError::expect_cmd_lines(
+            cmd.output(),
+            0, /*min_lines*/
+            "sl cli: exec",
+            None,
+        )?;
+        Ok(lines.into_iter().map(PathBuf::from).collect())
+    }
+
+    /// Returns the first parent (`p1`) of the working copy's parent commit.
+    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        self.node_of(SL_REVSET_PARENT, DriverError::NoParent)
+    }
+
+    /// Returns the Sapling bookmark of the first parent if available.
+    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError> {
+        // Resolving the ID first ensures we error the same as `parent_ref_id` at the root of
+        // history, rather than reporting a name for an empty revset.
+        let parent_id = self.parent_ref_id()?;
+        self.name_of(&parent_id)
+    }
+
+    /// Returns the nearest Sapling bookmark on the first-parent history of the working copy's
+    /// parent commit.
+    fn first_ancestor_ref_name(
+        &self,
+        limit: Option<NonZero<u64>>,
+    ) -> Result<Option<AncestorRef>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.sl_first_parent_bookmarks(limit).output(),
+            0, /*min_lines*/
+            "sl cli: exec",
+            None,
+        )?;
+        for (steps_back, line) in (1..).zip(lines) {
+            let mut fields = line.split(SL_TEMPLATE_SEP);
+            let Some(node) = fields.next() else {
+                continue;
+            };
+            if let Some(name) = fields.next() {
+                return Ok(Some(AncestorRef {
+                    id: node.to_string(),
+                    name: name.to_string(),
+                    distance: NonZero::new(steps_back).expect("bug: walk starts at 1"),
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns children of the working copy's parent commit.
+    fn child_ref_ids(&self, max: Option<NonZero<u64>>) -> Result<Vec<HistoryRefId>, DriverError> {
+        DriverError::expect_cmd_lines(
+            self.sl_children(max).output(),
+            0, /*min_lines*/
+            "sl cli: exec",
+            None,
+        )
+    }
+
+    /// Always `None`: Sapling's remote bookmarks aren't tied to any local bookmark, so there's no
+    /// upstream to compare against.
+    fn ahead_behind(&self) -> Result<Option<AheadBehind>, DriverError> {
+        Ok(None)
+    }
+
+    fn operation(&self) -> Result<Option<RepoOperation>, DriverError> {
+        let meta_dir = self.root.join(SL_META_DIR);
+        Ok(SL_OPERATION_MARKERS
+            .iter()
+            .find(|(marker, _)| meta_dir.join(marker).exists())
+            .map(|(_, operation)| *operation))
+    }
+
+    fn current_ref_id(&self, dirty_ok: bool) -> Result<HistoryRefId, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        let output = DriverError::expect_cmd_lossy(
+            "sl cli: exec".to_string(),
+            self.sl_current_id().output(),
+        )?;
+        let current_id = DriverError::expect_cmd_line("sl cli: exec", &output)?;
+        if current_id == SL_NULL_ID {
+            return Err(DriverError::EmptyHistory);
+        }
+        Ok(current_id)
+    }
+
+    /// Returns the current Sapling bookmark if available.
+    fn current_ref_name(&self, dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        self.name_of(".")
+    }
+}
@@ -0,0 +1,391 @@
+use crate::plexer::VcsBrand;
+use crate::repo::{
+    may_be_in_repo, AheadBehind, AncestorRef, Driver, DriverError, FileStatus, FileStatusKind,
+    HistoryRefId, HistoryRefName, QueryDir, RepoOperation, Validator, VcsAvailable,
+};
+use std::num::NonZero;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+static VCS_BIN_NAME: &str = "svn";
+
+/// Subversion's directory of metadata, relative to the working copy's root.
+const SVN_META_DIR: &str = ".svn";
+
+/// Revision number of a repository that has no commits yet.
+const SVN_REVISION_EMPTY: &str = "0";
+
+/// Number of fixed-width status columns `svn status` and `svn diff --summarize` print before each
+/// path (including the column of whitespace that separates them).
+const SVN_STATUS_COLUMNS: usize = 8;
+
+/// Path components of the conventional repository layout that name a branch or tag, eg:
+/// `^/branches/NAME` or `^/project/tags/NAME`.
+const SVN_LAYOUT_NAMED_DIRS: [&str; 2] = ["branches", "tags"];
+
+/// Path component of the conventional repository layout for the mainline, eg: `^/trunk`.
+const SVN_LAYOUT_TRUNK: &str = "trunk";
+
+/// Prefix of `svn info --show-item relative-url` output.
+const SVN_RELATIVE_URL_PREFIX: &str = "^/";
+
+/// Prefix `svn log --quiet` gives each revision, eg: `r5 | author | date`.
+const SVN_LOG_REVISION_PREFIX: char = 'r';
+
+#[derive(Debug)]
+pub struct Repo {
+    /// Root of the working copy, as every other path `svn` prints is relative to where it's run.
+    root: QueryDir,
+}
+
+#[derive(Debug)]
+pub struct Loader
+where
+    Self: Sized;
+
+impl Validator for Loader {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Subversion
+    }
+
+    fn new_driver(&self, dir: QueryDir) -> Result<Option<Box<dyn Driver>>, DriverError> {
+        if !may_be_in_repo(&dir, SVN_META_DIR) {
+            return Ok(None);
+        }
+        let mut cmd = start_vcs_shellout();
+        cmd.current_dir(dir)
+            .arg("info")
+            .arg("--show-item")
+            .arg("wc-root")
+            .stderr(Stdio::null());
+        let output = DriverError::unwrap_cmd_lossy("svn cli".to_string(), cmd.output())?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let root = PathBuf::from(DriverError::expect_cmd_line("svn cli", &output)?);
+        let repo: Box<dyn Driver> = Box::from(Repo { root });
+        Ok(Some(repo))
+    }
+
+    fn check_health(&self) -> Result<VcsAvailable, DriverError> {
+        let mut cmd = start_vcs_shellout();
+        cmd.arg("--version").arg("--quiet");
+        DriverError::expect_cmd_lossy("svn cli: exec".to_string(), cmd.output())
+    }
+}
+
+fn start_vcs_shellout() -> Command {
+    let mut cmd = Command::new(VCS_BIN_NAME);
+    cmd.arg("--non-interactive");
+    cmd
+}
+
+impl Repo {
+    fn start_shellout(&self) -> Command {
+        let mut cmd = start_vcs_shellout();
+        cmd.current_dir(self.root.clone());
+        cmd
+    }
+
+    fn svn_info(&self, item: &str) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("info").arg("--show-item").arg(item);
+        cmd
+    }
+
+    fn svn_dirty_files(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("status");
+        cmd
+    }
+
+    fn svn_tracked_files(&self) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("list")
+            .arg("--recursive")
+            .arg("--revision")
+            .arg("BASE");
+        cmd
+    }
+
+    /// Lists files `rev` changed relative to the revision before it.
+    fn svn_files_of(&self, rev: &str) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("diff").arg("--summarize").arg("--change").arg(rev);
+        cmd
+    }
+
+    fn svn_files_between(&self, base: &str, head: &str) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("diff")
+            .arg("--summarize")
+            .arg("--revision")
+            .arg(format!("{base}:{head}"));
+        cmd
+    }
+
+    /// Lists revisions in `range` that touched the working copy's path, in the order `range` is
+    /// written (eg: `5:1` lists newest first).
+    fn svn_log_revisions(&self, range: &str, limit: Option<NonZero<u64>>) -> Command {
+        let mut cmd = self.start_shellout();
+        cmd.arg("log").arg("--quiet").arg("--revision").arg(range);
+        if let Some(limit) = limit {
+            cmd.arg("--limit").arg(limit.to_string());
+        }
+        cmd
+    }
+
+    /// Returns the single line `svn info --show-item ITEM` prints.
+    fn info_item(&self, item: &str) -> Result<String, DriverError> {
+        let output = DriverError::expect_cmd_lossy(
+            "svn cli: exec".to_string(),
+            self.svn_info(item).output(),
+        )?;
+        DriverError::expect_cmd_line("svn cli: exec", &output)
+    }
+
+    /// Revision the working copy is at, or [`DriverError::EmptyHistory`] if there's no commits
+    /// yet.
+    fn revision(&self) -> Result<u64, DriverError> {
+        let revision = self.info_item("revision")?;
+        if revision == SVN_REVISION_EMPTY {
+            return Err(DriverError::EmptyHistory);
+        }
+        revision
+            .parse::<u64>()
+            .map_err(|_| DriverError::UnexpectedOutput {
+                cmd: "svn cli: exec".to_string(),
+                stdout: revision,
+            })
+    }
+
+    /// Revisions in `range` that touched the working copy's path, in the order `range` is written.
+    fn log_revisions(
+        &self,
+        range: &str,
+        limit: Option<NonZero<u64>>,
+    ) -> Result<Vec<HistoryRefId>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.svn_log_revisions(range, limit).output(),
+            0, /*min_lines*/
+            "svn cli: exec",
+            None,
+        )?;
+        Ok(lines
+            .iter()
+            .filter_map(|line| line.strip_prefix(SVN_LOG_REVISION_PREFIX))
+            .filter_map(|line| line.split_whitespace().next())
+            .filter(|revision| revision.chars().all(|c| c.is_ascii_digit()))
+            .map(std::string::ToString::to_string)
+            .collect())
+    }
+}
+
+impl Driver for Repo {
+    fn brand(&self) -> VcsBrand {
+        VcsBrand::Subversion
+    }
+
+    fn root(&self) -> Result<QueryDir, DriverError> {
+        Ok(self.root.clone())
+    }
+
+    /// Lists local modifications of the working copy (Subversion has no index, so nothing is ever
+    /// staged).
+    fn status(&self) -> Result<Vec<FileStatus>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.svn_dirty_files().output(),
+            0, /*min_lines*/
+            "svn cli: exec",
+            None,
+        )?;
+        Ok(lines
+            .iter()
+            .filter_map(|ln| parse_status_line(ln))
+            .collect())
+    }
+
+    /// Lists files in the repository as of the working copy's revision.
+    fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError> {
+        let lines = DriverError::expect_cmd_lines(
+            self.svn_tracked_files().output(),
+            0, /*min_lines*/
+            "svn cli: exec",
+            None,
+        )?;
+        Ok(lines
+            .into_iter()
+            // directories are listed with a trailing slash
+            .filter(|entry| !entry.ends_with('/'))
+            .map(PathBuf::from)
+            .collect())
+    }
+
+    fn changed_files(
+        &self,
+        base: Option<&str>,
+        head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError> {
+        let head = match head {
+            Some(head) => head.to_string(),
+            None => match self.revision() {
+                Ok(revision) => revision.to_string(),
+                Err(DriverError::EmptyHistory) => return Ok(vec![]),
+                Err(err) => return Err(err),
+            },
+        };
+        let mut cmd = match base {
+            Some(base) => self.svn_files_between(base, &head),
+            None => self.svn_files_of(&head),
+        };
+        let lines = DriverError::expect_cmd_lines(
+            cmd.output(),
+            0, /*min_lines*/
+            "svn cli: exec",
+            None,
+        )?;
+        Ok(lines
+            .iter()
+            .filter_map(|ln| ln.get(SVN_STATUS_COLUMNS..))
+            .map(PathBuf::from)
+            .collect())
+    }
+
+    /// Returns the newest revision before the working copy's that touched its path (Subversion
+    /// history is linear, so there's only ever one parent).
+    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        let revision = self.revision()?;
+        if revision <= 1 {
+            return Err(DriverError::NoParent);
+        }
+        self.log_revisions(&format!("{}:1", revision - 1), NonZero::new(1))?
+            .into_iter()
+            .next()
+            .ok_or(DriverError::NoParent)
+    }
+
+    /// Always `None` (once a parent is confirmed to exist): Subversion branches and tags are
+    /// paths in the repository, rather than names attached to revisions.
+    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError> {
+        self.parent_ref_id()?;
+        Ok(None)
+    }
+
+    /// Always `None`, for the same reason as `parent_ref_name`.
+    fn first_ancestor_ref_name(
+        &self,
+        _limit: Option<NonZero<u64>>,
+    ) -> Result<Option<AncestorRef>, DriverError> {
+        Ok(None)
+    }
+
+    /// Returns the revisions after the working copy's that touched its path, oldest first and up
+    /// to `max` of them (so this queries the repository, which may be remote). Subversion history
+    /// is linear, so each is a descendant of the one before it.
+    fn child_ref_ids(&self, max: Option<NonZero<u64>>) -> Result<Vec<HistoryRefId>, DriverError> {
+        let revision = match self.revision() {
+            Ok(revision) => revision,
+            Err(DriverError::EmptyHistory) => 0,
+            Err(err) => return Err(err),
+        };
+        let mut cmd = self.svn_info("revision");
+        cmd.arg("--revision").arg("HEAD");
+        let output = DriverError::expect_cmd_lossy("svn cli: exec".to_string(), cmd.output())?;
+        let newest = DriverError::expect_cmd_line("svn cli: exec", &output)?;
+        if newest.parse::<u64>().is_ok_and(|newest| newest <= revision) {
+            return Ok(vec![]);
+        }
+        self.log_revisions(&format!("{}:HEAD", revision + 1), max)
+    }
+
+    /// Returns the oldest revision after the working copy's (see `child_ref_ids`): with linear
+    /// history, only that one is a direct child, so there's never more than one.
+    fn child_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        self.child_ref_ids(NonZero::new(1))?
+            .pop()
+            .ok_or(DriverError::NoChild)
+    }
+
+    /// Always `None`: Subversion doesn't keep any of the repository's state locally (comparing
+    /// against it takes network access, eg: `svn status --show-updates`).
+    fn ahead_behind(&self) -> Result<Option<AheadBehind>, DriverError> {
+        Ok(None)
+    }
+
+    /// Always `None`: Subversion has no multi-step operations (conflicts are reported per-file
+    /// by `status()` instead).
+    fn operation(&self) -> Result<Option<RepoOperation>, DriverError> {
+        Ok(None)
+    }
+
+    /// Returns the revision number of the working copy's root (note a working copy can contain
+    /// a mix of revisions, eg: right after a commit and before an `svn update`).
+    fn current_ref_id(&self, dirty_ok: bool) -> Result<HistoryRefId, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        Ok(self.revision()?.to_string())
+    }
+
+    /// Returns the branch or tag the working copy is a checkout of, assuming the conventional
+    /// `trunk`, `branches/NAME`, `tags/NAME` repository layout.
+    fn current_ref_name(&self, dirty_ok: bool) -> Result<Option<HistoryRefName>, DriverError> {
+        if !dirty_ok && !self.is_clean()? {
+            return Err(DriverError::RepoDirty);
+        }
+        let relative_url = self.info_item("relative-url")?;
+        Ok(layout_name(
+            relative_url
+                .strip_prefix(SVN_RELATIVE_URL_PREFIX)
+                .unwrap_or(&relative_url),
+        ))
+    }
+}
+
+/// Finds the branch or tag name in a repository path that follows the conventional layout (see
+/// `SVN_LAYOUT_NAMED_DIRS` and `SVN_LAYOUT_TRUNK`).
+fn layout_name(path: &str) -> Option<HistoryRefName> {
+    let mut components = path.split('/');
+    while let Some(component) = components.next() {
+        if component == SVN_LAYOUT_TRUNK {
+            return Some(component.to_string());
+        }
+        if SVN_LAYOUT_NAMED_DIRS.contains(&component) {
+            return components
+                .next()
+                .filter(|name| !name.is_empty())
+                .map(std::string::ToString::to_string);
+        }
+    }
+    None
+}
+
+/// Parses one line of `svn status` output, whose first seven columns describe (in order) the
+/// item's content, its properties, and then locking, history, switching, lock-token and
+/// tree-conflict states, eg: `M       path`.
+fn parse_status_line(line: &str) -> Option<FileStatus> {
+    let columns = line.get(..SVN_STATUS_COLUMNS)?.as_bytes();
+    // Other lines (eg: summaries of conflicts, or tree-conflict descriptions) aren't aligned
+    // to these columns.
+    if columns[SVN_STATUS_COLUMNS - 1] != b' ' {
+        return None;
+    }
+    let kind = match (columns[0], columns[1], columns[6]) {
+        (b'C', _, _) | (_, b'C', _) | (_, _, b'C') => FileStatusKind::Conflicted,
+        (b'A', _, _) => FileStatusKind::Added,
+        (b'D' | b'!', _, _) => FileStatusKind::Deleted,
+        (b'M' | b'R' | b'~', _, _) | (b' ', b'M', _) => FileStatusKind::Modified,
+        (b'?', _, _) => FileStatusKind::Untracked,
+        _ => return None,
+    };
+    let path = line.get(SVN_STATUS_COLUMNS..)?;
+    if path.is_empty() {
+        return None;
+    }
+    Some(FileStatus {
+        kind,
+        path: PathBuf::from(path),
+        original_path: None,
+        staged: None,
+    })
+}
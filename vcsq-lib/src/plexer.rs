@@ -1,17 +1,278 @@
+use crate::adapter::bzr;
+use crate::adapter::cvs;
+use crate::adapter::darcs;
+#[cfg(feature = "external")]
+use crate::adapter::external;
+use crate::adapter::fossil;
+#[cfg(not(feature = "native-git"))]
 use crate::adapter::git;
+#[cfg(feature = "native-git")]
+use crate::adapter::git_native as git;
+#[cfg(not(feature = "native-hg"))]
 use crate::adapter::hg;
+#[cfg(feature = "native-hg")]
+use crate::adapter::hg_native as hg;
+#[cfg(not(feature = "native-jj"))]
 use crate::adapter::jj;
+#[cfg(feature = "native-jj")]
+use crate::adapter::jj_native as jj;
+use crate::adapter::pijul;
+use crate::adapter::sapling;
+use crate::adapter::svn;
 use crate::repo;
 use crate::repo::{AncestorRef, Driver, DriverError, QueryDir, Validator, VcsAvailable};
+use std::cmp::Reverse;
 use std::num::NonZero;
-use strum::{AsRefStr, EnumIter, IntoEnumIterator};
 
 /// The particular brands of VCS this library supports.
-#[derive(Debug, Clone, EnumIter, AsRefStr, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum VcsBrand {
     Git,
     Mercurial,
     Jujutsu,
+    Subversion,
+    Fossil,
+    Pijul,
+    Bazaar,
+    Darcs,
+    Cvs,
+    Sapling,
+
+    /// A brand this library doesn't ship an adapter for, by the name its (downstream) adapter
+    /// gives it (see [`Registry::register`]).
+    Other(String),
+}
+
+impl AsRef<str> for VcsBrand {
+    fn as_ref(&self) -> &str {
+        match self {
+            VcsBrand::Git => "Git",
+            VcsBrand::Mercurial => "Mercurial",
+            VcsBrand::Jujutsu => "Jujutsu",
+            VcsBrand::Subversion => "Subversion",
+            VcsBrand::Fossil => "Fossil",
+            VcsBrand::Pijul => "Pijul",
+            VcsBrand::Bazaar => "Bazaar",
+            VcsBrand::Darcs => "Darcs",
+            VcsBrand::Cvs => "Cvs",
+            VcsBrand::Sapling => "Sapling",
+            VcsBrand::Other(name) => name,
+        }
+    }
+}
+
+/// Serializes as the brand's name (eg: `"Git"`), including for [`VcsBrand::Other`].
+#[cfg(feature = "serde")]
+impl serde::Serialize for VcsBrand {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_ref())
+    }
+}
+
+/// Brands whose repos can share their root with a git repo they keep their history in (eg: `jj git
+/// init --colocate`).
+const COLOCATED_ATOP_GIT: [VcsBrand; 1] = [VcsBrand::Jujutsu];
+
+/// The VCS adapters to try a directory against, in order, so downstream crates can add their own
+/// brands (or replace a built-in one) without forking this library.
+#[derive(Debug, Default)]
+pub struct Registry {
+    validators: Vec<Box<dyn Validator>>,
+    /// Adapters only tried if none of `validators` claim a directory.
+    fallbacks: Vec<Box<dyn Validator>>,
+}
+
+impl Registry {
+    /// Registry without any adapters, eg: to only try a downstream crate's own.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry of every adapter this library ships (the in-process one, for brands whose
+    /// native-* feature is enabled), in [`VcsBrand`]'s order.
+    #[must_use]
+    pub fn with_defaults() -> Self {
+        Self::new()
+            .register(Box::from(git::Loader {}))
+            .register(Box::from(hg::Loader {}))
+            .register(Box::from(jj::Loader {}))
+            .register(Box::from(svn::Loader {}))
+            .register(Box::from(fossil::Loader {}))
+            .register(Box::from(pijul::Loader {}))
+            .register(Box::from(bzr::Loader {}))
+            .register(Box::from(darcs::Loader {}))
+            .register(Box::from(cvs::Loader {}))
+            .register(Box::from(sapling::Loader {}))
+    }
+
+    /// Adds `validator` to those tried last, unless one of the same [`Validator::brand`] is
+    /// already registered, in which case `validator` takes its place.
+    #[must_use]
+    pub fn register(mut self, validator: Box<dyn Validator>) -> Self {
+        let brand = validator.brand();
+        match self.validators.iter_mut().find(|v| v.brand() == brand) {
+            Some(registered) => *registered = validator,
+            None => self.validators.push(validator),
+        }
+        self
+    }
+
+    /// Like [`Registry::register`], but `validator` is only tried if no adapter added via
+    /// `register` claims a directory (eg: for adapters too slow to ask about every directory).
+    ///
+    /// Only a fallback of the same brand is replaced: one added via `register` is left alone (and
+    /// still tried first).
+    #[must_use]
+    pub fn register_fallback(mut self, validator: Box<dyn Validator>) -> Self {
+        let brand = validator.brand();
+        match self.fallbacks.iter_mut().find(|v| v.brand() == brand) {
+            Some(registered) => *registered = validator,
+            None => self.fallbacks.push(validator),
+        }
+        self
+    }
+
+    /// Registers an adapter for each `vcsq-adapter-<brand>` executable on `$PATH` (see
+    /// [`external`]) as a fallback (see [`Registry::register_fallback`]), so they're only run
+    /// for directories none of the adapters already registered claim.
+    #[cfg(feature = "external")]
+    #[must_use]
+    pub fn with_external_adapters(self) -> Self {
+        external::discover()
+            .into_iter()
+            .fold(self, |registry, loader| {
+                registry.register_fallback(Box::from(loader))
+            })
+    }
+
+    /// Brands of the registered adapters, in the order they're tried.
+    #[must_use]
+    pub fn brands(&self) -> Vec<VcsBrand> {
+        self.all().map(|v| v.brand()).collect()
+    }
+
+    /// Every registered adapter, in the order they're tried.
+    fn all(&self) -> impl Iterator<Item = &Box<dyn Validator>> {
+        self.validators.iter().chain(self.fallbacks.iter())
+    }
+
+    /// Like [`Repo::new_driver`], but trying only this registry's adapters.
+    ///
+    /// # Errors
+    ///
+    /// See [`Repo::new_driver`].
+    pub fn new_driver(&self, dir: &QueryDir) -> Result<Repo, DriverError> {
+        // Brands whose VCS isn't even installed can't claim `dir`, so they're only an error if
+        // _none_ of our adapters' underlying CLIs are available.
+        let mut missing_binary: Option<DriverError> = None;
+        let mut any_checked = false;
+        // Likewise, one adapter failing (eg: a broken external one) mustn't stop the others
+        // claiming `dir`, so its error only matters if none of them do.
+        let mut failure: Option<DriverError> = None;
+
+        // Every brand is asked, as `dir` can be in several repos at once: nested ones (eg: a git
+        // repo vendored inside an hg repo) or colocated ones sharing a root (eg: jj atop git).
+        // Fallbacks though are only asked if nothing else claimed `dir`.
+        let mut claims: Vec<(VcsBrand, QueryDir, Box<dyn Driver>)> = vec![];
+        for tier in [&self.validators, &self.fallbacks] {
+            if !claims.is_empty() {
+                break;
+            }
+            for validator in tier {
+                match validator
+                    .new_driver(dir.clone())
+                    .and_then(|adapter| adapter.map(|a| a.root().map(|root| (root, a))).transpose())
+                {
+                    Ok(Some((root, adapter))) => {
+                        let root = root.canonicalize().unwrap_or(root);
+                        claims.push((validator.brand(), root, adapter));
+                    }
+                    Ok(None) => any_checked = true,
+                    Err(err @ DriverError::VcsBinaryMissing { .. }) => missing_binary = Some(err),
+                    Err(err) => {
+                        failure.get_or_insert(err);
+                    }
+                }
+            }
+        }
+
+        // The innermost repo is the one `dir` belongs to. Of brands colocated at its root, those
+        // that colocate atop git come first (as git is merely their store, so they're the one
+        // actually in use), and otherwise registry order decides. Only those brands though: others
+        // can merely read git repos (eg: `brz root` succeeds in a plain git repo).
+        claims.sort_by_key(|(brand, root, _)| {
+            (
+                Reverse(root.components().count()),
+                !COLOCATED_ATOP_GIT.contains(brand),
+            )
+        });
+        let mut claims = claims.into_iter();
+        let Some((brand, root, adapter)) = claims.next() else {
+            if let Some(err) = failure {
+                return Err(err);
+            }
+            return match missing_binary {
+                Some(err) if !any_checked => Err(err),
+                _ => Err(DriverError::NotARepository {
+                    tried: self
+                        .brands()
+                        .iter()
+                        .map(|b| b.as_ref().to_string())
+                        .collect(),
+                }),
+            };
+        };
+        let also = claims
+            .filter(|(_, other_root, _)| *other_root == root)
+            .map(|(other, _, _)| other)
+            .collect();
+        Ok(Repo {
+            brand,
+            also,
+            adapter,
+        })
+    }
+
+    /// Like [`Repo::open_as`], but with only this registry's adapters to choose from.
+    ///
+    /// # Errors
+    ///
+    /// See [`Repo::open_as`].
+    pub fn open_as(&self, dir: &QueryDir, brand: &VcsBrand) -> Result<Repo, DriverError> {
+        let Some(validator) = self.all().find(|v| v.brand() == *brand) else {
+            return Err(DriverError::UnknownBrand {
+                brand: brand.as_ref().to_string(),
+                registered: self
+                    .brands()
+                    .iter()
+                    .map(|b| b.as_ref().to_string())
+                    .collect(),
+            });
+        };
+        let adapter =
+            validator
+                .new_driver(dir.clone())?
+                .ok_or_else(|| DriverError::NotOfBrand {
+                    brand: brand.as_ref().to_string(),
+                })?;
+        Ok(Repo {
+            brand: brand.clone(),
+            also: vec![],
+            adapter,
+        })
+    }
+
+    /// Like [`check_health`], but reporting only on this registry's adapters.
+    #[must_use]
+    pub fn check_health(&self) -> Vec<VcsHealth> {
+        self.all()
+            .map(|validator| VcsHealth {
+                brand: validator.brand(),
+                health: validator.check_health(),
+            })
+            .collect()
+    }
 }
 
 /// Demultiplexes all available VCS adapters into one interface so you don't have to figure out which
@@ -19,6 +280,11 @@ pub enum VcsBrand {
 #[derive(Debug)]
 pub struct Repo {
     pub brand: VcsBrand,
+
+    /// Other brands whose repos are colocated at the same root (eg: `Git`, for a `Jujutsu` repo
+    /// colocated with git), in registry order. Repos merely enclosing this one aren't included.
+    pub also: Vec<VcsBrand>,
+
     adapter: Box<dyn Driver>,
 }
 
@@ -26,41 +292,34 @@ impl Repo {
     /// Inspects on-disk directory path `dir` to determine if its a VCS repo, and if it is then
     /// returns a Repo object that can answer further questions about said repo.
     ///
+    /// Where `dir` is in several repos, the innermost is the one answering (see
+    /// [`Repo::also`] for any others colocated with it).
+    ///
+    /// Only this library's own adapters are tried (see [`Registry::new_driver`] to try others).
+    ///
     /// # Errors
     ///
-    /// Returns a [`DriverError`] if either no VCS driver is present that recognizes the directory,
-    /// or if some critical error happened (like one of the drivers hit an access error to the
-    /// directory, or found something silly like the directory is actually a plain file).
+    /// Returns [`DriverError::NotARepository`] if no VCS driver is present that recognizes the
+    /// directory ([`DriverError::VcsBinaryMissing`] if none of the VCS whose metadata is found
+    /// there are even installed), or
+    /// another [`DriverError`] if no driver recognizes the directory and some critical error
+    /// happened (like one of the drivers hit an access error to the directory, or found something
+    /// silly like the directory is actually a plain file).
     pub fn new_driver(dir: &QueryDir) -> Result<Self, DriverError> {
-        // TODO: (feature) generically handle "vcs" being not in $PATH, out here in our plexer; if
-        // _none_ of our adapter's underlying CLIs are in our plexer, _then_ translate that to an
-        // error.
-        //    if let NotFound = e.kind() { ... }
-        //    https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.NotFound
-
-        for current_attempt in VcsBrand::iter() {
-            let loader: Box<dyn repo::Validator> = match &current_attempt {
-                VcsBrand::Git => Box::from(git::Loader {}),
-                VcsBrand::Mercurial => Box::from(hg::Loader {}),
-                VcsBrand::Jujutsu => Box::from(jj::Loader {}),
-            };
-            if let Some(adapter) = loader.new_driver(dir.clone())? {
-                return Ok(Self {
-                    brand: current_attempt.clone(),
-                    adapter,
-                });
-            }
-        }
+        Registry::with_defaults().new_driver(dir)
+    }
 
-        Err(format!(
-            "if dir is a VCS, it's of an unknown brand (tried these {:?}: {})",
-            VcsBrand::iter().len(),
-            VcsBrand::iter()
-                .map(|b| b.as_ref().to_string())
-                .collect::<Vec<String>>()
-                .join(", "),
-        )
-        .into())
+    /// Like [`Repo::new_driver`], but skipping detection to treat `dir` as a repo of `brand`
+    /// (eg: where several brands claim `dir`, or asking all of them is too slow). Other brands'
+    /// adapters are never asked, so [`Repo::also`] is always empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DriverError::NotOfBrand`] if `brand`'s adapter doesn't recognize `dir`,
+    /// [`DriverError::UnknownBrand`] if this library has no adapter for `brand`, or any other
+    /// [`DriverError`] that adapter hits (eg: [`DriverError::VcsBinaryMissing`]).
+    pub fn open_as(dir: &QueryDir, brand: &VcsBrand) -> Result<Self, DriverError> {
+        Registry::with_defaults().open_as(dir, brand)
     }
 }
 
@@ -74,29 +333,42 @@ pub struct VcsHealth {
     pub health: Result<VcsAvailable, DriverError>,
 }
 
-/// Returns all VCS drivers' health reports.
+/// Serializes as `{"brand": ..., "ok": ..., "output": ..., "error": ...}` where exactly one of
+/// `output` (the VCS's own report, eg: `--version` output) or `error` is non-null.
+#[cfg(feature = "serde")]
+impl serde::Serialize for VcsHealth {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut health = serializer.serialize_struct("VcsHealth", 4)?;
+        health.serialize_field("brand", &self.brand)?;
+        health.serialize_field("ok", &self.health.is_ok())?;
+        health.serialize_field(
+            "output",
+            &self.health.as_ref().ok().map(|h| h.stdout.trim_end()),
+        )?;
+        health.serialize_field(
+            "error",
+            &self.health.as_ref().err().map(ToString::to_string),
+        )?;
+        health.end()
+    }
+}
+
+/// Returns all of this library's VCS drivers' health reports.
 #[must_use]
 pub fn check_health() -> Vec<VcsHealth> {
-    VcsBrand::iter()
-        .map(|brand| match brand {
-            VcsBrand::Git => {
-                let validator: Box<dyn Validator> = Box::from(git::Loader {});
-                (brand, validator.check_health())
-            }
-            VcsBrand::Mercurial => {
-                let validator: Box<dyn Validator> = Box::from(hg::Loader {});
-                (brand, validator.check_health())
-            }
-            VcsBrand::Jujutsu => {
-                let validator: Box<dyn Validator> = Box::from(jj::Loader {});
-                (brand, validator.check_health())
-            }
-        })
-        .map(|(brand, health)| VcsHealth { brand, health })
-        .collect::<Vec<VcsHealth>>()
+    Registry::with_defaults().check_health()
 }
 
 impl Driver for Repo {
+    fn brand(&self) -> VcsBrand {
+        self.brand.clone()
+    }
+
+    fn snapshot(&self) -> Result<repo::RepoSnapshot, DriverError> {
+        self.adapter.snapshot()
+    }
+
     fn root(&self) -> Result<QueryDir, DriverError> {
         self.adapter.root()
     }
@@ -109,10 +381,22 @@ impl Driver for Repo {
         self.adapter.dirty_files(clean_ok)
     }
 
+    fn status(&self) -> Result<Vec<repo::FileStatus>, DriverError> {
+        self.adapter.status()
+    }
+
     fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError> {
         self.adapter.tracked_files()
     }
 
+    fn changed_files(
+        &self,
+        base: Option<&str>,
+        head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError> {
+        self.adapter.changed_files(base, head)
+    }
+
     fn parent_ref(&self) -> Result<repo::HistoryRef, DriverError> {
         self.adapter.parent_ref()
     }
@@ -132,6 +416,25 @@ impl Driver for Repo {
         self.adapter.first_ancestor_ref_name(limit)
     }
 
+    fn child_ref_ids(
+        &self,
+        max: Option<NonZero<u64>>,
+    ) -> Result<Vec<repo::HistoryRefId>, DriverError> {
+        self.adapter.child_ref_ids(max)
+    }
+
+    fn child_ref_id(&self) -> Result<repo::HistoryRefId, DriverError> {
+        self.adapter.child_ref_id()
+    }
+
+    fn ahead_behind(&self) -> Result<Option<repo::AheadBehind>, DriverError> {
+        self.adapter.ahead_behind()
+    }
+
+    fn operation(&self) -> Result<Option<repo::RepoOperation>, DriverError> {
+        self.adapter.operation()
+    }
+
     fn current_ref(&self, dirty_ok: bool) -> Result<repo::HistoryRef, DriverError> {
         self.adapter.current_ref(dirty_ok)
     }
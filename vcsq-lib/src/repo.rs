@@ -1,17 +1,35 @@
 //! Provides the traits any driver a particular brand of VCS must implement.
 use crate::cmd::{Utf8CmdOutput, Utf8CmdOutputLossy};
+use crate::plexer::VcsBrand;
 use std::convert::From;
 use std::num::NonZero;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Output;
 use thiserror::Error;
 
 /// The local repository a VCS query will center around.
 pub type QueryDir = PathBuf;
 
+/// Whether `dir` might be in a repo whose metadata lives in a `meta_name` entry (eg: `.hg`) of
+/// its root, ie: whether it's worth asking the VCS's (comparatively slow) CLI about `dir`.
+///
+/// Anything that isn't a directory is let through, so the CLI can report what's wrong with it.
+pub(crate) fn may_be_in_repo(dir: &Path, meta_name: &str) -> bool {
+    let Ok(dir) = dir.canonicalize() else {
+        return true;
+    };
+    !dir.is_dir()
+        || dir
+            .ancestors()
+            .any(|ancestor| ancestor.join(meta_name).exists())
+}
+
 pub const ERROR_REPO_NOT_CLEAN: &str = "repo not clean, references not hermetic";
 pub const ERROR_REPO_NOT_DIRTY: &str = "repo not dirty";
-pub const ERROR_REPO_NONEMPTY_OUTPUT: &str = "unexpectedly returned no lines";
+pub const ERROR_REPO_NO_PARENT: &str = "current ref has no parent (root of history)";
+pub const ERROR_REPO_NO_CHILD: &str = "current ref has no children";
+pub const ERROR_REPO_MULTIPLE_CHILDREN: &str = "current ref has multiple children";
+pub const ERROR_REPO_EMPTY_HISTORY: &str = "repo has no history yet";
 
 #[derive(Error, Debug)]
 pub enum DriverError {
@@ -27,20 +45,106 @@ pub enum DriverError {
         source: std::io::Error,
     },
 
+    /// VCS binary couldn't be found (eg: it's not installed, or not in `$PATH`).
+    #[error("vcs binary missing: {:?}: {:?}", .context, .source)]
+    VcsBinaryMissing {
+        context: String,
+        source: std::io::Error,
+    },
+
     /// VCS binary failed and printed an error message
     #[error("vcs stderr: {:?}: {:?}", .context, .stderr)]
     Stderr { context: String, stderr: String },
 
+    /// VCS binary succeeded, but printed something we don't know how to interpret.
+    #[error("vcs output unexpected: {:?}: {:?}", .cmd, .stdout)]
+    UnexpectedOutput { cmd: String, stdout: String },
+
+    /// A VCS library (used in place of the VCS binary, by drivers that can) failed.
+    #[error("vcs library call failed: {:?}: {}", .context, .source)]
+    Library {
+        context: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
     /// An error occurred reading the directory name
     #[error("vcs returned a problematic root name")]
     RootName(#[from] std::string::FromUtf8Error),
 
-    /// An unknown error occurred
-    #[error("{0}")]
-    Unknown(String),
+    /// Directory isn't a repo of any of the brands that were tried.
+    #[error(
+        "if dir is a VCS, it's of an unknown brand (tried these {}: {})",
+        .tried.len(),
+        .tried.join(", ")
+    )]
+    NotARepository { tried: Vec<String> },
+
+    /// Directory was opened as a repo of a particular brand (see `plexer::Repo::open_as`), but
+    /// that brand's adapter doesn't recognize it.
+    #[error("dir is not a repo of the brand it was opened as: {brand}")]
+    NotOfBrand { brand: String },
+
+    /// Directory was opened as a repo of a brand no adapter is registered for.
+    #[error(
+        "no adapter for brand {brand} (registered brands: {})",
+        .registered.join(", ")
+    )]
+    UnknownBrand {
+        brand: String,
+        registered: Vec<String>,
+    },
+
+    /// Repo is dirty, so an answer about its current state wouldn't be hermetic.
+    #[error("{}", ERROR_REPO_NOT_CLEAN)]
+    RepoDirty,
+
+    /// Repo was expected to be dirty, but wasn't.
+    #[error("{}", ERROR_REPO_NOT_DIRTY)]
+    RepoNotDirty,
+
+    /// Repo doesn't have any history yet (eg: a freshly initialized git repo).
+    #[error("{}", ERROR_REPO_EMPTY_HISTORY)]
+    EmptyHistory,
+
+    /// Current point in history is the root of history, so has no parent.
+    #[error("{}", ERROR_REPO_NO_PARENT)]
+    NoParent,
+
+    /// Current point in history has no descendants.
+    #[error("{}", ERROR_REPO_NO_CHILD)]
+    NoChild,
+
+    /// Current point in history has more than one descendant, where one was expected.
+    #[error("{}", ERROR_REPO_MULTIPLE_CHILDREN)]
+    MultipleChildren,
+
+    /// VCS's model of history has no equivalent of what was asked (eg: a range of commits, in a
+    /// VCS that has no commits), so there's no answer to give.
+    #[error("unsupported by this brand of vcs: {0}")]
+    Unsupported(String),
 }
 
 impl DriverError {
+    /// Translates a failure to even run a VCS binary, distinguishing the binary being missing.
+    fn from_spawn(context: String, source: std::io::Error) -> Self {
+        if source.kind() == std::io::ErrorKind::NotFound {
+            Self::VcsBinaryMissing { context, source }
+        } else {
+            Self::Command { context, source }
+        }
+    }
+
+    /// Wraps an error from a VCS library, with `context` on what was being asked of it.
+    pub fn from_library(
+        context: &str,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        Self::Library {
+            context: context.to_string(),
+            source: Box::new(source),
+        }
+    }
+
     /// Low-level unwrapping of a command that's strict about its expectations that the
     /// underlying CLI produces valid utf8 content.
     ///
@@ -55,7 +159,7 @@ impl DriverError {
         context: String,
         cmd_output: std::io::Result<Output>,
     ) -> Result<Utf8CmdOutput, Self> {
-        let output = cmd_output.map_err(|e| Self::Command { context, source: e })?;
+        let output = cmd_output.map_err(|e| Self::from_spawn(context, e))?;
         Ok(Utf8CmdOutput::from(output))
     }
 
@@ -76,12 +180,12 @@ impl DriverError {
         if !utf8_output.status.success() {
             return Err(Self::Stderr {
                 context,
-                stderr: utf8_output.stderr.map_err(|e| {
+                stderr: utf8_output.stderr.unwrap_or_else(|e| {
                     format!(
                         "bad utf8 from stderr: {}; lossy conversion: {}",
                         e, utf8_output.stderr_lossy
                     )
-                })?,
+                }),
             });
         }
         Ok(utf8_output)
@@ -101,7 +205,7 @@ impl DriverError {
         context: String,
         cmd_output: std::io::Result<Output>,
     ) -> Result<Utf8CmdOutputLossy, Self> {
-        let output = cmd_output.map_err(|e| Self::Command { context, source: e })?;
+        let output = cmd_output.map_err(|e| Self::from_spawn(context, e))?;
         Ok(Utf8CmdOutputLossy::from(output))
     }
 
@@ -143,18 +247,16 @@ impl DriverError {
     // internally).
     pub fn expect_cmd_line(context: &str, output: &Utf8CmdOutputLossy) -> Result<String, Self> {
         let lines = output.stdout_strings();
-        if lines.len() > 1 {
-            return Err(Self::Unknown(format!(
-                "unexpectedly got multiple ({}) lines: {}:\n'''\n{:?}\n'''\n'''",
-                lines.len(),
-                context,
-                lines
-            )));
+        if lines.len() != 1 {
+            return Err(Self::UnexpectedOutput {
+                cmd: context.to_string(),
+                stdout: output.stdout.clone(),
+            });
         }
         Ok(lines
             .last()
-            .ok_or_else(|| Self::Unknown(format!("unexpectedly returned empty output: {context}")))?
-            .to_string())
+            .expect("bug: just checked there's exactly one line")
+            .clone())
     }
 
     /// Like `expect_cmd_line(...)`  but might expect lines depending on `min_lines`, and doesn't
@@ -162,32 +264,26 @@ impl DriverError {
     ///
     /// # Errors
     ///
-    /// Returns [`DriverError`] if `output` had less than `min_lines` to stdout.
+    /// Returns [`DriverError`] if `output` had less than `min_lines` to stdout: either
+    /// `too_few_err` if given, or [`DriverError::UnexpectedOutput`] otherwise.
     // TODO: (rust) idiomatic API is probably Iter<> of String, not Vec? Try to fix that here
     pub fn expect_cmd_lines(
         output: std::io::Result<Output>,
         min_lines: u8,
         context: &str,
-        expect_msg: Option<String>,
+        too_few_err: Option<DriverError>,
     ) -> Result<Vec<String>, Self> {
         let lines = Self::expect_cmd_lossy(context.to_string(), output)?.stdout_strings();
         if lines.len() < min_lines.into() {
-            return Err(Self::Unknown(format!(
-                "{}: {}",
-                context,
-                expect_msg.unwrap_or(ERROR_REPO_NONEMPTY_OUTPUT.to_string()),
-            )));
+            return Err(too_few_err.unwrap_or_else(|| Self::UnexpectedOutput {
+                cmd: context.to_string(),
+                stdout: lines.join("\n"),
+            }));
         }
         Ok(lines)
     }
 }
 
-impl From<String> for DriverError {
-    fn from(item: String) -> Self {
-        DriverError::Unknown(item)
-    }
-}
-
 /// VCS repo's canonical, machine-generated identifier describing a reference-point in its history
 /// (eg: branch or tag in git, bookmark in jj).
 ///
@@ -201,6 +297,7 @@ pub type HistoryRefId = String;
 pub type HistoryRefName = String;
 
 /// Single point in time in the Repo's history.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct HistoryRef {
     /// VCS's canonical identifier for this point in the repo's history.
     pub id: HistoryRefId,
@@ -213,14 +310,214 @@ pub struct HistoryRef {
     pub dirty: bool,
 }
 
+/// Nearest point in the Repo's (first-parent) history that has a human-made name, as found by
+/// walking back from the current point in history.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct AncestorRef {
+    /// VCS's canonical identifier for the named ancestor.
     pub id: HistoryRefId,
+
+    /// Hand-written, human-readable name of the ancestor.
     pub name: HistoryRefName,
 
     /// How far back of an ancestor is this (will always be 1 or more).
     pub distance: NonZero<u64>,
 }
 
+/// The kind of uncommitted change a file has, as reported by [`Driver::status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FileStatusKind {
+    /// File's content (or type, eg: regular file to symlink) was changed.
+    Modified,
+
+    /// File is newly tracked.
+    Added,
+
+    /// File was removed from tracking, or is missing from disk.
+    Deleted,
+
+    /// File was moved from [`FileStatus::original_path`].
+    Renamed,
+
+    /// File was copied from [`FileStatus::original_path`].
+    Copied,
+
+    /// File exists on disk, but the VCS isn't tracking it (and isn't ignoring it).
+    Untracked,
+
+    /// File has merge conflicts that have yet to be resolved.
+    Conflicted,
+}
+
+impl FileStatusKind {
+    /// Single-character, brand-neutral code for this kind of change.
+    #[must_use]
+    pub fn code(&self) -> char {
+        match self {
+            FileStatusKind::Modified => 'M',
+            FileStatusKind::Added => 'A',
+            FileStatusKind::Deleted => 'D',
+            FileStatusKind::Renamed => 'R',
+            FileStatusKind::Copied => 'C',
+            FileStatusKind::Untracked => '?',
+            FileStatusKind::Conflicted => 'U',
+        }
+    }
+}
+
+/// One uncommitted change to one file.
+///
+/// For brands that have an index (eg: git's staging area) the same path can be reported twice:
+/// once for its staged change and once for its unstaged change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileStatus {
+    pub kind: FileStatusKind,
+
+    /// Path of the file, relative to the repo (its new path, in the case of a rename or copy).
+    pub path: QueryDir,
+
+    /// For renames and copies only: the path the file originated from.
+    pub original_path: Option<QueryDir>,
+
+    /// Whether this change is staged in the VCS's index, or `None` if the brand has no index.
+    pub staged: Option<bool>,
+}
+
+/// How far the current point in history has diverged from the upstream it tracks (eg: a git
+/// branch's configured remote branch).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AheadBehind {
+    /// Number of commits in the current history that the upstream doesn't have.
+    pub ahead: u64,
+
+    /// Number of commits in the upstream's history that the current history doesn't have.
+    pub behind: u64,
+}
+
+/// Multi-step operation a repo can be left in the middle of (eg: awaiting conflict resolution).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RepoOperation {
+    Merge,
+    Rebase,
+    CherryPick,
+    Revert,
+    Bisect,
+    Graft,
+    Histedit,
+}
+
+impl RepoOperation {
+    /// Short, brand-neutral name of this operation.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            RepoOperation::Merge => "merge",
+            RepoOperation::Rebase => "rebase",
+            RepoOperation::CherryPick => "cherry-pick",
+            RepoOperation::Revert => "revert",
+            RepoOperation::Bisect => "bisect",
+            RepoOperation::Graft => "graft",
+            RepoOperation::Histedit => "histedit",
+        }
+    }
+}
+
+/// Everything about the repo's current state that's commonly asked about together, as gathered by
+/// [`Driver::snapshot`] in as few calls to the VCS as the brand allows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RepoSnapshot {
+    pub brand: VcsBrand,
+
+    /// Root dir of the repo (see [`Driver::root`]).
+    pub root: QueryDir,
+
+    /// VCS's canonical identifier for the current point in history, if there is one (see
+    /// [`Driver::current_ref_id`]).
+    pub id: SnapshotId,
+
+    /// Human-readable name of the current point in history, if there is one (see
+    /// [`Driver::current_ref_name`]).
+    pub name: Option<HistoryRefName>,
+
+    /// Every uncommitted change in the repo (see [`Driver::status`]).
+    pub status: Vec<FileStatus>,
+}
+
+/// What a [`RepoSnapshot`] found of the current point in history's ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SnapshotId {
+    Id(HistoryRefId),
+
+    /// Repo has no history yet (see [`DriverError::EmptyHistory`]).
+    EmptyHistory,
+
+    /// Repo's brand has no such identifier (see [`DriverError::Unsupported`]).
+    Unsupported(String),
+}
+
+impl SnapshotId {
+    /// The ID, if there is one.
+    #[must_use]
+    pub fn as_id(&self) -> Option<&HistoryRefId> {
+        match self {
+            SnapshotId::Id(id) => Some(id),
+            SnapshotId::EmptyHistory | SnapshotId::Unsupported(_) => None,
+        }
+    }
+}
+
+impl RepoSnapshot {
+    /// Whether repo was in a clean state.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.status.is_empty()
+    }
+
+    /// Unique paths of `status`, in the order they were first reported.
+    #[must_use]
+    pub fn dirty_files(&self) -> Vec<QueryDir> {
+        let mut dirty_files: Vec<QueryDir> = vec![];
+        for status in &self.status {
+            if !dirty_files.contains(&status.path) {
+                dirty_files.push(status.path.clone());
+            }
+        }
+        dirty_files
+    }
+
+    /// The VCS ref for the current point in history, as described by [`Driver::current_ref`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DriverError::RepoDirty`] if the repo was dirty (unless `dirty_ok`),
+    /// [`DriverError::EmptyHistory`] if there's no history yet, or [`DriverError::Unsupported`] if
+    /// the brand has no IDs.
+    pub fn current_ref(&self, dirty_ok: bool) -> Result<HistoryRef, DriverError> {
+        let dirty = !self.is_clean();
+        if !dirty_ok && dirty {
+            return Err(DriverError::RepoDirty);
+        }
+        Ok(HistoryRef {
+            id: match &self.id {
+                SnapshotId::Id(id) => id.clone(),
+                SnapshotId::EmptyHistory => return Err(DriverError::EmptyHistory),
+                SnapshotId::Unsupported(why) => return Err(DriverError::Unsupported(why.clone())),
+            },
+            name: self.name.clone(),
+            dirty,
+        })
+    }
+}
+
 /// Proof of underlying VCS's existence on the current system (typically --version output).
 pub type VcsAvailable = Utf8CmdOutputLossy;
 
@@ -230,6 +527,10 @@ pub trait Validator
 where
     Self: std::fmt::Debug,
 {
+    /// Brand of VCS this validator recognizes, which [`Driver::brand`] of any driver it returns
+    /// should match (see [`VcsBrand::Other`] for brands this library doesn't ship an adapter for).
+    fn brand(&self) -> VcsBrand;
+
     /// Inspects on-disk directory path `dir` to determine if its a VCS repo, and if it is then
     /// returns a Driver that can answer further questions about said repo.
     ///
@@ -257,6 +558,37 @@ pub trait Driver
 where
     Self: std::fmt::Debug,
 {
+    /// The brand of VCS this driver answers questions about.
+    fn brand(&self) -> VcsBrand;
+
+    /// Gathers the answers to `root()`, `current_ref_id()`, `current_ref_name()` and `status()`
+    /// all at once.
+    ///
+    /// The default implementation just calls each of those; drivers should override it if their
+    /// VCS can answer several of them in one call (and then answer those per-field methods from
+    /// that same call where it's no more expensive, as `current-id` and `current-name` ask them
+    /// directly).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DriverError`] if (eg) there was a problem accessing the repo, or the underlying
+    /// VCS APIs failed.
+    fn snapshot(&self) -> Result<RepoSnapshot, DriverError> {
+        let id = match self.current_ref_id(true /*dirty_ok*/) {
+            Ok(id) => SnapshotId::Id(id),
+            Err(DriverError::EmptyHistory) => SnapshotId::EmptyHistory,
+            Err(DriverError::Unsupported(why)) => SnapshotId::Unsupported(why),
+            Err(err) => return Err(err),
+        };
+        Ok(RepoSnapshot {
+            brand: self.brand(),
+            root: self.root()?,
+            id,
+            name: self.current_ref_name(true /*dirty_ok*/)?,
+            status: self.status()?,
+        })
+    }
+
     /// Prints the root dir of the repo.
     ///
     /// # Errors
@@ -280,12 +612,33 @@ where
     /// `clean_ok`) simply lists no output is the repo isn't dirty (thus can be used as a 1:1 proxy
     /// for `IsClean`'s behavior).
     ///
+    /// Thin wrapper for `status()` that just unpacks each (unique) path.
+    ///
     /// # Errors
     ///
     /// Returns [`DriverError`] if (eg) there was a problem accessing the repo, or the underlying
     /// VCS APIs failed. Will also return this error if repo wasn't even dirty (unless `clean_ok`
     /// in which case an empty vector will be returned).
-    fn dirty_files(&self, clean_ok: bool) -> Result<Vec<QueryDir>, DriverError>;
+    fn dirty_files(&self, clean_ok: bool) -> Result<Vec<QueryDir>, DriverError> {
+        let mut dirty_files: Vec<QueryDir> = vec![];
+        for status in self.status()? {
+            if !dirty_files.contains(&status.path) {
+                dirty_files.push(status.path);
+            }
+        }
+        if !clean_ok && dirty_files.is_empty() {
+            return Err(DriverError::RepoNotDirty);
+        }
+        Ok(dirty_files)
+    }
+
+    /// Lists every uncommitted change in the repo, or nothing if the repo is clean.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DriverError`] if (eg) there was a problem accessing the repo, or the underlying
+    /// VCS APIs failed.
+    fn status(&self) -> Result<Vec<FileStatus>, DriverError>;
 
     /// Lists filepaths tracked by this repo, ignoring the state of the repo edits (ie: any
     /// "staged" in git or deleted "working-copy" jj). The goal of this listing is to show the full
@@ -297,14 +650,41 @@ where
     /// VCS APIs failed.
     fn tracked_files(&self) -> Result<Vec<QueryDir>, DriverError>;
 
+    /// Lists filepaths changed between two points in history: `base` and `head`, each of which is
+    /// any revision the underlying VCS understands (eg: an ID from `current_ref_id()`, or a
+    /// tag/bookmark name).
+    ///
+    /// A `None` head is the current point in history, and a `None` base is the first parent of
+    /// `head` (so by default: the files touched by the current commit). When `head` is the root of
+    /// history and `base` is `None`, every file in `head` is listed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DriverError`] if (eg) there was a problem accessing the repo, or the underlying
+    /// VCS APIs failed (including the VCS not recognizing `base` or `head`).
+    fn changed_files(
+        &self,
+        base: Option<&str>,
+        head: Option<&str>,
+    ) -> Result<Vec<QueryDir>, DriverError>;
+
     /// Returns the historical reference of the direct ancestor of the current state.
     ///
+    /// When the current state is a merge, the "direct ancestor" is always the first parent (ie:
+    /// the one that was checked out when the merge was made). When the current state is the root
+    /// of history [`DriverError::NoParent`] is returned (or [`DriverError::EmptyHistory`] if
+    /// there's no history at all).
+    ///
     /// # Errors
     ///
     /// Returns [`DriverError`] if (eg) there was a problem accessing the repo, or the underlying
     /// VCS APIs failed.
     fn parent_ref(&self) -> Result<HistoryRef, DriverError> {
-        todo!(); // TODO: default implementation based on implementor's own impls of {parent_ref_id, parent_ref_name}
+        Ok(HistoryRef {
+            id: self.parent_ref_id()?,
+            name: self.parent_ref_name()?,
+            dirty: !self.is_clean()?,
+        })
     }
 
     /// Thin wrapper for `parent_ref` that just unpacks the ID.
@@ -313,9 +693,7 @@ where
     ///
     /// Returns [`DriverError`] if (eg) there was a problem accessing the repo, or the underlying
     /// VCS APIs failed.
-    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError> {
-        todo!(); // TODO: (feature) delete and implement in adaapters
-    }
+    fn parent_ref_id(&self) -> Result<HistoryRefId, DriverError>;
 
     /// Thin wrapper for `parent_ref()` that just unpacks the name if there is one.
     ///
@@ -323,25 +701,69 @@ where
     ///
     /// Returns [`DriverError`] if (eg) there was a problem accessing the repo, or the underlying
     /// VCS APIs failed.
-    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError> {
-        todo!(); // TODO: (feature) delete and implement in adaapters
-    }
+    fn parent_ref_name(&self) -> Result<Option<HistoryRefName>, DriverError>;
 
     /// Walks up the ancestor history and returns the first encountered ref that has a
     /// human-made name. None return indicates a name doesn't exist on any of the ancestor refs, or
     /// none were seen before `limit` was steps-back were taken in history.
     ///
+    /// Only first parents are followed (so a merge's second parent and its history are never
+    /// considered), and the walk starts at the parent of the current point in history (distance
+    /// 1). A `None` limit walks all the way to the root of history.
+    ///
     /// # Errors
     ///
     /// Returns [`DriverError`] if (eg) there was a problem accessing the repo, or the underlying
     /// VCS APIs failed.
     fn first_ancestor_ref_name(
         &self,
-        _limit: Option<NonZero<u64>>,
-    ) -> Result<Option<AncestorRef>, DriverError> {
-        todo!(); // TODO: (feature) delete and implement in adaapters
+        limit: Option<NonZero<u64>>,
+    ) -> Result<Option<AncestorRef>, DriverError>;
+
+    /// Lists the IDs of the direct descendants of the current state, up to `max` of them (or all
+    /// of them if `max` is `None`). An empty list indicates the current state has no children.
+    ///
+    /// Only descendants the VCS can see are listed (eg: in git, only commits reachable from some
+    /// ref), and all children of a merge are children of each of its parents.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DriverError`] if (eg) there was a problem accessing the repo, or the underlying
+    /// VCS APIs failed.
+    fn child_ref_ids(&self, max: Option<NonZero<u64>>) -> Result<Vec<HistoryRefId>, DriverError>;
+
+    /// Thin wrapper for `child_ref_ids()` that expects exactly one child to exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DriverError`] if (eg) there was a problem accessing the repo, or the underlying
+    /// VCS APIs failed. Also returns [`DriverError::NoChild`] or
+    /// [`DriverError::MultipleChildren`] if the current state doesn't have exactly one child.
+    fn child_ref_id(&self) -> Result<HistoryRefId, DriverError> {
+        let mut children = self.child_ref_ids(NonZero::new(2))?;
+        if children.len() > 1 {
+            return Err(DriverError::MultipleChildren);
+        }
+        children.pop().ok_or(DriverError::NoChild)
     }
 
+    /// How far the current point in history has diverged from its upstream, or `None` if there is
+    /// no upstream to compare against (eg: none is configured, or the brand has no such concept).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DriverError`] if (eg) there was a problem accessing the repo, or the underlying
+    /// VCS APIs failed.
+    fn ahead_behind(&self) -> Result<Option<AheadBehind>, DriverError>;
+
+    /// Which multi-step operation (eg: a rebase) the repo is in the middle of, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DriverError`] if (eg) there was a problem accessing the repo, or the underlying
+    /// VCS APIs failed.
+    fn operation(&self) -> Result<Option<RepoOperation>, DriverError>;
+
     /// Returns the VCS ref for the current point in history.
     ///
     /// # Errors
@@ -349,28 +771,27 @@ where
     /// Returns [`DriverError`] if (eg) there was a problem accessing the repo, or the underlying
     /// VCS APIs failed. Unless `dirty_ok`, then an error is returned when the repo is in a dirty
     /// state (as a reference for the current state is inherently not a reliable identifier).
+    /// Returns [`DriverError::EmptyHistory`] if no commits have been made yet.
     fn current_ref(&self, dirty_ok: bool) -> Result<HistoryRef, DriverError> {
-        let is_dirty = !self.is_clean()?;
-        if !dirty_ok && is_dirty {
-            return Err(ERROR_REPO_NOT_CLEAN.to_string().into());
-        }
-        Ok(HistoryRef {
-            id: self.current_ref_id(dirty_ok)?,
-            name: self.current_ref_name(dirty_ok)?,
-            dirty: is_dirty,
-        })
+        self.snapshot()?.current_ref(dirty_ok)
     }
 
-    /// Thin wrapper for `current_ref()` that just unpacks the name if there is one.
+    /// VCS's canonical identifier for the current point in history: the ID `current_ref()` (via
+    /// `snapshot()`) reports.
+    ///
+    /// Every brand reports a repo with no commits yet the same way, rather than with its own
+    /// placeholder ID (eg: Mercurial's `null` revision, or jj's `root()` commit).
     ///
     /// # Errors
     ///
     /// Returns [`DriverError`] if (eg) there was a problem accessing the repo, or the underlying
     /// VCS APIs failed. Unless `dirty_ok`, then an error is returned when the repo is in a dirty
     /// state (as a reference for the current state is inherently not a reliable identifier).
+    /// Returns [`DriverError::EmptyHistory`] if no commits have been made yet.
     fn current_ref_id(&self, _dirty_ok: bool) -> Result<HistoryRefId, DriverError>;
 
-    /// Thin wrapper for `current_ref()` that just unpacks the name if there is one.
+    /// Human-readable name of the current point in history, if there is one: the name
+    /// `current_ref()` (via `snapshot()`) reports.
     ///
     /// # Errors
     ///